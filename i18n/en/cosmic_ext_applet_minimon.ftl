@@ -110,3 +110,102 @@ graph-ring-unused = Unused.
 graph-cpu-load = Load.
 graph-cpu-idle = Idle.
 allocated-explanation = Allocated = total minus free. Includes system cache and buffers, which improve performance and are resized/released as needed.
+sensor-power = Power
+settings-power = Power draw
+power-title = Power Draw
+label-power = PWR
+power-total = Total (estimate)
+power-cpu = CPU
+power-gpu = GPU
+power-battery = Battery
+power-source = Show
+power-max = Chart maximum (W)
+power-max-adaptive = Adaptive
+power-explanation = CPU power is read from the RAPL energy counters, which may require read permission on /sys/class/powercap. The total is the battery discharge rate when on battery, otherwise CPU plus GPU.
+sensor-battery = Battery
+settings-battery = Battery
+battery-title = Battery
+label-battery = BAT
+battery-show-time = Show time remaining
+battery-health = Health (full vs design capacity)
+battery-to-empty = to empty
+battery-to-full = to full
+battery-state-unknown = Unknown
+battery-state-charging = Charging
+battery-state-discharging = Discharging
+battery-state-full = Full
+battery-state-not-charging = Not charging
+power-saving = Power saving
+profile-power-saver = Power saver
+profile-balanced = Balanced
+profile-performance = Performance
+profile-on-battery = On battery
+override-refresh-rate = Slowest refresh rate (0 = unchanged)
+override-suspend = Suspend costly sensors (NVIDIA)
+override-hide-gpu-charts = Hide GPU charts
+power-saving-explanation = Settings applied while the power profile is active or the laptop runs on battery. When both apply the most power saving setting wins.
+gpu-title-extended = Extended metrics
+gpu-show-extended = Show extended metrics
+gpu-memory-controller = Memory controller
+gpu-encoder = Encoder
+gpu-decoder = Decoder
+gpu-pcie = PCIe
+gpu-clocks = Core / memory clock
+gpu-throttle-reasons = Clocks limited by
+gpu-extended-unsupported = This GPU does not report extended metrics.
+throttle-none = Nothing
+throttle-idle = Idle
+throttle-app-clocks = Application clocks
+throttle-power-cap = Power cap
+throttle-thermal = Temperature
+throttle-hw-slowdown = Hardware slowdown
+throttle-sync-boost = Sync boost
+throttle-display-clock = Display clock
+gpu-media = Media engine
+gpu-hotspot-temperature = Hotspot temperature
+gpu-memory-temperature = Memory temperature
+gpu-fan = Fan
+gpu-shared-memory = Shared memory APU: {$vram} {$unit} VRAM carve-out plus {$gtt} {$unit} GTT system memory.
+settings-gpu-stack-gtt = Stacking also draws the GTT used on top of the VRAM
+gpu-temp-sensor = Sensor
+gpu-temp-edge = Edge
+gpu-temp-junction = Junction (hotspot)
+gpu-temp-memory = Memory
+gpu-temp-max = Hottest
+gpu-title-processes = Processes
+gpu-processes-none = No processes are using this GPU.
+gpu-processes-unsupported = The driver doesn't report per process usage.
+gpu-processes-suspended = Paused by the power saving settings.
+gpu-display-name = Name
+gpu-hide = Hide and stop polling
+gpu-order = Order
+gpu-state-active = Active
+gpu-state-suspended = Sleeping
+gpu-state-paused = Paused
+gpu-state-error = Not responding
+settings-canvas-charts = Draw charts directly instead of as images
+settings-chart-aspect = Chart width to height
+graph-type-gauge = Gauge
+graph-type-sparkline = Sparkline
+log-scale = Logarithmic scale
+sticky-peak = Let the scale fall slowly after a peak
+disks-bandwidth = Disk speed
+show-grid = Gridlines on line charts
+template = Value template
+template-placeholder = Built-in format
+template-help = Placeholders like {"{"}cpu:.1{"}"} show a value with a number of decimals: {$values}
+template-unclosed = Placeholder at {$pos} has no closing brace.
+template-unopened = Closing brace at {$pos} without an opening one, use {"}}"} for a brace.
+template-empty = Placeholder at {$pos} has no name.
+template-unknown = Unknown value {$name}.
+template-decimals = Expected decimals like .1 instead of {$spec}.
+settings-unit-system = Size units
+disks-use-bits = Show disk rates in bits
+colorpicker-gradient = Gradient fill
+colorpicker-middle-stop = Three colors
+colorpicker-stop-start = Bottom
+colorpicker-stop-middle = Middle
+colorpicker-stop-end = Top
+colorpicker-stop-position = Position
+settings-text-only = Text only, without charts, icons or labels
+colorpicker-gradient-unsupported = Gradient fills are only available for line, heat and gauge charts
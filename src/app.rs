@@ -9,7 +9,7 @@ use cosmic::iced::alignment::Horizontal::{self};
 use cosmic::iced::core::text::Wrapping;
 use cosmic::iced::program::graphics::text::cosmic_text::Attrs;
 
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::{fs, time};
 
 use cosmic::app::{Core, Task};
@@ -33,26 +33,31 @@ use cosmic::{
     },
 };
 
-use zbus::blocking::Connection;
-use zvariant::OwnedObjectPath;
-
 use log::{error, info};
 
 use crate::barchart::StackedBarSvg;
-use crate::colorpicker::ColorPicker;
+use crate::colorpicker::{ColorPicker, StopKind};
 use crate::config::{
-    ChartColors, ChartKind, ColorVariant, ContentType, DeviceKind, DisksVariant, GpuConfig,
-    NetworkVariant,
+    ChartAspect, ChartColors, ChartKind, ColorVariant, ContentType, DeviceKind, DisksVariant,
+    GpuConfig, GpuTempSensor, NetworkVariant, PowerCondition, PowerVariant, ProfileOverride,
+    UnitSystem,
 };
+use crate::hotplug::{self, DrmEvent};
+use crate::locale;
+use crate::sensors::battery::{Battery, BatteryStatus};
 use crate::sensors::cpu::Cpu;
 use crate::sensors::cputemp::CpuTemp;
 use crate::sensors::disks::{self, Disks};
-use crate::sensors::gpu::GpuType;
+use crate::sensors::gpu::{GpuPowerState, GpuType};
 use crate::sensors::gpus::{Gpu, Gpus};
 use crate::sensors::memory::Memory;
 use crate::sensors::network::{self, Network};
-use crate::sensors::{Sensor, TempUnit};
+use crate::sensors::power::Power;
+use crate::sensors::{DisplayOptions, Sensor, TempUnit};
 use crate::system_monitors;
+use crate::template;
+use crate::units::Quantity;
+use crate::upower;
 use crate::{config::MinimonConfig, fl};
 
 use cosmic::widget::Id as WId;
@@ -68,6 +73,8 @@ const RAM_ICON: &str = "io.github.cosmic_utils.minimon-applet-ram";
 const GPU_ICON: &str = "io.github.cosmic_utils.minimon-applet-gpu";
 const NETWORK_ICON: &str = "io.github.cosmic_utils.minimon-applet-network";
 const DISK_ICON: &str = "io.github.cosmic_utils.minimon-applet-harddisk";
+const POWER_ICON: &str = "io.github.cosmic_utils.minimon-applet-power";
+const BATTERY_ICON: &str = "io.github.cosmic_utils.minimon-applet-battery";
 
 const DEFAULT_MONITOR: &str = "COSMIC System Monitor";
 
@@ -83,6 +90,10 @@ pub static SETTINGS_DISKS_CHOICE: LazyLock<&'static str> =
     LazyLock::new(|| fl!("settings-disks").leak());
 pub static SETTINGS_GPU_CHOICE: LazyLock<&'static str> =
     LazyLock::new(|| fl!("settings-gpu").leak());
+pub static SETTINGS_POWER_CHOICE: LazyLock<&'static str> =
+    LazyLock::new(|| fl!("settings-power").leak());
+pub static SETTINGS_BATTERY_CHOICE: LazyLock<&'static str> =
+    LazyLock::new(|| fl!("settings-battery").leak());
 
 pub static SETTINGS_GENERAL_HEADING: LazyLock<&'static str> =
     LazyLock::new(|| fl!("settings-subpage-general").leak());
@@ -98,6 +109,10 @@ pub static SETTINGS_NETWORK_HEADING: LazyLock<&'static str> =
 pub static SETTINGS_DISKS_HEADING: LazyLock<&'static str> =
     LazyLock::new(|| fl!("disks-title").leak());
 pub static SETTINGS_GPU_HEADING: LazyLock<&'static str> = LazyLock::new(|| fl!("gpu-title").leak());
+pub static SETTINGS_POWER_HEADING: LazyLock<&'static str> =
+    LazyLock::new(|| fl!("power-title").leak());
+pub static SETTINGS_BATTERY_HEADING: LazyLock<&'static str> =
+    LazyLock::new(|| fl!("battery-title").leak());
 
 // The UI requires static lifetime of dropdown items
 pub static SYSMON_LIST: LazyLock<BTreeMap<String, system_monitors::DesktopApp>> =
@@ -106,6 +121,20 @@ pub static SYSMON_LIST: LazyLock<BTreeMap<String, system_monitors::DesktopApp>>
 pub static SYSMON_NAMES: LazyLock<Vec<&'static str>> =
     LazyLock::new(|| SYSMON_LIST.values().map(|app| app.name.as_str()).collect());
 
+// Same order as the usize conversion of PowerCondition
+pub static POWER_CONDITION_NAMES: LazyLock<[&'static str; 4]> = LazyLock::new(|| {
+    [
+        fl!("profile-power-saver").leak(),
+        fl!("profile-balanced").leak(),
+        fl!("profile-performance").leak(),
+        fl!("profile-on-battery").leak(),
+    ]
+});
+
+// Same order as the usize conversion of ChartAspect
+pub static CHART_ASPECT_NAMES: [&str; 3] = ["1:1", "2:1", "3:1"];
+pub static UNIT_SYSTEM_NAMES: [&str; 2] = ["MB (1000)", "MiB (1024)"];
+
 macro_rules! network_select {
     ($self:ident, $variant:expr) => {
         match $variant {
@@ -143,6 +172,8 @@ pub enum SettingsVariant {
     Network,
     Disks,
     Gpu(String),
+    Power,
+    Battery,
 }
 
 pub struct Minimon {
@@ -157,6 +188,8 @@ pub struct Minimon {
     disks1: Disks,
     disks2: Disks,
     gpus: Gpus,
+    power: Power,
+    battery: Battery,
 
     /// As the Nvidia runtime may be slow to load we trach number of retries
     nvidia_redetect_attempts: u8,
@@ -180,6 +213,11 @@ pub struct Minimon {
     is_laptop: bool,
     on_ac: bool,
 
+    // Active power-profiles-daemon profile
+    power_profile: PowerCondition,
+    // Which profile override the settings page is editing
+    override_edit: PowerCondition,
+
     // Tracks whether any chart or value is showing on the panel
     data_is_visible: bool,
 
@@ -194,6 +232,28 @@ pub struct Minimon {
     value_network_width: Option<f32>,
     value_disks_width: Option<f32>,
     value_w_width: Option<f32>,
+
+    // Parsed value templates of the panel items that have one, and their widths
+    templates: HashMap<TemplateKey, PanelTemplate>,
+}
+
+/// Panel items with a value template, GPUs by id
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum TemplateKey {
+    Cpu,
+    CpuTemp,
+    Memory,
+    Network,
+    Disks,
+    Power,
+    Battery,
+    Gpu(String),
+}
+
+// A template and the width of it rendered at its widest, so the value doesn't wobble
+struct PanelTemplate {
+    template: template::Template,
+    width: Option<f32>,
 }
 
 #[derive(Debug, Clone)]
@@ -216,6 +276,10 @@ pub enum Message {
     ColorPickerSliderBlueChanged(u8),
     ColorPickerSliderAlphaChanged(u8),
     ColorPickerSelectVariant(ColorVariant),
+    ColorPickerGradient(bool),
+    ColorPickerMiddleStop(bool),
+    ColorPickerSelectStop(StopKind),
+    ColorPickerStopOffset(u8),
 
     ColorTextInputRedChanged(String),
     ColorTextInputGreenChanged(String),
@@ -223,6 +287,7 @@ pub enum Message {
     ColorTextInputAlphaChanged(String),
 
     ToggleNetBytes(bool),
+    ToggleDisksBits(bool),
     ToggleNetCombined(bool),
     ToggleNetChart(NetworkVariant, bool),
     ToggleNetValue(NetworkVariant, bool),
@@ -231,16 +296,32 @@ pub enum Message {
     ToggleAdaptiveNet(NetworkVariant, bool),
     NetworkSelectUnit(NetworkVariant, usize),
     TextInputBandwidthChanged(NetworkVariant, String),
+    ToggleNetLogScale(NetworkVariant, bool),
+    ToggleNetStickyPeak(NetworkVariant, bool),
 
     ToggleDisksCombined(bool),
     ToggleDisksChart(DisksVariant, bool),
     ToggleDisksValue(DisksVariant, bool),
     ToggleDisksLabel(DisksVariant, bool),
     ToggleDisksIcon(DisksVariant, bool),
+    ToggleAdaptiveDisks(DisksVariant, bool),
+    DisksSelectUnit(DisksVariant, usize),
+    TextInputDisksBandwidthChanged(DisksVariant, String),
+    ToggleDisksLogScale(DisksVariant, bool),
+    ToggleDisksStickyPeak(DisksVariant, bool),
 
     SelectGraphType(DeviceKind, ChartKind),
+    ToggleGrid(DeviceKind, bool),
+    TemplateChanged(DeviceKind, String),
     Tick,
-    SlowTimer,
+    AcStateChanged(bool),
+    BatteryChanged(BatteryStatus),
+    GpuHotplug(DrmEvent),
+    PowerProfileChanged(PowerCondition),
+    SelectOverrideCondition(PowerCondition),
+    OverrideRefreshRateChanged(f64),
+    ToggleOverrideSuspend(bool),
+    ToggleOverrideHideGpuCharts(bool),
     PopupClosed(Id),
 
     ToggleCpuChart(bool),
@@ -260,12 +341,27 @@ pub enum Message {
     ToggleMemoryIcon(bool),
     ToggleMemoryPercentage(bool),
     ToggleMemoryAllocated(bool),
+    TogglePowerChart(bool),
+    TogglePowerValue(bool),
+    TogglePowerLabel(bool),
+    TogglePowerIcon(bool),
+    SelectPowerVariant(PowerVariant),
+    PowerMaxWattsChanged(u32),
+    ToggleBatteryChart(bool),
+    ToggleBatteryValue(bool),
+    ToggleBatteryLabel(bool),
+    ToggleBatteryIcon(bool),
+    ToggleBatteryTime(bool),
     ConfigChanged(Box<MinimonConfig>),
     ThemeChanged(Box<cosmic::config::CosmicTk>),
     LaunchSystemMonitor(&'static system_monitors::DesktopApp),
     RefreshRateChanged(f64),
     ValueSizeChanged(u16),
     ToggleMonospaceValues(bool),
+    ToggleCanvasCharts(bool),
+    ToggleTextOnly(bool),
+    ChartAspect(usize),
+    SelectUnitSystem(usize),
     PanelSpacing(u16),
     SelectCpuTempUnit(TempUnit),
     CpuTempMinTempChanged(f64),
@@ -277,8 +373,15 @@ pub enum Message {
     GpuToggleLabel(String, bool),
     GpuToggleIcon(String, bool),
     GpuToggleStackValues(String, bool),
+    GpuToggleExtended(String, bool),
+    GpuRename(String, String),
+    GpuToggleHidden(String, bool),
+    GpuChangeOrder(String, isize),
     GpuSelectGraphType(String, DeviceKind, ChartKind),
+    GpuToggleGrid(String, DeviceKind, bool),
+    GpuTemplateChanged(String, String),
     SelectGpuTempUnit(String, TempUnit),
+    SelectGpuTempSensor(String, GpuTempSensor),
     GpuTempMinTempChanged(String, f64),
     ToggleDisableOnBattery(String, bool),
     SysmonSelect(usize),
@@ -323,6 +426,8 @@ impl cosmic::Application for Minimon {
             disks1: Disks::default(),
             disks2: Disks::default(),
             gpus,
+            power: Power::new(),
+            battery: Battery::new(is_laptop),
             nvidia_redetect_attempts: 0,
             popup: None,
             settings_page: None,
@@ -331,6 +436,8 @@ impl cosmic::Application for Minimon {
             refresh_rate: Arc::new(AtomicU32::new(1000)),
             is_laptop,
             on_ac: true,
+            power_profile: PowerCondition::Balanced,
+            override_edit: PowerCondition::PowerSaver,
             data_is_visible: false,
             font_system: FontSystem::new(),
             interface_font: None,
@@ -339,6 +446,7 @@ impl cosmic::Application for Minimon {
             value_network_width: None,
             value_disks_width: None,
             value_w_width: None,
+            templates: HashMap::new(),
         };
 
         let config: MinimonConfig =
@@ -377,10 +485,6 @@ impl cosmic::Application for Minimon {
             iced::time::every(time::Duration::from_millis(u64::from(val)))
         }
 
-        fn slow_time_subscription() -> Subscription<time::Instant> {
-            iced::time::every(time::Duration::from_millis(3000))
-        }
-
         let mut subscriptions: Vec<Subscription<Message>> = vec![
             time_subscription(&self.refresh_rate).map(|_| Message::Tick),
             self.core
@@ -392,7 +496,14 @@ impl cosmic::Application for Minimon {
                 .map(|u| Message::ConfigChanged(Box::new(u.config))),
         ];
 
-        subscriptions.push(slow_time_subscription().map(|_| Message::SlowTimer));
+        if self.is_laptop {
+            subscriptions.push(upower::ac_state_subscription());
+        }
+        if let Some(path) = self.battery.upower_path() {
+            subscriptions.push(upower::battery_subscription(path.clone()));
+        }
+        subscriptions.push(upower::power_profile_subscription());
+        subscriptions.push(hotplug::drm_hotplug_subscription());
 
         subscriptions.push(
             self.core
@@ -426,6 +537,7 @@ impl cosmic::Application for Minimon {
         }
 
         // Build the full list of panel elements
+        let started = crate::render_cache::stats_enabled().then(std::time::Instant::now);
         let mut elements: Vec<Element<Message>> = Vec::new();
 
         // If the applet is not visible, return an icon button to toggle the popup
@@ -450,14 +562,27 @@ impl cosmic::Application for Minimon {
                         elements.extend(self.disks_panel_ui(horizontal));
                     }
                     ContentType::GpuInfo => {
-                        for gpu in self.gpus.values() {
+                        for (id, gpu) in self.ordered_gpus() {
+                            if self.config.gpus.get(id).is_some_and(|c| c.hidden) {
+                                continue;
+                            }
                             elements.extend(self.gpu_panel_ui(gpu, horizontal));
                         }
                     }
+                    ContentType::PowerDraw => {
+                        elements.extend(self.power_panel_ui(horizontal));
+                    }
+                    ContentType::Battery => {
+                        elements.extend(self.battery_panel_ui(horizontal));
+                    }
                 }
             }
         }
 
+        if let Some(started) = started {
+            crate::render_cache::record_view(started.elapsed());
+        }
+
         let spacing = match self.config.panel_spacing {
             1 => cosmic.space_xxxs(),
             2 => cosmic.space_xxs(),
@@ -597,6 +722,11 @@ impl cosmic::Application for Minimon {
                             widget::toggler(disks_variant == DisksVariant::Combined)
                                 .on_toggle(Message::ToggleDisksCombined),
                         ));
+                        content = content.push(settings::item(
+                            fl!("disks-use-bits"),
+                            widget::toggler(self.config.disks1.show_bits)
+                                .on_toggle(Message::ToggleDisksBits),
+                        ));
                         content = content.push(settings::item(
                             fl!("enable-label"),
                             widget::toggler(self.config.disks1.label_visible())
@@ -620,14 +750,26 @@ impl cosmic::Application for Minimon {
                         {
                             content = content.push(
                                 widget::row::with_capacity(2)
-                                    .push(text::heading(gpu.name()))
+                                    .push(text::heading(gpu.display_name(config)))
+                                    .push(text::body(gpu.power_state().to_string()))
                                     .spacing(cosmic::theme::spacing().space_m),
                             );
                             content = content.push(gpu.settings_ui(config));
+                            content = content
+                                .push(gpu.processes_ui(self.active_override().suspend_expensive));
                         } else {
                             error!("SettingsVariant::Gpu: Not found {id}");
                         }
                     }
+                    SettingsVariant::Power => {
+                        content = content.push(settings_sub_page_heading!(SETTINGS_POWER_HEADING));
+                        content = content.push(self.power.settings_ui());
+                    }
+                    SettingsVariant::Battery => {
+                        content =
+                            content.push(settings_sub_page_heading!(SETTINGS_BATTERY_HEADING));
+                        content = content.push(self.battery.settings_ui());
+                    }
                     SettingsVariant::General => {
                         content =
                             content.push(settings_sub_page_heading!(SETTINGS_GENERAL_HEADING));
@@ -649,14 +791,20 @@ impl cosmic::Application for Minimon {
 
                 let cpu = widget::text::body(self.cpu.to_string());
                 let cputemp = widget::text::body(self.cputemp.to_string());
+                let units = self.config.unit_system;
+                // Sizes in the conventions of the language, long numbers grouped
                 let memory = widget::text::body(format!(
-                    "{} / {:.1} GB / {:.1} GB",
+                    "{} / {} {giga} / {} {giga}",
                     self.memory.to_string(false),
-                    self.memory.latest_sample_allocated(),
-                    self.memory.total()
+                    locale::format_grouped(
+                        units.from_gib(self.memory.latest_sample_allocated()),
+                        1
+                    ),
+                    locale::format_grouped(units.from_gib(self.memory.total()), 1),
+                    giga = units.giga()
                 ));
 
-                let sample_rate_ms = self.config.refresh_rate;
+                let sample_rate_ms = self.effective_refresh_rate();
                 let network = widget::text::body(format!(
                     "↓ {} ↑ {}",
                     &self
@@ -715,25 +863,54 @@ impl cosmic::Application for Minimon {
                     ));
 
                 if self.has_gpus() {
-                    for (key, gpu) in self.gpus.iter() {
+                    for (key, gpu) in self.ordered_gpus() {
                         let temp = gpu.temp.to_string();
 
-                        let info = widget::text::body(format!(
-                            "{} {} / {:.2} GB {}",
-                            gpu.gpu,
-                            gpu.vram.string(false),
-                            gpu.vram.total(),
-                            temp
-                        ));
+                        // Dashes for a sleeping GPU would look like a failure, name the state
+                        let info = if gpu.power_state() == GpuPowerState::Active {
+                            let units = self.config.unit_system;
+                            widget::text::body(format!(
+                                "{} {} / {} {} {}",
+                                gpu.gpu,
+                                gpu.vram.string(false),
+                                locale::format_grouped(units.from_gib(gpu.vram.total()), 2),
+                                units.giga(),
+                                temp
+                            ))
+                        } else {
+                            widget::text::body(gpu.power_state().to_string())
+                        };
+
+                        let name = self
+                            .config
+                            .gpus
+                            .get(key)
+                            .map_or(*SETTINGS_GPU_CHOICE, |c| gpu.display_name(c));
 
                         sensor_settings = sensor_settings.add(Minimon::go_next_with_item(
-                            &SETTINGS_GPU_CHOICE,
+                            name,
                             info,
                             Message::Settings(Some(SettingsVariant::Gpu(key.clone()))),
                         ));
                     }
                 }
 
+                if self.power.is_found() {
+                    sensor_settings = sensor_settings.add(Minimon::go_next_with_item(
+                        &SETTINGS_POWER_CHOICE,
+                        widget::text::body(self.power.to_string(false)),
+                        Message::Settings(Some(SettingsVariant::Power)),
+                    ));
+                }
+
+                if self.battery.is_found() {
+                    sensor_settings = sensor_settings.add(Minimon::go_next_with_item(
+                        &SETTINGS_BATTERY_CHOICE,
+                        widget::text::body(self.battery.to_string(false)),
+                        Message::Settings(Some(SettingsVariant::Battery)),
+                    ));
+                }
+
                 content = content.push(sensor_settings);
             }
 
@@ -830,6 +1007,12 @@ impl cosmic::Application for Minimon {
                             error!("Id is None");
                         }
                     }
+                    DeviceKind::Power => {
+                        self.colorpicker.activate(device, self.power.demo_graph());
+                    }
+                    DeviceKind::Battery => {
+                        self.colorpicker.activate(device, self.battery.demo_graph());
+                    }
                 }
                 self.colorpicker.set_color_variant(ColorVariant::Background);
             }
@@ -887,6 +1070,24 @@ impl cosmic::Application for Minimon {
                 self.colorpicker.set_color_variant(variant);
             }
 
+            Message::ColorPickerGradient(enabled) => {
+                info!("Message::ColorPickerGradient({enabled})");
+                self.colorpicker.set_gradient(enabled);
+            }
+
+            Message::ColorPickerMiddleStop(enabled) => {
+                info!("Message::ColorPickerMiddleStop({enabled})");
+                self.colorpicker.set_middle_stop(enabled);
+            }
+
+            Message::ColorPickerSelectStop(stop) => {
+                self.colorpicker.select_stop(stop);
+            }
+
+            Message::ColorPickerStopOffset(offset) => {
+                self.colorpicker.set_stop_offset(offset);
+            }
+
             Message::ToggleNetBytes(toggle) => {
                 info!("Message::ToggleNetBytes({toggle})");
                 self.config.network1.show_bytes = toggle;
@@ -894,6 +1095,13 @@ impl cosmic::Application for Minimon {
                 self.save_config();
             }
 
+            Message::ToggleDisksBits(toggle) => {
+                info!("Message::ToggleDisksBits({toggle})");
+                self.config.disks1.show_bits = toggle;
+                self.config.disks2.show_bits = toggle;
+                self.save_config();
+            }
+
             Message::ToggleNetCombined(toggle) => {
                 info!("Message::ToggleNetCombined({toggle})");
                 if toggle.is_true() {
@@ -957,6 +1165,47 @@ impl cosmic::Application for Minimon {
                 self.save_config();
             }
 
+            Message::ToggleNetLogScale(variant, toggle) => {
+                info!("Message::ToggleNetLogScale({variant:?}, {toggle:?})");
+                let (_, config) = network_select!(self, variant);
+                config.log_scale = toggle;
+                self.save_config();
+            }
+
+            Message::ToggleNetStickyPeak(variant, toggle) => {
+                info!("Message::ToggleNetStickyPeak({variant:?}, {toggle:?})");
+                let (_, config) = network_select!(self, variant);
+                config.sticky_peak = toggle;
+                self.save_config();
+            }
+
+            Message::ToggleAdaptiveDisks(variant, toggle) => {
+                info!("Message::ToggleAdaptiveDisks({variant:?}, {toggle:?})");
+                let (_, config) = disks_select!(self, variant);
+                config.adaptive = toggle;
+                self.save_config();
+            }
+
+            Message::DisksSelectUnit(variant, unit) => {
+                let (_, config) = disks_select!(self, variant);
+                config.unit = Some(unit);
+                self.save_config();
+            }
+
+            Message::ToggleDisksLogScale(variant, toggle) => {
+                info!("Message::ToggleDisksLogScale({variant:?}, {toggle:?})");
+                let (_, config) = disks_select!(self, variant);
+                config.log_scale = toggle;
+                self.save_config();
+            }
+
+            Message::ToggleDisksStickyPeak(variant, toggle) => {
+                info!("Message::ToggleDisksStickyPeak({variant:?}, {toggle:?})");
+                let (_, config) = disks_select!(self, variant);
+                config.sticky_peak = toggle;
+                self.save_config();
+            }
+
             Message::SelectGraphType(dev, kind) => {
                 info!("Message::SelectGraphType({dev:?})");
                 match dev {
@@ -972,7 +1221,69 @@ impl cosmic::Application for Minimon {
                         self.memory.set_graph_kind(kind);
                         self.config.memory.chart = kind;
                     }
-                    _ => error!("Message::SelectGraphType unsupported kind/device combination."), // Disks and Network don't have graph selection
+                    DeviceKind::Power => {
+                        self.power.set_graph_kind(kind);
+                        self.config.power.chart = kind;
+                    }
+                    DeviceKind::Battery => {
+                        self.battery.set_graph_kind(kind);
+                        self.config.battery.chart = kind;
+                    }
+                    DeviceKind::Network(variant) => {
+                        let (network, config) = network_select!(self, variant);
+                        network.set_graph_kind(kind);
+                        config.chart = kind;
+                    }
+                    DeviceKind::Disks(variant) => {
+                        let (disks, config) = disks_select!(self, variant);
+                        disks.set_graph_kind(kind);
+                        config.chart = kind;
+                    }
+                    _ => error!("Message::SelectGraphType unsupported kind/device combination."), // GPUs use GpuSelectGraphType
+                }
+                self.save_config();
+            }
+
+            Message::ToggleGrid(dev, toggled) => {
+                info!("Message::ToggleGrid({dev:?}, {toggled:?})");
+                match dev {
+                    DeviceKind::Cpu => self.config.cpu.show_grid(toggled),
+                    DeviceKind::CpuTemp => self.config.cputemp.show_grid(toggled),
+                    DeviceKind::Memory => self.config.memory.show_grid(toggled),
+                    DeviceKind::Power => self.config.power.show_grid(toggled),
+                    DeviceKind::Battery => self.config.battery.show_grid(toggled),
+                    DeviceKind::Network(variant) => {
+                        let (_, config) = network_select!(self, variant);
+                        config.show_grid(toggled);
+                    }
+                    DeviceKind::Disks(variant) => {
+                        let (_, config) = disks_select!(self, variant);
+                        config.show_grid(toggled);
+                    }
+                    _ => error!("Message::ToggleGrid unsupported device, GPUs use GpuToggleGrid"),
+                }
+                self.save_config();
+            }
+
+            Message::TemplateChanged(dev, template) => {
+                info!("Message::TemplateChanged({dev:?}, {template:?})");
+                match dev {
+                    DeviceKind::Cpu => self.config.cpu.template = template,
+                    DeviceKind::CpuTemp => self.config.cputemp.template = template,
+                    DeviceKind::Memory => self.config.memory.template = template,
+                    DeviceKind::Power => self.config.power.template = template,
+                    DeviceKind::Battery => self.config.battery.template = template,
+                    DeviceKind::Network(variant) => {
+                        let (_, config) = network_select!(self, variant);
+                        config.template = template;
+                    }
+                    DeviceKind::Disks(variant) => {
+                        let (_, config) = disks_select!(self, variant);
+                        config.template = template;
+                    }
+                    _ => error!(
+                        "Message::TemplateChanged unsupported device, GPUs use GpuTemplateChanged"
+                    ),
                 }
                 self.save_config();
             }
@@ -991,33 +1302,97 @@ impl cosmic::Application for Minimon {
                 self.save_config();
             }
 
+            Message::TextInputDisksBandwidthChanged(variant, string) => {
+                let value = if string.is_empty() {
+                    Some(0)
+                } else {
+                    string.parse::<u64>().ok()
+                };
+
+                if let Some(val) = value {
+                    let (_, config) = disks_select!(self, variant);
+                    config.bandwidth = val;
+                }
+                self.save_config();
+            }
+
             Message::Tick => {
                 self.refresh_stats();
+                crate::render_cache::tick();
             }
 
-            Message::SlowTimer => {
-                if self.is_laptop {
-                    let current_on_ac = self.is_on_ac().unwrap_or(true);
-                    if self.on_ac != current_on_ac {
-                        self.on_ac = current_on_ac;
-
-                        for (id, gpu) in self.gpus.iter_mut() {
-                            if let Some(c) = self.config.gpus.get(id)
-                                && c.pause_on_battery
-                            {
-                                if current_on_ac {
-                                    info!("Changed to AC, restart polling");
-                                    gpu.restart(); // on AC, start polling
-                                } else {
-                                    info!("Changed to DC, stop polling");
-                                    gpu.stop(); // on battery, stop polling
-                                }
-                            }
-                        }
-                    }
+            Message::AcStateChanged(on_ac) => {
+                info!("Message::AcStateChanged({on_ac:?})");
+                if self.on_ac != on_ac {
+                    self.on_ac = on_ac;
+                    self.apply_power_policy();
+                }
+            }
+
+            Message::BatteryChanged(status) => {
+                info!("Message::BatteryChanged({:?})", status.state);
+                self.battery.set_status(status);
+            }
+
+            Message::GpuHotplug(event) => {
+                info!("Message::GpuHotplug({event:?})");
+                self.gpus.hotplug(&event, self.is_laptop);
+                self.sync_gpu_configs();
+
+                if let Some(SettingsVariant::Gpu(id)) = &self.settings_page
+                    && self.gpus.get(id).is_none()
+                {
+                    self.settings_page = None;
+                }
+
+                // New GPUs start out polling, pause them if the policy says so
+                if self.popup.is_none() {
+                    self.maybe_stop_gpus();
+                }
+            }
+
+            Message::PowerProfileChanged(profile) => {
+                info!("Message::PowerProfileChanged({profile:?})");
+                if self.power_profile != profile {
+                    self.power_profile = profile;
+                    self.apply_power_policy();
                 }
             }
 
+            Message::SelectOverrideCondition(condition) => {
+                info!("Message::SelectOverrideCondition({condition:?})");
+                self.override_edit = condition;
+            }
+
+            Message::OverrideRefreshRateChanged(rate) => {
+                info!("Message::OverrideRefreshRateChanged({rate:?})");
+                self.config
+                    .profile_overrides
+                    .get_mut(self.override_edit)
+                    .refresh_rate = (rate * 1000.0) as u32;
+                self.save_config();
+            }
+
+            Message::ToggleOverrideSuspend(toggled) => {
+                info!("Message::ToggleOverrideSuspend({toggled:?})");
+                self.config
+                    .profile_overrides
+                    .get_mut(self.override_edit)
+                    .suspend_expensive = toggled;
+                self.save_config();
+                self.apply_power_policy();
+            }
+
+            Message::ToggleOverrideHideGpuCharts(toggled) => {
+                info!("Message::ToggleOverrideHideGpuCharts({toggled:?})");
+                self.config
+                    .profile_overrides
+                    .get_mut(self.override_edit)
+                    .hide_gpu_charts = toggled;
+                self.save_config();
+                self.apply_power_policy();
+            }
+
             Message::ToggleCpuChart(toggled) => {
                 info!("Message::ToggleCpuChart({toggled:?})");
                 self.config.cpu.show_chart(toggled);
@@ -1144,6 +1519,72 @@ impl cosmic::Application for Minimon {
                 self.save_config();
             }
 
+            Message::TogglePowerChart(toggled) => {
+                info!("Message::TogglePowerChart({toggled:?})");
+                self.config.power.show_chart(toggled);
+                self.save_config();
+            }
+
+            Message::TogglePowerValue(toggled) => {
+                info!("Message::TogglePowerValue({toggled:?})");
+                self.config.power.show_value(toggled);
+                self.save_config();
+            }
+
+            Message::TogglePowerLabel(toggled) => {
+                info!("Message::TogglePowerLabel({toggled:?})");
+                self.config.power.show_label(toggled);
+                self.save_config();
+            }
+
+            Message::TogglePowerIcon(toggled) => {
+                info!("Message::TogglePowerIcon({toggled:?})");
+                self.config.power.show_icon(toggled);
+                self.save_config();
+            }
+
+            Message::SelectPowerVariant(variant) => {
+                info!("Message::SelectPowerVariant({variant:?})");
+                self.config.power.variant = variant;
+                self.save_config();
+            }
+
+            Message::PowerMaxWattsChanged(watts) => {
+                info!("Message::PowerMaxWattsChanged({watts})");
+                self.config.power.max_watts = watts;
+                self.save_config();
+            }
+
+            Message::ToggleBatteryChart(toggled) => {
+                info!("Message::ToggleBatteryChart({toggled:?})");
+                self.config.battery.show_chart(toggled);
+                self.save_config();
+            }
+
+            Message::ToggleBatteryValue(toggled) => {
+                info!("Message::ToggleBatteryValue({toggled:?})");
+                self.config.battery.show_value(toggled);
+                self.save_config();
+            }
+
+            Message::ToggleBatteryLabel(toggled) => {
+                info!("Message::ToggleBatteryLabel({toggled:?})");
+                self.config.battery.show_label(toggled);
+                self.save_config();
+            }
+
+            Message::ToggleBatteryIcon(toggled) => {
+                info!("Message::ToggleBatteryIcon({toggled:?})");
+                self.config.battery.show_icon(toggled);
+                self.save_config();
+            }
+
+            Message::ToggleBatteryTime(toggled) => {
+                info!("Message::ToggleBatteryTime({toggled:?})");
+                self.config.battery.show_time = toggled;
+                self.save_config();
+            }
+
             Message::ToggleNetValue(variant, toggled) => {
                 info!("Message::ToggleNetValue({toggled:?})");
                 let (_, config) = network_select!(self, variant);
@@ -1217,6 +1658,30 @@ impl cosmic::Application for Minimon {
                 self.save_config();
             }
 
+            Message::ToggleCanvasCharts(toggle) => {
+                info!("Message::ToggleCanvasCharts({toggle:?})");
+                self.config.canvas_charts = toggle;
+                self.save_config();
+            }
+
+            Message::ToggleTextOnly(toggle) => {
+                info!("Message::ToggleTextOnly({toggle:?})");
+                self.config.text_only = toggle;
+                self.save_config();
+            }
+
+            Message::ChartAspect(index) => {
+                info!("Message::ChartAspect({index})");
+                self.config.chart_aspect = ChartAspect::from(index);
+                self.save_config();
+            }
+
+            Message::SelectUnitSystem(index) => {
+                info!("Message::SelectUnitSystem({index})");
+                self.config.unit_system = UnitSystem::from(index);
+                self.save_config();
+            }
+
             Message::PanelSpacing(spacing) => {
                 info!("Message::PanelSpacing({spacing})");
                 self.config.panel_spacing = spacing;
@@ -1225,6 +1690,11 @@ impl cosmic::Application for Minimon {
 
             Message::Settings(setting) => {
                 info!("Message::Settings({setting:?})");
+                if let Some(SettingsVariant::Gpu(id)) = &self.settings_page
+                    && let Some(gpu) = self.gpus.get_mut(id)
+                {
+                    gpu.clear_processes();
+                }
                 self.settings_page = setting;
             }
             Message::SysmonSelect(idx) => {
@@ -1247,6 +1717,20 @@ impl cosmic::Application for Minimon {
                 );
             }
 
+            Message::GpuToggleGrid(id, device, toggled) => {
+                self.update_gpu_config(
+                    &id,
+                    "GpuToggleGrid",
+                    device,
+                    |config, device| match device {
+                        DeviceKind::Gpu => config.usage.show_grid(toggled),
+                        DeviceKind::Vram => config.vram.show_grid(toggled),
+                        DeviceKind::GpuTemp => config.temp.show_grid(toggled),
+                        _ => error!("GpuToggleGrid: wrong kind {device:?}"),
+                    },
+                );
+            }
+
             Message::GpuToggleValue(id, device, toggled) => {
                 self.update_gpu_config(
                     &id,
@@ -1292,6 +1776,16 @@ impl cosmic::Application for Minimon {
                 self.save_config();
             }
 
+            Message::SelectGpuTempSensor(id, sensor) => {
+                info!("Message::SelectGpuTempSensor({id:?}, {sensor:?})");
+                if let Some(c) = self.config.gpus.get_mut(&id) {
+                    c.temp.sensor = sensor;
+                    self.save_config();
+                } else {
+                    error!("SelectGpuTempSensor: wrong id {id:?}");
+                }
+            }
+
             Message::GpuTempMinTempChanged(id, temp) => {
                 info!("Message::GpuTempMinTempChanged({id:?}, {temp})");
                 if temp >= 0.0 && temp < 100.0 {
@@ -1314,6 +1808,75 @@ impl cosmic::Application for Minimon {
                 }
             }
 
+            Message::GpuToggleExtended(id, toggled) => {
+                info!("Message::GpuToggleExtended({id:?}, {toggled:?})");
+                if let Some(c) = self.config.gpus.get_mut(&id) {
+                    c.show_extended = toggled;
+                    self.save_config();
+                } else {
+                    error!("GpuToggleExtended: wrong id {id:?}");
+                }
+            }
+
+            Message::GpuRename(id, name) => {
+                info!("Message::GpuRename({id:?}, {name:?})");
+                if let Some(c) = self.config.gpus.get_mut(&id) {
+                    c.name = name;
+                    self.save_config();
+                } else {
+                    error!("GpuRename: wrong id {id:?}");
+                }
+            }
+
+            Message::GpuTemplateChanged(id, template) => {
+                info!("Message::GpuTemplateChanged({id:?}, {template:?})");
+                if let Some(c) = self.config.gpus.get_mut(&id) {
+                    c.usage.template = template;
+                    self.save_config();
+                } else {
+                    error!("GpuTemplateChanged: wrong id {id:?}");
+                }
+            }
+
+            Message::GpuToggleHidden(id, hidden) => {
+                info!("Message::GpuToggleHidden({id:?}, {hidden:?})");
+                if let Some(c) = self.config.gpus.get_mut(&id) {
+                    c.hidden = hidden;
+                    self.save_config();
+                    // Hidden GPUs are not polled, an unhidden one may still be paused
+                    self.apply_power_policy();
+                } else {
+                    error!("GpuToggleHidden: wrong id {id:?}");
+                }
+            }
+
+            Message::GpuChangeOrder(id, offset) => {
+                info!("Message::GpuChangeOrder({id:?}, {offset:?})");
+                let mut ids: Vec<String> = self
+                    .ordered_gpus()
+                    .iter()
+                    .map(|(id, _)| (*id).clone())
+                    .collect();
+                let Some(index) = ids.iter().position(|i| *i == id) else {
+                    error!("GpuChangeOrder: wrong id {id:?}");
+                    return Task::none();
+                };
+                let Some(new_index) = index.checked_add_signed(offset) else {
+                    return Task::none();
+                };
+                if new_index >= ids.len() {
+                    return Task::none();
+                }
+
+                ids.swap(index, new_index);
+                for (order, id) in ids.iter().enumerate() {
+                    if let Some(c) = self.config.gpus.get_mut(id) {
+                        c.order = order as u32;
+                    }
+                }
+                self.save_config();
+            }
+
             Message::GpuSelectGraphType(id, device, kind) => {
                 info!("Message::GpuSelectGraphType({id:?}, {device:?}, {kind:?})");
                 self.update_gpu_config(&id, "GpuSelectGraphType", device, |config, device| {
@@ -1367,42 +1930,50 @@ impl Minimon {
     fn config_changed(&mut self, config: &MinimonConfig) {
         info!("Updating state with configuration data");
         self.config = config.clone();
-        let rr = self.config.refresh_rate;
+        self.config.content_order.add_missing();
+        let rr = self.effective_refresh_rate();
         self.refresh_rate.store(rr, atomic::Ordering::Relaxed);
-        self.cpu.update_config(&config.cpu, rr);
-        self.cputemp.update_config(&config.cputemp, rr);
-        self.memory.update_config(&config.memory, rr);
-        self.network1.update_config(&config.network1, rr);
-        self.network2.update_config(&config.network2, rr);
-        self.disks1.update_config(&config.disks1, rr);
-        self.disks2.update_config(&config.disks2, rr);
+        let display = self.display_options();
+        self.cpu.update_config(&config.cpu, rr, display);
+        self.cputemp.update_config(&config.cputemp, rr, display);
+        self.memory.update_config(&config.memory, rr, display);
+        self.network1.update_config(&config.network1, rr, display);
+        self.network2.update_config(&config.network2, rr, display);
+        self.disks1.update_config(&config.disks1, rr, display);
+        self.disks2.update_config(&config.disks2, rr, display);
+        self.power.update_config(&config.power, rr, display);
+        self.battery.update_config(&config.battery, rr, display);
         self.sync_gpu_configs();
 
         // Track whether anything is visible on the panel, or just the app-icon
         {
             self.data_is_visible = false;
+            let text_only = self.config.text_only;
             for gpu in self.gpus.values() {
                 if let Some(g) = self.config.gpus.get(&gpu.id())
-                    && g.is_visible()
+                    && g.is_shown(text_only)
                 {
                     self.data_is_visible = true;
                     break;
                 }
             }
 
-            if self.config.cpu.visible()
-                || self.config.cputemp.visible()
-                || self.config.memory.visible()
-                || self.config.network1.visible()
+            if self.config.cpu.shown(text_only)
+                || self.config.cputemp.shown(text_only)
+                || self.config.memory.shown(text_only)
+                || self.config.network1.shown(text_only)
                 || (self.config.network1.variant != NetworkVariant::Combined
-                    && self.config.network2.visible())
-                || self.config.disks1.visible()
+                    && self.config.network2.shown(text_only))
+                || self.config.disks1.shown(text_only)
                 || (self.config.disks1.variant != DisksVariant::Combined
-                    && self.config.disks2.visible())
+                    && self.config.disks2.shown(text_only))
+                || self.config.power.shown(text_only)
+                || self.config.battery.shown(text_only)
             {
                 self.data_is_visible = true;
             }
         }
+        self.parse_templates();
         self.calculate_max_label_widths();
     }
 
@@ -1514,6 +2085,42 @@ impl Minimon {
             ),
         );
 
+        let canvas_row = settings::item(
+            fl!("settings-canvas-charts"),
+            row!(
+                widget::checkbox(self.config.canvas_charts).on_toggle(Message::ToggleCanvasCharts)
+            ),
+        );
+
+        let text_only_row = settings::item(
+            fl!("settings-text-only"),
+            row!(widget::checkbox(self.config.text_only).on_toggle(Message::ToggleTextOnly)),
+        );
+
+        let aspect_row = settings::item(
+            fl!("settings-chart-aspect"),
+            row!(
+                widget::dropdown(
+                    &CHART_ASPECT_NAMES,
+                    Some(self.config.chart_aspect.into()),
+                    Message::ChartAspect
+                )
+                .width(80)
+            ),
+        );
+
+        let units_row = settings::item(
+            fl!("settings-unit-system"),
+            row!(
+                widget::dropdown(
+                    &UNIT_SYSTEM_NAMES,
+                    Some(self.config.unit_system.into()),
+                    Message::SelectUnitSystem
+                )
+                .width(120)
+            ),
+        );
+
         let spacing_row = settings::item(
             fl!("settings-panel-spacing"),
             widget::row::with_children(vec![
@@ -1559,6 +2166,18 @@ impl Minimon {
                         }
                         text(fl!("settings-gpu"))
                     }
+                    ContentType::PowerDraw => {
+                        if !self.power.is_found() {
+                            continue;
+                        }
+                        text(fl!("settings-power"))
+                    }
+                    ContentType::Battery => {
+                        if !self.battery.is_found() {
+                            continue;
+                        }
+                        text(fl!("settings-battery"))
+                    }
                 };
 
                 let item_row = row!(
@@ -1599,9 +2218,56 @@ impl Minimon {
             refresh_row,
             value_size_row,
             mono_row,
+            canvas_row,
+            text_only_row,
+            aspect_row,
+            units_row,
             spacing_row,
             sysmon_row,
-            content_order
+            content_order,
+            self.power_saving_ui()
+        )
+        .spacing(10)
+        .into()
+    }
+
+    fn power_saving_ui(&'_ self) -> Element<'_, crate::app::Message> {
+        let edited = self.config.profile_overrides.get(self.override_edit);
+        let override_rate = f64::from(edited.refresh_rate) / 1000.0;
+        let selected: Option<usize> = Some(self.override_edit.into());
+
+        column!(
+            row!(
+                text::heading(fl!("power-saving")),
+                space::horizontal(),
+                widget::dropdown(&*POWER_CONDITION_NAMES, selected, |m| {
+                    Message::SelectOverrideCondition(m.into())
+                })
+                .width(160)
+            )
+            .align_y(Alignment::Center),
+            settings::item(
+                fl!("override-refresh-rate"),
+                spin_button(
+                    format!("{override_rate:.2}"),
+                    override_rate,
+                    0.250,
+                    0.0,
+                    15.00,
+                    Message::OverrideRefreshRateChanged,
+                ),
+            ),
+            settings::item(
+                fl!("override-suspend"),
+                widget::checkbox(edited.suspend_expensive)
+                    .on_toggle(Message::ToggleOverrideSuspend),
+            ),
+            settings::item(
+                fl!("override-hide-gpu-charts"),
+                widget::checkbox(edited.hide_gpu_charts)
+                    .on_toggle(Message::ToggleOverrideHideGpuCharts),
+            ),
+            text::caption(fl!("power-saving-explanation")),
         )
         .spacing(10)
         .into()
@@ -1643,54 +2309,71 @@ impl Minimon {
         elements
     }
 
+    // Height and width of a chart in the panel. Rings stay square, the other charts keep
+    // the thickness of the panel and get the configured aspect ratio along it, so on a
+    // vertical panel they grow taller instead of narrower.
+    fn chart_size(&self, chart: ChartKind) -> (u16, u16) {
+        let size = self.core.applet.suggested_size(false);
+        let ratio = self.config.chart_aspect.ratio();
+        if chart == ChartKind::Ring || ratio == 1 {
+            size
+        } else if self.core.applet.is_horizontal() {
+            (size.0, size.0.saturating_mul(ratio))
+        } else {
+            (size.1.saturating_mul(ratio), size.1)
+        }
+    }
+
     fn cpu_panel_ui(&'_ self, horizontal: bool) -> VecDeque<Element<'_, crate::app::Message>> {
         let size = self.core.applet.suggested_size(false);
 
         let mut elements: VecDeque<Element<Message>> = VecDeque::new();
 
-        let cpu_has_content = self.config.cpu.value_visible() || self.config.cpu.chart_visible();
+        let cpu_has_content = self.config.cpu.shown(self.config.text_only);
 
-        if self.config.cpu.icon_visible() && cpu_has_content {
+        if !self.config.text_only && self.config.cpu.icon_visible() && cpu_has_content {
             self.push_symbolic_icon(&mut elements, CPU_ICON, false);
         }
 
-        if self.config.cpu.label_visible() && cpu_has_content {
+        if !self.config.text_only && self.config.cpu.label_visible() && cpu_has_content {
             self.push_text_label(&mut elements, &fl!("label-cpu"));
         }
 
         let cpu_usage = self.cpu.latest_sample();
         // Format CPU usage based on horizontal layout and sample value
-        let formatted_cpu = if self.config.cpu.no_decimals {
-            format!("{}%", cpu_usage.round())
+        let cpu_value = if self.config.cpu.no_decimals {
+            locale::format_number(cpu_usage.round(), 0)
         } else if cpu_usage < 10.0 && horizontal {
-            format!("{:.2}%", (cpu_usage * 100.0).trunc() / 100.0)
+            locale::format_number((cpu_usage * 100.0).trunc() / 100.0, 2)
         } else {
-            format!("{:.1}%", (cpu_usage * 10.0).trunc() / 10.0)
+            locale::format_number((cpu_usage * 10.0).trunc() / 10.0, 1)
         };
+        let formatted_cpu = format!("{cpu_value}{}", locale::unit_symbol("%"));
 
         if self.config.cpu.value_visible() {
-            elements.push_back(
-                self.figure_value(formatted_cpu, self.value_cpu_width)
-                    .into(),
-            );
+            let value = self
+                .templated_value(&TemplateKey::Cpu, None)
+                .unwrap_or_else(|| self.figure_value(formatted_cpu, self.value_cpu_width));
+            elements.push_back(value.into());
         }
 
-        let width: u16 = if self.config.cpu.chart == ChartKind::StackedBars {
-            StackedBarSvg::new(
+        let (height, width) = if self.config.cpu.chart == ChartKind::StackedBars {
+            let width = StackedBarSvg::new(
                 self.config.cpu.bar_width,
                 size.0,
                 self.config.cpu.bar_spacing,
             )
-            .width(self.cpu.core_count())
+            .width(self.cpu.core_count());
+            (size.0, width)
         } else {
-            size.1
+            self.chart_size(self.config.cpu.chart)
         };
 
-        if self.config.cpu.chart_visible() {
+        if !self.config.text_only && self.config.cpu.chart_visible() {
             elements.push_back(
                 self.cpu
-                    .chart(size.0, width)
-                    .height(size.0)
+                    .chart(height, width)
+                    .height(height)
                     .width(width)
                     .into(),
             );
@@ -1703,32 +2386,34 @@ impl Minimon {
         &'_ self,
         _horizontal: bool,
     ) -> VecDeque<Element<'_, crate::app::Message>> {
-        let size = self.core.applet.suggested_size(false);
-
         let mut elements: VecDeque<Element<Message>> = VecDeque::new();
 
         if self.cputemp.is_found() {
-            let cputemp_has_content =
-                self.config.cputemp.value_visible() || self.config.cputemp.chart_visible();
+            let cputemp_has_content = self.config.cputemp.shown(self.config.text_only);
 
-            if self.config.cputemp.icon_visible() && cputemp_has_content {
+            if !self.config.text_only && self.config.cputemp.icon_visible() && cputemp_has_content {
                 self.push_symbolic_icon(&mut elements, TEMP_ICON, false);
             }
 
-            if self.config.cputemp.label_visible() && cputemp_has_content {
+            if !self.config.text_only && self.config.cputemp.label_visible() && cputemp_has_content
+            {
                 self.push_text_label(&mut elements, &fl!("label-cpu-temp"));
             }
 
             if self.config.cputemp.value_visible() {
-                elements.push_back(self.figure_value(self.cputemp.to_string(), None).into());
+                let value = self
+                    .templated_value(&TemplateKey::CpuTemp, None)
+                    .unwrap_or_else(|| self.figure_value(self.cputemp.to_string(), None));
+                elements.push_back(value.into());
             }
 
-            if self.config.cputemp.chart_visible() {
+            if !self.config.text_only && self.config.cputemp.chart_visible() {
+                let (height, width) = self.chart_size(self.config.cputemp.chart);
                 elements.push_back(
                     self.cputemp
-                        .chart(size.0, size.1)
-                        .height(size.0)
-                        .width(size.1)
+                        .chart(height, width)
+                        .height(height)
+                        .width(width)
                         .into(),
                 );
             }
@@ -1738,33 +2423,33 @@ impl Minimon {
     }
 
     fn memory_panel_ui(&'_ self, horizontal: bool) -> VecDeque<Element<'_, crate::app::Message>> {
-        let size = self.core.applet.suggested_size(false);
-
         let mut elements: VecDeque<Element<Message>> = VecDeque::new();
 
-        let memory_has_content =
-            self.config.memory.value_visible() || self.config.memory.chart_visible();
+        let memory_has_content = self.config.memory.shown(self.config.text_only);
 
-        if self.config.memory.icon_visible() && memory_has_content {
+        if !self.config.text_only && self.config.memory.icon_visible() && memory_has_content {
             self.push_symbolic_icon(&mut elements, RAM_ICON, false);
         }
 
-        if self.config.memory.label_visible() && memory_has_content {
+        if !self.config.text_only && self.config.memory.label_visible() && memory_has_content {
             self.push_text_label(&mut elements, &fl!("label-memory"));
         }
 
         if self.config.memory.value_visible() {
-            let formatted_mem = self.memory.to_string(!horizontal);
-            elements.push_back(self.figure_value(formatted_mem, None).into());
+            let value = self
+                .templated_value(&TemplateKey::Memory, None)
+                .unwrap_or_else(|| self.figure_value(self.memory.to_string(!horizontal), None));
+            elements.push_back(value.into());
         }
 
         // Chart section
-        if self.config.memory.chart_visible() {
+        if !self.config.text_only && self.config.memory.chart_visible() {
+            let (height, width) = self.chart_size(self.config.memory.chart);
             elements.push_back(
                 self.memory
-                    .chart(size.0, size.1)
-                    .height(size.0)
-                    .width(size.1)
+                    .chart(height, width)
+                    .height(height)
+                    .width(width)
                     .into(),
             );
         }
@@ -1772,11 +2457,85 @@ impl Minimon {
         elements
     }
 
-    fn network_panel_ui(&'_ self, horizontal: bool) -> VecDeque<Element<'_, crate::app::Message>> {
-        let size = self.core.applet.suggested_size(false);
+    fn power_panel_ui(&'_ self, horizontal: bool) -> VecDeque<Element<'_, crate::app::Message>> {
+        let mut elements: VecDeque<Element<Message>> = VecDeque::new();
+
+        if !self.power.is_found() {
+            return elements;
+        }
+
+        let power_has_content = self.config.power.shown(self.config.text_only);
+
+        if !self.config.text_only && self.config.power.icon_visible() && power_has_content {
+            self.push_symbolic_icon(&mut elements, POWER_ICON, false);
+        }
+
+        if !self.config.text_only && self.config.power.label_visible() && power_has_content {
+            self.push_text_label(&mut elements, &fl!("label-power"));
+        }
+
+        if self.config.power.value_visible() {
+            let value = self
+                .templated_value(&TemplateKey::Power, None)
+                .unwrap_or_else(|| self.figure_value(self.power.to_string(!horizontal), None));
+            elements.push_back(value.into());
+        }
+
+        if !self.config.text_only && self.config.power.chart_visible() {
+            let (height, width) = self.chart_size(self.config.power.chart);
+            elements.push_back(
+                self.power
+                    .chart(height, width)
+                    .height(height)
+                    .width(width)
+                    .into(),
+            );
+        }
+
+        elements
+    }
+
+    fn battery_panel_ui(&'_ self, horizontal: bool) -> VecDeque<Element<'_, crate::app::Message>> {
+        let mut elements: VecDeque<Element<Message>> = VecDeque::new();
+
+        if !self.battery.is_found() {
+            return elements;
+        }
+
+        let battery_has_content = self.config.battery.shown(self.config.text_only);
+
+        if !self.config.text_only && self.config.battery.icon_visible() && battery_has_content {
+            self.push_symbolic_icon(&mut elements, BATTERY_ICON, false);
+        }
+
+        if !self.config.text_only && self.config.battery.label_visible() && battery_has_content {
+            self.push_text_label(&mut elements, &fl!("label-battery"));
+        }
+
+        if self.config.battery.value_visible() {
+            let value = self
+                .templated_value(&TemplateKey::Battery, None)
+                .unwrap_or_else(|| self.figure_value(self.battery.to_string(!horizontal), None));
+            elements.push_back(value.into());
+        }
+
+        if !self.config.text_only && self.config.battery.chart_visible() {
+            let (height, width) = self.chart_size(self.config.battery.chart);
+            elements.push_back(
+                self.battery
+                    .chart(height, width)
+                    .height(height)
+                    .width(width)
+                    .into(),
+            );
+        }
+
+        elements
+    }
 
+    fn network_panel_ui(&'_ self, horizontal: bool) -> VecDeque<Element<'_, crate::app::Message>> {
         let nw_combined = self.config.network1.variant == NetworkVariant::Combined;
-        let sample_rate_ms = self.config.refresh_rate;
+        let sample_rate_ms = self.effective_refresh_rate();
         let mut elements: VecDeque<Element<Message>> = VecDeque::new();
 
         let format_value = |text: String| self.figure_value(text, self.value_network_width);
@@ -1787,16 +2546,25 @@ impl Minimon {
             network::UnitVariant::Short
         };
 
-        let network_has_content = self.config.network1.value_visible()
-            || self.config.network1.chart_visible()
-            || (!nw_combined
-                && (self.config.network2.value_visible() || self.config.network2.chart_visible()));
+        let network_has_content = self.config.network1.shown(self.config.text_only)
+            || (!nw_combined && self.config.network2.shown(self.config.text_only));
 
-        if self.config.network1.label_visible() && network_has_content {
+        if !self.config.text_only && self.config.network1.label_visible() && network_has_content {
             self.push_text_label(&mut elements, &fl!("label-network"));
         }
 
-        if self.config.network1.value_visible() {
+        // A template replaces the values of both configs
+        let templated = self
+            .config
+            .network1
+            .value_visible()
+            .then(|| self.templated_value(&TemplateKey::Network, None))
+            .flatten();
+        let has_template = templated.is_some();
+
+        if let Some(value) = templated {
+            elements.push_back(value.into());
+        } else if self.config.network1.value_visible() {
             let mut network_values = Vec::new();
             let mut dl_row = Vec::new();
 
@@ -1835,17 +2603,18 @@ impl Minimon {
             elements.push_back(Column::from_vec(network_values).into());
         }
 
-        if self.config.network1.chart_visible() {
+        if !self.config.text_only && self.config.network1.chart_visible() {
+            let (height, width) = self.chart_size(self.config.network1.chart);
             elements.push_back(
                 self.network1
-                    .chart(size.0, size.1)
-                    .height(size.0)
-                    .width(size.1)
+                    .chart(height, width)
+                    .height(height)
+                    .width(width)
                     .into(),
             );
         }
 
-        if self.config.network2.value_visible() && !nw_combined {
+        if self.config.network2.value_visible() && !nw_combined && !has_template {
             let mut network_values = Vec::new();
 
             let mut ul_row = Vec::new();
@@ -1860,17 +2629,18 @@ impl Minimon {
             elements.push_back(Column::from_vec(network_values).into());
         }
 
-        if self.config.network2.chart_visible() && !nw_combined {
+        if !self.config.text_only && self.config.network2.chart_visible() && !nw_combined {
+            let (height, width) = self.chart_size(self.config.network2.chart);
             elements.push_back(
                 self.network2
-                    .chart(size.0, size.1)
-                    .height(size.0)
-                    .width(size.1)
+                    .chart(height, width)
+                    .height(height)
+                    .width(width)
                     .into(),
             );
         }
 
-        if self.config.network1.icon_visible() && network_has_content {
+        if !self.config.text_only && self.config.network1.icon_visible() && network_has_content {
             self.push_symbolic_icon(&mut elements, NETWORK_ICON, true);
         }
 
@@ -1878,10 +2648,8 @@ impl Minimon {
     }
 
     fn disks_panel_ui(&'_ self, horizontal: bool) -> VecDeque<Element<'_, crate::app::Message>> {
-        let size = self.core.applet.suggested_size(false);
-
         let disks_combined = self.config.disks1.variant == DisksVariant::Combined;
-        let sample_rate_ms = self.config.refresh_rate;
+        let sample_rate_ms = self.effective_refresh_rate();
         let mut elements: VecDeque<Element<Message>> = VecDeque::new();
 
         let format_value = |text: String| self.figure_value(text, self.value_disks_width);
@@ -1892,16 +2660,25 @@ impl Minimon {
             disks::UnitVariant::Short
         };
 
-        let disks_has_content = self.config.disks1.value_visible()
-            || self.config.disks1.chart_visible()
-            || (!disks_combined
-                && (self.config.disks2.value_visible() || self.config.disks2.chart_visible()));
+        let disks_has_content = self.config.disks1.shown(self.config.text_only)
+            || (!disks_combined && self.config.disks2.shown(self.config.text_only));
 
-        if self.config.disks1.label_visible() && disks_has_content {
+        if !self.config.text_only && self.config.disks1.label_visible() && disks_has_content {
             self.push_text_label(&mut elements, &fl!("label-disks"));
         }
 
-        if self.config.disks1.value_visible() {
+        // A template replaces the values of both configs
+        let templated = self
+            .config
+            .disks1
+            .value_visible()
+            .then(|| self.templated_value(&TemplateKey::Disks, None))
+            .flatten();
+        let has_template = templated.is_some();
+
+        if let Some(value) = templated {
+            elements.push_back(value.into());
+        } else if self.config.disks1.value_visible() {
             let mut disks_values = Vec::new();
 
             let mut wr_row = Vec::new();
@@ -1930,17 +2707,18 @@ impl Minimon {
             elements.push_back(Column::from_vec(disks_values).into());
         }
 
-        if self.config.disks1.chart_visible() {
+        if !self.config.text_only && self.config.disks1.chart_visible() {
+            let (height, width) = self.chart_size(self.config.disks1.chart);
             elements.push_back(
                 self.disks1
-                    .chart(size.0, size.1)
-                    .height(size.0)
-                    .width(size.1)
+                    .chart(height, width)
+                    .height(height)
+                    .width(width)
                     .into(),
             );
         }
 
-        if self.config.disks2.value_visible() && !disks_combined {
+        if self.config.disks2.value_visible() && !disks_combined && !has_template {
             let mut disks_values = Vec::new();
 
             let mut rd_row = Vec::new();
@@ -1953,17 +2731,18 @@ impl Minimon {
             elements.push_back(Column::from_vec(disks_values).into());
         }
 
-        if self.config.disks2.chart_visible() && !disks_combined {
+        if !self.config.text_only && self.config.disks2.chart_visible() && !disks_combined {
+            let (height, width) = self.chart_size(self.config.disks2.chart);
             elements.push_back(
                 self.disks2
-                    .chart(size.0, size.1)
-                    .height(size.0)
-                    .width(size.1)
+                    .chart(height, width)
+                    .height(height)
+                    .width(width)
                     .into(),
             );
         }
 
-        if self.config.disks1.icon_visible() && disks_has_content {
+        if !self.config.text_only && self.config.disks1.icon_visible() && disks_has_content {
             self.push_symbolic_icon(&mut elements, DISK_ICON, true);
         }
 
@@ -1975,20 +2754,21 @@ impl Minimon {
         gpu: &'a Gpu,
         horizontal: bool,
     ) -> VecDeque<Element<'a, crate::app::Message>> {
-        let size = self.core.applet.suggested_size(false);
-
         let mut elements: VecDeque<Element<Message>> = VecDeque::new();
 
         if let Some(config) = self.config.gpus.get(&gpu.id()) {
-            let gpu_has_content = config.usage.value_visible()
-                || config.usage.chart_visible()
-                || config.temp.value_visible()
-                || config.temp.chart_visible()
-                || config.vram.value_visible()
-                || config.vram.chart_visible();
-
-            if config.usage.label_visible() && gpu_has_content {
-                self.push_text_label(&mut elements, &fl!("label-gpu"));
+            let gpu_has_content = config.usage.shown(self.config.text_only)
+                || config.temp.shown(self.config.text_only)
+                || config.vram.shown(self.config.text_only);
+
+            if !self.config.text_only && config.usage.label_visible() && gpu_has_content {
+                // The detected model name is too long for the panel, only a custom one is shown
+                let name = config.name.trim();
+                if name.is_empty() {
+                    self.push_text_label(&mut elements, &fl!("label-gpu"));
+                } else {
+                    self.push_text_label(&mut elements, name);
+                }
             }
 
             let formatted_gpu = gpu.gpu.to_string();
@@ -1996,7 +2776,17 @@ impl Minimon {
             let stacked_values =
                 config.stack_values && config.usage.value_visible() && config.vram.value_visible();
 
-            if stacked_values {
+            // A template replaces the usage, temperature and VRAM values
+            let templated = config
+                .usage
+                .value_visible()
+                .then(|| self.templated_value(&TemplateKey::Gpu(gpu.id()), Some(gpu)))
+                .flatten();
+            let has_template = templated.is_some();
+
+            if let Some(value) = templated {
+                elements.push_back(value.into());
+            } else if stacked_values {
                 let gpu_values = vec![
                     widget::space::vertical().into(),
                     self.figure_value(formatted_gpu, self.value_gpu_width)
@@ -2012,35 +2802,55 @@ impl Minimon {
                 );
             }
 
-            if config.usage.chart_visible() {
-                elements.push_back(gpu.gpu.chart().height(size.0).width(size.1).into());
+            let hide_charts = self.active_override().hide_gpu_charts || self.config.text_only;
+
+            if config.usage.chart_visible() && !hide_charts {
+                let (height, width) = self.chart_size(config.usage.chart);
+                elements.push_back(
+                    gpu.gpu
+                        .chart(height, width)
+                        .height(height)
+                        .width(width)
+                        .into(),
+                );
             }
-            if config.temp.value_visible() {
+            if config.temp.value_visible() && !has_template {
                 elements.push_back(self.figure_value(gpu.temp.to_string(), None).into());
             }
 
-            if config.temp.chart_visible() {
-                elements.push_back(gpu.temp.chart().height(size.0).width(size.1).into());
+            if config.temp.chart_visible() && !hide_charts {
+                let (height, width) = self.chart_size(config.temp.chart);
+                elements.push_back(
+                    gpu.temp
+                        .chart(height, width)
+                        .height(height)
+                        .width(width)
+                        .into(),
+                );
             }
 
-            if config.vram.value_visible() && !stacked_values {
+            if config.vram.value_visible() && !stacked_values && !has_template {
                 elements.push_back(self.figure_value(formatted_vram, None).into());
             }
 
-            if config.vram.chart_visible() {
-                elements.push_back(gpu.vram.chart().height(size.0).width(size.1).into());
+            if config.vram.chart_visible() && !hide_charts {
+                let (height, width) = self.chart_size(config.vram.chart);
+                elements.push_back(
+                    gpu.vram
+                        .chart(height, width)
+                        .height(height)
+                        .width(width)
+                        .into(),
+                );
             }
         }
 
         if let Some(config) = self.config.gpus.get(&gpu.id()) {
-            let gpu_has_content = config.usage.value_visible()
-                || config.usage.chart_visible()
-                || config.temp.value_visible()
-                || config.temp.chart_visible()
-                || config.vram.value_visible()
-                || config.vram.chart_visible();
-
-            if config.usage.icon_visible() && gpu_has_content {
+            let gpu_has_content = config.usage.shown(self.config.text_only)
+                || config.temp.shown(self.config.text_only)
+                || config.vram.shown(self.config.text_only);
+
+            if !self.config.text_only && config.usage.icon_visible() && gpu_has_content {
                 self.push_symbolic_icon(&mut elements, GPU_ICON, true);
             }
         }
@@ -2118,6 +2928,12 @@ impl Minimon {
                     }
                 }
             }
+            DeviceKind::Power => {
+                *self.config.power.colors_mut() = *colors;
+            }
+            DeviceKind::Battery => {
+                *self.config.battery.colors_mut() = *colors;
+            }
         }
     }
 
@@ -2178,24 +2994,113 @@ impl Minimon {
 
         for gpu in &mut self.gpus.values_mut() {
             if let Some(g) = self.config.gpus.get(&gpu.id())
+                && !g.hidden
                 && (all || g.is_visible())
             {
                 if all && !gpu.is_active() {
                     gpu.restart();
                 }
                 gpu.update();
+                if all && g.show_extended {
+                    gpu.update_extended();
+                }
             }
         }
+
+        // Process lists are only sampled while their GPU page is open
+        if all
+            && !self.active_override().suspend_expensive
+            && let Some(SettingsVariant::Gpu(id)) = &self.settings_page
+            && let Some(gpu) = self.gpus.get_mut(id)
+        {
+            gpu.update_processes();
+        }
+
+        if all || self.config.power.visible() {
+            self.power.set_gpu_power(self.gpus.power_draw());
+            self.power.update();
+        }
+
+        if all || self.config.battery.visible() {
+            self.battery.update();
+        }
     }
 
     fn maybe_stop_gpus(&mut self) {
+        for (id, pause) in self.gpus_paused_by_policy() {
+            if let Some(gpu) = self.gpus.get_mut(&id)
+                && pause
+            {
+                info!("Power saving, stop polling {id}");
+                gpu.stop();
+            }
+        }
+    }
+
+    /// The profile override in effect, merged with the battery override when unplugged
+    fn active_override(&self) -> ProfileOverride {
+        let overrides = &self.config.profile_overrides;
+        let mut active = *overrides.get(self.power_profile);
         if self.is_laptop && !self.on_ac {
-            for (id, gpu) in self.gpus.iter_mut() {
-                if let Some(c) = self.config.gpus.get(id)
-                    && c.pause_on_battery
-                {
-                    info!("Changed to DC, stop polling");
-                    gpu.stop(); // on battery, stop polling
+            active = active.merge(*overrides.get(PowerCondition::OnBattery));
+        }
+        active
+    }
+
+    /// The renderer and unit settings the sensors draw and format with
+    fn display_options(&self) -> DisplayOptions {
+        DisplayOptions {
+            canvas: self.config.canvas_charts,
+            units: self.config.unit_system,
+        }
+    }
+
+    /// The configured refresh rate, lengthened by the active profile override
+    fn effective_refresh_rate(&self) -> u32 {
+        self.config
+            .refresh_rate
+            .max(self.active_override().refresh_rate)
+    }
+
+    /// Whether each GPU should be paused on battery or by the active profile override
+    fn gpus_paused_by_policy(&self) -> Vec<(String, bool)> {
+        let overrides = self.active_override();
+        self.gpus
+            .iter()
+            .map(|(id, gpu)| {
+                let pause_on_battery = self.is_laptop
+                    && !self.on_ac
+                    && self.config.gpus.get(id).is_some_and(|c| c.pause_on_battery);
+                let suspend_nvml = overrides.suspend_expensive && gpu.gpu_type() == GpuType::Nvidia;
+                let hidden = self.config.gpus.get(id).is_some_and(|c| c.hidden);
+                (id.clone(), pause_on_battery || suspend_nvml || hidden)
+            })
+            .collect()
+    }
+
+    /// Called when the AC state, the power profile or the settings the policy reads change
+    fn apply_power_policy(&mut self) {
+        // Picks up the new refresh rate
+        let config = self.config.clone();
+        self.config_changed(&config);
+
+        // Everything but hidden GPUs keeps running while the popup is open, maybe_stop_gpus
+        // runs when it closes
+        let popup_open = self.popup.is_some();
+
+        for (id, pause) in self.gpus_paused_by_policy() {
+            let pause = if popup_open {
+                self.config.gpus.get(&id).is_some_and(|c| c.hidden)
+            } else {
+                pause
+            };
+            if let Some(gpu) = self.gpus.get_mut(&id) {
+                if pause && gpu.is_active() {
+                    info!("Power saving, stop polling {id}");
+                    gpu.stop();
+                } else if !pause && !gpu.is_active() {
+                    info!("Power saving ended, restart polling {id}");
+                    gpu.restart();
                 }
             }
         }
@@ -2212,6 +3117,129 @@ impl Minimon {
         }
     }
 
+    // Parse the templates once, not on every view
+    fn parse_templates(&mut self) {
+        let mut sources = vec![
+            (TemplateKey::Cpu, &self.config.cpu.template),
+            (TemplateKey::CpuTemp, &self.config.cputemp.template),
+            (TemplateKey::Memory, &self.config.memory.template),
+            (TemplateKey::Network, &self.config.network1.template),
+            (TemplateKey::Disks, &self.config.disks1.template),
+            (TemplateKey::Power, &self.config.power.template),
+            (TemplateKey::Battery, &self.config.battery.template),
+        ];
+        sources.extend(
+            self.config
+                .gpus
+                .iter()
+                .map(|(id, config)| (TemplateKey::Gpu(id.clone()), &config.usage.template)),
+        );
+
+        self.templates = sources
+            .into_iter()
+            .filter_map(|(key, source)| {
+                let template = template::configured(source)?;
+                Some((
+                    key,
+                    PanelTemplate {
+                        template,
+                        width: None,
+                    },
+                ))
+            })
+            .collect();
+    }
+
+    // The value of a panel item as the user's template renders it, None without a valid one
+    fn templated_value<'a>(
+        &self,
+        key: &TemplateKey,
+        gpu: Option<&Gpu>,
+    ) -> Option<widget::Text<'a, cosmic::Theme>> {
+        let template = self.templates.get(key)?;
+        let text = template
+            .template
+            .render(|name| self.template_value(name, gpu));
+        Some(self.figure_value(text, template.width))
+    }
+
+    // A placeholder of a template, `gpu` is the GPU of the panel item, else the first one
+    fn template_value(&self, name: &str, gpu: Option<&Gpu>) -> Option<template::Value> {
+        use template::Value;
+
+        let sample_rate_ms = self.effective_refresh_rate();
+        let units = self.config.unit_system;
+        let gpu = gpu.or_else(|| {
+            self.ordered_gpus()
+                .into_iter()
+                .find(|(id, _)| !self.config.gpus.get(*id).is_some_and(|c| c.hidden))
+                .map(|(_, gpu)| gpu)
+        });
+        let nw_combined = self.config.network1.variant == NetworkVariant::Combined;
+        let disks_combined = self.config.disks1.variant == DisksVariant::Combined;
+
+        match name {
+            "cpu" => Some(Value::Number(self.cpu.latest_sample())),
+            "cputemp" => self.cputemp.is_found().then(|| {
+                let unit = self.config.cputemp.unit;
+                Value::Number(unit.convert(self.cputemp.latest_sample()))
+            }),
+            "mem" => Some(Value::Number(units.from_gib(self.memory.latest_sample()))),
+            "mem.total" => Some(Value::Number(units.from_gib(self.memory.total()))),
+            "mem.pct" => (self.memory.total() > 0.0)
+                .then(|| Value::Number(self.memory.latest_sample() * 100.0 / self.memory.total())),
+            "net.down" => Some(Value::Text(
+                self.network1
+                    .download_label(sample_rate_ms, network::UnitVariant::Short),
+            )),
+            "net.up" => {
+                let upload = if nw_combined {
+                    &self.network1
+                } else {
+                    &self.network2
+                };
+                Some(Value::Text(
+                    upload.upload_label(sample_rate_ms, network::UnitVariant::Short),
+                ))
+            }
+            "disk.write" => Some(Value::Text(
+                self.disks1
+                    .write_label(sample_rate_ms, disks::UnitVariant::Short),
+            )),
+            "disk.read" => {
+                let read = if disks_combined {
+                    &self.disks1
+                } else {
+                    &self.disks2
+                };
+                Some(Value::Text(
+                    read.read_label(sample_rate_ms, disks::UnitVariant::Short),
+                ))
+            }
+            "power" => self
+                .power
+                .is_found()
+                .then(|| Value::Number(self.power.latest_sample())),
+            "battery" => self
+                .battery
+                .is_found()
+                .then(|| Value::Number(self.battery.status().percentage)),
+            "gpu" | "gpu.temp" | "gpu.vram" => {
+                let gpu = gpu.filter(|gpu| gpu.is_active())?;
+                match name {
+                    "gpu" => Some(Value::Number(gpu.gpu.latest_sample())),
+                    "gpu.vram" => Some(Value::Number(units.from_gib(gpu.vram.latest_sample()))),
+                    _ => {
+                        let celsius = gpu.temp.latest_sample();
+                        let unit = self.config.gpus.get(&gpu.id()).map(|c| c.temp.unit)?;
+                        (celsius > 0.0).then(|| Value::Number(unit.convert(celsius)))
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
     fn figure_value<'a>(
         &self,
         text: String,
@@ -2235,11 +3263,11 @@ impl Minimon {
     }
 
     fn sync_gpu_configs(&mut self) {
+        let rr = self.effective_refresh_rate();
+        let display = self.display_options();
         let config_gpus = &mut self.config.gpus;
 
-        // Remove entries not present in detected GPUs
-        config_gpus.retain(|id, _| self.gpus.get(id).is_some());
-
+        // Entries of GPUs that are gone are kept, so an eGPU has its settings when it comes back
         // Add missing GPU configs
         for (id, _) in self.gpus.iter() {
             config_gpus.entry(id.clone()).or_default();
@@ -2248,7 +3276,7 @@ impl Minimon {
         // Sync runtime config into GPU objects
         for (id, gpu) in self.gpus.iter_mut() {
             if let Some(config) = config_gpus.get(id) {
-                gpu.update_config(config, self.config.refresh_rate);
+                gpu.update_config(config, rr, display);
             }
         }
     }
@@ -2266,45 +3294,15 @@ impl Minimon {
         }
     }
 
-    fn has_gpus(&self) -> bool {
-        !self.gpus.is_empty()
+    /// The GPUs in the order chosen by the user
+    fn ordered_gpus(&self) -> Vec<(&String, &Gpu)> {
+        let mut gpus: Vec<(&String, &Gpu)> = self.gpus.iter().collect();
+        gpus.sort_by_key(|(id, _)| (self.config.gpus.get(*id).map_or(0, |c| c.order), *id));
+        gpus
     }
 
-    fn is_on_ac(&self) -> Result<bool, Box<dyn std::error::Error>> {
-        if self.is_laptop {
-            // Connect to the system bus
-            let connection = Connection::system()?;
-
-            // Create a proxy to UPower service
-            let proxy = zbus::blocking::Proxy::new(
-                &connection,
-                "org.freedesktop.UPower",
-                "/org/freedesktop/UPower",
-                "org.freedesktop.UPower",
-            )?;
-
-            // Get the list of power-related devices
-            let devices: Vec<OwnedObjectPath> = proxy.call("EnumerateDevices", &())?;
-
-            for device_path in devices {
-                let device_proxy = zbus::blocking::Proxy::new(
-                    &connection,
-                    "org.freedesktop.UPower",
-                    device_path.as_str(),
-                    "org.freedesktop.UPower.Device",
-                )?;
-
-                // Get the Type property (1 = line power / AC)
-                let kind: u32 = device_proxy.get_property("Type")?;
-                if kind == 1 {
-                    // Get the Online property
-                    let online: bool = device_proxy.get_property("Online")?;
-                    return Ok(online);
-                }
-            }
-        }
-
-        Ok(true)
+    fn has_gpus(&self) -> bool {
+        !self.gpus.is_empty()
     }
 
     fn is_laptop() -> bool {
@@ -2369,23 +3367,72 @@ impl Minimon {
 
             let is_horizontal = self.core.applet.is_horizontal();
 
-            self.value_cpu_width = self.measure_text_width("8.88%", &attrs);
+            let widest_cpu = locale::format_number(8.88, 2) + &locale::unit_symbol("%");
+            self.value_cpu_width = self.measure_text_width(&widest_cpu, &attrs);
             self.value_gpu_width = self.value_cpu_width;
 
-            self.value_network_width = match (self.config.network1.show_bytes, is_horizontal) {
-                (false, false) => self.measure_text_width("8.88M", &attrs),
-                (false, true) => self.measure_text_width("8.88 Mbps", &attrs),
-                (true, false) => self.measure_text_width("8.88M", &attrs),
-                (true, true) => self.measure_text_width("8.88 MB/s", &attrs),
+            // A rate with three digits and the mega prefix of the unit system
+            let units = self.config.unit_system;
+            let variant = if is_horizontal {
+                crate::units::UnitVariant::Long
+            } else {
+                crate::units::UnitVariant::Short
             };
-
-            self.value_disks_width = if is_horizontal {
-                self.measure_text_width("8.88 MB/s", &attrs)
+            let rate = |quantity| units.rate(units.multiplier(2) * 888 / 100, quantity, variant);
+            let network_rate = if self.config.network1.show_bytes {
+                rate(Quantity::Bytes)
+            } else {
+                rate(Quantity::Bits)
+            };
+            let disks_rate = if self.config.disks1.show_bits {
+                rate(Quantity::Bits)
             } else {
-                self.measure_text_width("8.88M", &attrs)
+                rate(Quantity::Bytes)
             };
 
+            self.value_network_width = self.measure_text_width(network_rate.trim(), &attrs);
+            self.value_disks_width = self.measure_text_width(disks_rate.trim(), &attrs);
+
             self.value_w_width = self.measure_text_width("W ", &attrs);
+
+            // Templates show the short rates, as wide as the ones above
+            let short_rate = |quantity| {
+                units.rate(
+                    units.multiplier(2) * 888 / 100,
+                    quantity,
+                    crate::units::UnitVariant::Short,
+                )
+            };
+            let network_rate = short_rate(if self.config.network1.show_bytes {
+                Quantity::Bytes
+            } else {
+                Quantity::Bits
+            });
+            let disks_rate = short_rate(if self.config.disks1.show_bits {
+                Quantity::Bits
+            } else {
+                Quantity::Bytes
+            });
+            let widest: Vec<(TemplateKey, String)> = self
+                .templates
+                .iter()
+                .map(|(key, panel)| {
+                    let text = panel.template.widest(|name| match name {
+                        "net.down" | "net.up" => Some(template::Value::Text(network_rate.clone())),
+                        "disk.read" | "disk.write" => {
+                            Some(template::Value::Text(disks_rate.clone()))
+                        }
+                        _ => Some(template::Value::Number(0.0)),
+                    });
+                    (key.clone(), text)
+                })
+                .collect();
+            for (key, text) in widest {
+                let width = self.measure_text_width(&text, &attrs);
+                if let Some(panel) = self.templates.get_mut(&key) {
+                    panel.width = width;
+                }
+            }
         }
     }
 
@@ -0,0 +1,98 @@
+use cosmic::iced::mouse::Cursor;
+use cosmic::iced::{Point, Rectangle, Renderer, Size};
+use cosmic::iced_widget::canvas::Geometry;
+use cosmic::theme;
+use cosmic::widget::canvas;
+use std::collections::HashMap;
+
+use crate::app::Message;
+use crate::config::ChartColors;
+use crate::sensors::cpu::CpuLoad;
+
+use super::ChartColorsIced;
+
+// Same as the rounding of StackedBarSvg
+const CORNER_RADIUS: f32 = 4.0;
+const PADDING: f32 = 1.0;
+
+// The canvas counterpart of StackedBarSvg, a bar per core with the system load on top of the
+// user load
+#[derive(Debug)]
+pub struct BarChart {
+    pub cores: Vec<CpuLoad>,
+    pub bar_width: u16,
+    pub spacing: u16,
+    pub colors: ChartColorsIced,
+}
+
+impl BarChart {
+    pub fn new(
+        cores: &HashMap<usize, CpuLoad>,
+        bar_width: u16,
+        spacing: u16,
+        colors: &ChartColors,
+    ) -> Self {
+        BarChart {
+            cores: (0..cores.len())
+                .map_while(|i| cores.get(&i).copied())
+                .collect(),
+            bar_width,
+            spacing,
+            colors: (*colors).into(),
+        }
+    }
+}
+
+impl canvas::Program<Message, theme::Theme> for BarChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &theme::Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+
+        super::draw_background(&mut frame, CORNER_RADIUS, &self.colors);
+
+        let available = bounds.height - 2.0 * PADDING;
+        let bottom = PADDING + available;
+        let bar_width = f32::from(self.bar_width);
+
+        super::draw_clipped(&mut frame, CORNER_RADIUS, |frame, _| {
+            for (i, core) in self.cores.iter().enumerate() {
+                let x = PADDING + i as f32 * f32::from(self.bar_width + self.spacing);
+
+                let user = core.user_pct.clamp(0.0, 100.0) as f32;
+                let system = core.system_pct.clamp(0.0, 100.0) as f32;
+                let user_height = (available * user / 100.0).trunc();
+                let system_height = (available * system / 100.0)
+                    .trunc()
+                    .min(available - user_height);
+
+                // User load at the bottom, system load stacked on top
+                if user_height > 0.0 {
+                    frame.fill_rectangle(
+                        Point::new(x, bottom - user_height),
+                        Size::new(bar_width, user_height),
+                        self.colors.graph1,
+                    );
+                }
+                if system_height > 0.0 {
+                    frame.fill_rectangle(
+                        Point::new(x, bottom - user_height - system_height),
+                        Size::new(bar_width, system_height),
+                        self.colors.graph2,
+                    );
+                }
+            }
+        });
+
+        super::draw_frame(&mut frame, CORNER_RADIUS, &self.colors);
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,106 @@
+use cosmic::iced::mouse::Cursor;
+use cosmic::iced::{Point, Rectangle, Renderer, Size};
+use cosmic::iced_widget::canvas::Geometry;
+use cosmic::theme;
+use cosmic::widget::canvas::{self, Path};
+
+use crate::app::Message;
+use crate::config::ChartColors;
+
+use super::ChartColorsIced;
+
+// Same sizes as svg_graph::gauge on a 42 pixel chart
+const MIN_THICKNESS: f32 = 6.0;
+const GAP: f32 = 2.0;
+const TRACK_RADIUS: f32 = 2.0;
+const FILL_RADIUS: f32 = 1.0;
+
+// The canvas counterpart of svg_graph::gauge, a bar per value along the longer side
+#[derive(Debug)]
+pub struct GaugeChart {
+    pub fractions: Vec<f32>,
+    pub colors: ChartColorsIced,
+}
+
+impl GaugeChart {
+    pub fn new(fractions: &[f64], colors: &ChartColors) -> Self {
+        GaugeChart {
+            fractions: fractions
+                .iter()
+                .map(|fraction| fraction.max(0.0).min(1.0) as f32)
+                .collect(),
+            colors: (*colors).into(),
+        }
+    }
+}
+
+impl canvas::Program<Message, theme::Theme> for GaugeChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &theme::Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+
+        let horizontal = bounds.width > bounds.height;
+        let (long, short) = if horizontal {
+            (bounds.width, bounds.height)
+        } else {
+            (bounds.height, bounds.width)
+        };
+
+        let bars = self.fractions.len().max(1) as f32;
+        let thickness = (short / 3.0).round().max(MIN_THICKNESS);
+        let total = bars * thickness + (bars - 1.0) * GAP;
+        let first = ((short - total) / 2.0).max(0.0);
+
+        // Positions are along and across the bar, swapped for a vertical one
+        let rect = |along: f32, across: f32, length: f32, width: f32| {
+            if horizontal {
+                (Point::new(along, across), Size::new(length, width))
+            } else {
+                (
+                    Point::new(across, long - along - length),
+                    Size::new(width, length),
+                )
+            }
+        };
+
+        // Gradients run along the bars, from the empty end to the full one
+        let (empty, full) = if horizontal {
+            (Point::new(2.5, 0.0), Point::new(long - 2.5, 0.0))
+        } else {
+            (Point::new(0.0, long - 2.5), Point::new(0.0, 2.5))
+        };
+
+        for (index, fraction) in self.fractions.iter().enumerate() {
+            let (color, stops) = if index == 0 {
+                (self.colors.graph1, self.colors.gradient1)
+            } else {
+                (self.colors.graph2, self.colors.gradient2)
+            };
+            let across = first + index as f32 * (thickness + GAP);
+
+            let (position, size) = rect(1.5, across + 0.5, long - 3.0, thickness - 1.0);
+            let track = Path::rounded_rectangle(position, size, TRACK_RADIUS.into());
+            frame.fill(&track, self.colors.background);
+            frame.stroke(&track, super::line_stroke(self.colors.frame));
+
+            let filled = (long - 5.0) * fraction;
+            if filled > 0.0 {
+                let (position, size) = rect(2.5, across + 1.5, filled, thickness - 3.0);
+                frame.fill(
+                    &Path::rounded_rectangle(position, size, FILL_RADIUS.into()),
+                    super::fill(super::opaque(color), stops, empty, full),
+                );
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -1,37 +1,29 @@
 use cosmic::iced::mouse::Cursor;
-use cosmic::iced::{Point, Renderer};
-use cosmic::iced::{Rectangle, Size};
+use cosmic::iced::{Point, Rectangle, Renderer};
 use cosmic::iced_widget::canvas::Geometry;
 use cosmic::theme;
-use cosmic::widget::canvas::{self, Stroke, Style, path};
+use cosmic::widget::canvas::{self, Path};
 use std::collections::VecDeque;
 
-use cosmic::iced::Color;
-
 use crate::app::Message;
 use crate::config::ChartColors;
 
 use super::ChartColorsIced;
 
+// The canvas counterpart of svg_graph::heat, a graph filled with the gradient of graph1,
+// orange to red from bottom to top by default
 #[derive(Debug)]
 pub struct HeatChart {
-    pub steps: usize,
-    pub samples: VecDeque<f64>,
-    pub max_y: Option<f64>,
+    pub samples: Vec<f64>,
+    pub max_y: f64,
     pub colors: ChartColorsIced,
 }
 
 impl HeatChart {
-    pub fn new(
-        steps: usize,
-        samples: &VecDeque<f64>,
-        max: Option<f64>,
-        colors: &ChartColors,
-    ) -> Self {
+    pub fn new(samples: &VecDeque<f64>, max_y: u64, colors: &ChartColors) -> Self {
         HeatChart {
-            steps,
-            samples: samples.clone(),
-            max_y: max,
+            samples: samples.iter().copied().collect(),
+            max_y: max_y as f64,
             colors: (*colors).into(),
         }
     }
@@ -44,153 +36,51 @@ impl canvas::Program<Message, theme::Theme> for HeatChart {
         &self,
         _state: &Self::State,
         renderer: &Renderer,
-        theme: &theme::Theme,
+        _theme: &theme::Theme,
         bounds: Rectangle,
         _cursor: Cursor,
     ) -> Vec<Geometry<Renderer>> {
-
-        let frame_start = 1.5;
-
         let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let radius = super::corner_radius(bounds.size());
 
-        let top_left = Point::new(
-            frame.center().x - frame.size().width / 2. + 1.,
-            frame.center().y - frame.size().height / 2. + 1.,
-        );
-        let bottom_right = Point::new(
-            frame.center().x + frame.size().width / 2. - 1.,
-            frame.center().y + frame.size().height / 2. - 1.,
-        );
-        let scale = bottom_right - top_left;
+        super::draw_background(&mut frame, radius, &self.colors);
 
-        // Find max value, if not provided will scale to largest value
-        let max_value: f64 = if let Some(m) = self.max_y {
-            m
+        let width = bounds.width - 2.0;
+        let height = bounds.height - 2.0;
+        let step = width / self.samples.len().saturating_sub(1).max(1) as f32;
+        let scaling = if self.max_y > 0.0 {
+            height / self.max_y as f32
         } else {
-            let max_point = self.samples.iter().cloned().fold(0.0, f64::max);
-            if max_point > 0.0 { max_point } else { 100.0 }
+            0.0
         };
 
-        let corner_radius = frame.size().width.min(frame.size().height) / 7.0;
-
-        let frame_color = self.colors.color2;
-        let bg_color = self.colors.color1;
-
-        frame.fill_rectangle(Point { x: frame_start, y: frame_start }, Size {
-            width: frame.size().width - 2.0,
-            height: frame.size().height - 2.0,
-        }, bg_color);
-
-        let step_length = scale.x / self.steps as f32;
-        let scaling = (scale.y - 1.0) as f64 / max_value;
-
-        let mut builder = path::Builder::new();
-        let mut shade_builder = path::Builder::new();
-
-        let mut previous_point = None;
-
-        for i in 0..self.samples.len() {
-            let sample = self.samples[i].min(max_value);
-            let y = 0.5 + scale.y - (sample * scaling) as f32;
-            let x = (top_left.x + step_length * i as f32).round();
-            let p = Point::new(x, y.round());
-
-            if i == 0 {
-                builder.move_to(p);
-                shade_builder.move_to(Point::new(top_left.x, bottom_right.y));
-                shade_builder.line_to(p);
-            } else {
-                builder.line_to(p);
-                shade_builder.line_to(p);
-            }
-
-            previous_point = Some(p);
-        }
-
-        /* Bezier version, much more expensive, only looks better on big charts
-
-                // Draw graph
-                let step_length = scale.x / self.steps as f32;
-                let scaling = (scale.y - 0.5) as f64 / max_value;
-
-                let mut builder = path::Builder::new();
-                let mut shade_builder = path::Builder::new();
-
-                let mut previous_point: Option<Point> = None;
-
-                for i in 0..self.samples.len() {
-                    let sample = self.samples[i].min(max_value);
-                    let y = 0.5 + scale.y - (sample * scaling) as f32;
-                    let x = (top_left.x + step_length * i as f32).round();
-                    let p = Point::new(x, y.round());
-
+        if !self.samples.is_empty() {
+            let shade = Path::new(|p| {
+                for (i, sample) in self.samples.iter().enumerate() {
+                    let value = sample.clamp(0.0, self.max_y) as f32;
+                    let point = Point::new(
+                        (1.0 + step * i as f32).round(),
+                        (1.0 + height - value * scaling).round(),
+                    );
                     if i == 0 {
-                        shade_builder.move_to(Point::new(top_left.x, bottom_right.y));
-                        shade_builder.line_to(p);
-                    } else if let Some(prev) = previous_point {
-                        let control_prev = Point::new(prev.x + step_length * 0.5, prev.y);
-                        let control_curr = Point::new(p.x - step_length * 0.5, p.y);
-
-                        builder.move_to(prev);
-                        builder.bezier_curve_to(control_prev, control_curr, p);
-                        shade_builder.bezier_curve_to(control_prev, control_curr, p);
+                        p.move_to(point);
+                    } else {
+                        p.line_to(point);
                     }
-
-                    previous_point = Some(p);
                 }
-        */
-
-        if previous_point.is_some() {
-            shade_builder.line_to(bottom_right);
-        }
-
-        // Draw the chart, with a gradient
-
-        let linear = cosmic::widget::canvas::gradient::Linear::new(
-            Point::new(0.0, frame.size().height),
-            Point::new(0.0, 0.0),
-        )
-        .add_stop(0.0, Color::from_rgba(1.0, 0.65, 0.0, 1.0))
-        .add_stop(1.0, Color::from_rgba(1.0, 0.0, 0.0, 1.0));
-
-        frame.fill(
-            &shade_builder.build(),
-            canvas::Fill {
-                style: canvas::Style::Gradient(canvas::Gradient::Linear(linear)),
-                ..Default::default()
-            },
-        );
-
-        let frame_size: Size = Size {
-            width: frame.size().width - 2.0,
-            height: frame.size().height - 2.0,
-        };
-
-        // Erase corners, with transparent pixels
-        for i in 0..=corner_radius.trunc() as i32 {
-            let mut square = path::Builder::new();
-            square.rounded_rectangle(Point { x: frame_start, y: frame_start }, frame_size, i.into());
-            frame.stroke(
-                &square.build(),
-                Stroke {
-                    style: Style::Solid(theme.cosmic().bg_color().into()),
-                    width: 1.0,
-                    ..Default::default()
-                },
-            );
+                p.line_to(Point::new(1.0 + width, 1.0 + height));
+                p.line_to(Point::new(1.0, 1.0 + height));
+                p.close();
+            });
+
+            let fill =
+                super::vertical_fill(bounds.height, self.colors.graph1, self.colors.gradient1);
+            super::draw_clipped(&mut frame, radius, |frame, _| {
+                frame.fill(&shade, fill.clone());
+            });
         }
 
-        // Draw background square
-        let mut square = path::Builder::new();
-        square.rounded_rectangle(Point { x: frame_start, y: frame_start }, frame_size, corner_radius.into());
-        frame.stroke(
-            &square.build(),
-            Stroke {
-                style: Style::Solid(frame_color),
-                width: 1.0,
-                ..Default::default()
-            },
-        );
+        super::draw_frame(&mut frame, radius, &self.colors);
 
         vec![frame.into_geometry()]
     }
@@ -1,9 +1,9 @@
+use cosmic::iced::alignment::{Horizontal, Vertical};
 use cosmic::iced::mouse::Cursor;
-use cosmic::iced::{Color, Point, Renderer};
-use cosmic::iced::{Rectangle, Size};
+use cosmic::iced::{Color, Point, Rectangle, Renderer};
 use cosmic::iced_widget::canvas::Geometry;
-use cosmic::widget::canvas::{self, Path, Stroke, Style, path};
 use cosmic::theme;
+use cosmic::widget::canvas::{self, Path, Stroke, Text};
 use std::collections::VecDeque;
 
 use crate::app::Message;
@@ -11,6 +11,12 @@ use crate::config::ChartColors;
 
 use super::ChartColorsIced;
 
+// Adaptive charts never scale below this, same as the SVG charts
+const MIN_ADAPTIVE_MAX: f64 = 40.0;
+
+// Size of the scale label, on a 42 pixel high chart like the SVG one
+const GRID_FONT_SIZE: f32 = 8.0;
+
 // Trait for numeric sample types
 pub trait SampleValue: Copy + PartialOrd {
     fn to_f64(self) -> f64;
@@ -28,224 +34,291 @@ impl SampleValue for f64 {
     }
 }
 
-// Generic LineChart widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    // One graph
+    Single,
+    // Two graphs on top of each other, in graph1 and graph2
+    Double,
+    // A band in graph3 from the first graph up to the second
+    Stacked,
+}
+
+// LineChart widget, the canvas counterpart of svg_graph::line and its variants
 // * Draws a graph of the last 'steps' samples.
 // * Can take u64 or f64.
-// * Can be adaptive or take a fixed max_y.
-// * If samples2.len() is 0, only draws samples1 graph.
+// * Can be adaptive or take a fixed max_y, on a linear or log scale.
+// * Can draw gridlines with a label for the top of the scale.
 #[derive(Debug, Clone)]
-pub struct LineChart<T: SampleValue> {
-    pub steps: usize,
-    pub samples1: VecDeque<T>,
-    pub samples2: VecDeque<T>,
-    pub max_y: Option<T>,
-    pub colors: ChartColorsIced,
+pub struct LineChart {
+    steps: usize,
+    samples1: Vec<f64>,
+    samples2: Vec<f64>,
+    max_y: f64,
+    log_scale: bool,
+    grid: Option<String>,
+    kind: LineKind,
+    colors: ChartColorsIced,
 }
 
-impl<T: SampleValue> LineChart<T> {
-    pub fn new(
+impl LineChart {
+    /// One graph scaled to `max_y`, like `svg_graph::line`
+    pub fn new<T: SampleValue>(samples: &VecDeque<T>, max_y: T, colors: &ChartColors) -> Self {
+        Self::build(
+            LineKind::Single,
+            samples.len(),
+            samples,
+            &VecDeque::new(),
+            Some(max_y),
+            colors,
+        )
+    }
+
+    /// One graph of the last `steps` samples, scaled to the largest if there is no max
+    pub fn adaptive<T: SampleValue>(
+        samples: &VecDeque<T>,
+        steps: usize,
+        colors: &ChartColors,
+        max_y: Option<T>,
+    ) -> Self {
+        Self::build(
+            LineKind::Single,
+            steps,
+            samples,
+            &VecDeque::new(),
+            max_y,
+            colors,
+        )
+    }
+
+    /// Two graphs, like `svg_graph::double_line`
+    pub fn double<T: SampleValue>(
+        samples: &VecDeque<T>,
+        samples2: &VecDeque<T>,
+        steps: usize,
+        colors: &ChartColors,
+        max_y: Option<T>,
+    ) -> Self {
+        Self::build(LineKind::Double, steps, samples, samples2, max_y, colors)
+    }
+
+    /// Used samples with a band up to the allocated ones, like `svg_graph::line_stacked`
+    pub fn stacked<T: SampleValue>(
+        samples_used: &VecDeque<T>,
+        samples_allocated: &VecDeque<T>,
+        max_y: T,
+        colors: &ChartColors,
+    ) -> Self {
+        Self::build(
+            LineKind::Stacked,
+            samples_used.len(),
+            samples_used,
+            samples_allocated,
+            Some(max_y),
+            colors,
+        )
+    }
+
+    /// Plot the samples on a log scale, which keeps small rates visible next to a large one
+    pub fn log_scale(mut self, log_scale: bool) -> Self {
+        if log_scale {
+            for sample in self.samples1.iter_mut().chain(self.samples2.iter_mut()) {
+                *sample = sample.max(0.0).ln_1p();
+            }
+            self.max_y = self.max_y.max(0.0).ln_1p();
+        }
+        self.log_scale = log_scale;
+        self
+    }
+
+    /// Gridlines at round values of the scale with `label` for the top of it in a corner,
+    /// or no grid for `None`
+    pub fn grid(mut self, label: Option<String>) -> Self {
+        self.grid = label;
+        self
+    }
+
+    fn build<T: SampleValue>(
+        kind: LineKind,
         steps: usize,
         samples1: &VecDeque<T>,
         samples2: &VecDeque<T>,
-        max: Option<T>,
+        max_y: Option<T>,
         colors: &ChartColors,
     ) -> Self {
+        let last = |samples: &VecDeque<T>| -> Vec<f64> {
+            let start = samples.len().saturating_sub(steps);
+            samples.iter().skip(start).map(|s| s.to_f64()).collect()
+        };
+        let samples1 = last(samples1);
+        let samples2 = last(samples2);
+
+        let max_y = max_y.map(SampleValue::to_f64).unwrap_or_else(|| {
+            samples1
+                .iter()
+                .chain(samples2.iter())
+                .copied()
+                .fold(MIN_ADAPTIVE_MAX, f64::max)
+        });
+
         Self {
             steps,
-            samples1: samples1.clone(),
-            samples2: samples2.clone(),
-            max_y: max,
+            samples1,
+            samples2,
+            max_y,
+            log_scale: false,
+            grid: None,
+            kind,
             colors: (*colors).into(),
         }
     }
+
+    // Gridlines as in `svg_graph::with_grid`, in the frame color
+    fn draw_grid(&self, frame: &mut canvas::Frame, bounds: Rectangle, label: &str) {
+        let width = bounds.width - 2.0;
+        let height = bounds.height - 2.0;
+        // The samples and max of a log scale chart are already on the log scale
+        let max_y = if self.log_scale {
+            self.max_y.exp_m1()
+        } else {
+            self.max_y
+        };
+        let color = Color {
+            a: self.colors.frame.a * 0.4,
+            ..self.colors.frame
+        };
+
+        for tick in crate::svg_graph::grid_ticks(max_y, self.log_scale) {
+            let fraction = crate::svg_graph::scale_fraction(tick, max_y, self.log_scale) as f32;
+            let y = (1.0 + height * (1.0 - fraction)).round() + 0.5;
+            let line = Path::line(Point::new(1.0, y), Point::new(1.0 + width, y));
+            frame.stroke(&line, Stroke::default().with_color(color).with_width(1.0));
+        }
+
+        if !label.is_empty() {
+            let scale = bounds.height / 42.0;
+            frame.fill_text(Text {
+                content: label.to_owned(),
+                position: Point::new(3.0 * scale, 2.0 * scale),
+                color: self.colors.frame,
+                size: cosmic::iced::Pixels(GRID_FONT_SIZE * scale),
+                align_x: Horizontal::Left.into(),
+                align_y: Vertical::Top,
+                ..Default::default()
+            });
+        }
+    }
+
+    // Points of a graph, inside the 1 pixel frame
+    fn points(&self, samples: &[f64], bounds: Rectangle) -> Vec<Point> {
+        let width = bounds.width - 2.0;
+        let height = bounds.height - 2.0;
+        let step = width / self.steps.saturating_sub(1).max(1) as f32;
+        let scaling = if self.max_y > 0.0 {
+            height / self.max_y as f32
+        } else {
+            0.0
+        };
+
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                let value = sample.clamp(0.0, self.max_y) as f32;
+                Point::new(
+                    (1.0 + step * i as f32).round(),
+                    (1.0 + height - value * scaling).round(),
+                )
+            })
+            .collect()
+    }
 }
 
-impl<T: SampleValue + 'static> canvas::Program<Message, theme::Theme> for LineChart<T> {
+impl canvas::Program<Message, theme::Theme> for LineChart {
     type State = ();
 
     fn draw(
         &self,
         _state: &Self::State,
         renderer: &Renderer,
-        theme: &theme::Theme,
+        _theme: &theme::Theme,
         bounds: Rectangle,
         _cursor: Cursor,
     ) -> Vec<Geometry<Renderer>> {
-        fn draw_graph(frame: &mut canvas::Frame, path: Path, shade: Path, mut color: Color) {
-            frame.stroke(
-                &path,
-                canvas::Stroke {
-                    style: canvas::Style::Solid(color),
-                    width: 1.0,
-                    line_join: canvas::LineJoin::Round,
-                    ..Default::default()
-                },
-            );
-
-            color.a = 0.3;
-
-            frame.fill(
-                &shade,
-                canvas::Fill {
-                    style: canvas::Style::Solid(color),
-                    ..Default::default()
-                },
-            );
-        }
-
         let mut frame = canvas::Frame::new(renderer, bounds.size());
-        let top_left = Point::new(
-            frame.center().x - frame.size().width / 2. + 1.,
-            frame.center().y - frame.size().height / 2. + 1.,
-        );
-        let bottom_right = Point::new(
-            frame.center().x + frame.size().width / 2. - 1.,
-            frame.center().y + frame.size().height / 2. - 1.,
-        );
-        // Frame needs to be on mid-pixel, to avoid an anti-aliased outwashed double line
-        let frame_start = 1.5;
-        let frame_size = Size {
-            width: frame.size().width - 2.0,
-            height: frame.size().height - 2.0,
+        let radius = super::corner_radius(bounds.size());
+
+        super::draw_background(&mut frame, radius, &self.colors);
+
+        let points1 = self.points(&self.samples1, bounds);
+        let points2 = match self.kind {
+            LineKind::Single => Vec::new(),
+            LineKind::Double => self.points(&self.samples2, bounds),
+            LineKind::Stacked => {
+                // The top of the band is never below the used line
+                let top: Vec<f64> = self
+                    .samples1
+                    .iter()
+                    .zip(self.samples2.iter())
+                    .map(|(used, allocated)| used.max(*allocated))
+                    .collect();
+                self.points(&top, bounds)
+            }
         };
 
-        let corner_radius = frame.size().width.min(frame.size().height) / 7.0;
-
-        let scale = bottom_right - top_left;
-
-        // Fill background
-        let mut square = path::Builder::new();
-        square.rounded_rectangle(
-            Point {
-                x: frame_start,
-                y: frame_start,
-            },
-            frame_size,
-            corner_radius.into(),
-        );
-        frame.fill(
-            &square.build(),
-            canvas::Fill {
-                style: Style::Solid(self.colors.color1),
-                ..Default::default()
-            },
-        );
-
-        let min = scale.y as f64;
-        let max_value = self.max_y.map(|v| v.to_f64()).unwrap_or_else(|| {
-            let max1 = self.samples1.iter().map(|s| s.to_f64()).fold(0.0, f64::max);
-            let max2 = self.samples2.iter().map(|s| s.to_f64()).fold(0.0, f64::max);
-            max1.max(max2).max(min)
-        });
-
-        let dual_graph = !self.samples2.is_empty();
-
-        let step_length = scale.x / (self.steps - 1) as f32;
-        let scaling = (scale.y - 1.0) as f64 / max_value;
-
-        let mut builder1 = path::Builder::new();
-        let mut builder2 = path::Builder::new();
-        let mut shade1 = path::Builder::new();
-        let mut shade2 = path::Builder::new();
-
-        let len = self.samples1.len().min(self.steps);
-        let start_index1 = self.samples1.len().saturating_sub(len);
-        let start_index2 = self.samples2.len().saturating_sub(len);
-
-        let iter1 = self.samples1.iter().skip(start_index1).take(len);
-        let iter2 = self.samples2.iter().skip(start_index2).take(len);
-
-        let mut iter2_opt = dual_graph.then_some(iter2);
-
-        for (i, sample1) in iter1.enumerate() {
-            let x = (top_left.x + step_length * i as f32).round();
-            let y1 = 0.5 + scale.y - (sample1.to_f64().min(max_value) * scaling) as f32;
-            let p1 = Point::new(x, y1.round());
-
-            if i == 0 {
-                builder1.move_to(p1);
-                shade1.move_to(Point::new(top_left.x, bottom_right.y));
-                shade1.line_to(p1);
-            } else {
-                builder1.line_to(p1);
-                shade1.line_to(p1);
-            }
+        super::draw_clipped(&mut frame, radius, |frame, size| {
+            super::draw_graph(
+                frame,
+                size.height,
+                &points1,
+                self.colors.graph1,
+                self.colors.gradient1,
+            );
 
-            if let Some(iter2) = iter2_opt.as_mut() {
-                if let Some(sample2) = iter2.next() {
-                    let y2 = 0.5 + scale.y - (sample2.to_f64().min(max_value) * scaling) as f32;
-                    let p2 = Point::new(x, y2.round());
-
-                    if i == 0 {
-                        builder2.move_to(p2);
-                        shade2.move_to(Point::new(top_left.x, bottom_right.y));
-                        shade2.line_to(p2);
-                    } else {
-                        builder2.line_to(p2);
-                        shade2.line_to(p2);
-                    }
+            match self.kind {
+                LineKind::Single => (),
+                LineKind::Double => {
+                    super::draw_graph(
+                        frame,
+                        size.height,
+                        &points2,
+                        self.colors.graph2,
+                        self.colors.gradient2,
+                    );
+                }
+                LineKind::Stacked => {
+                    let band = Path::new(|p| {
+                        for (i, point) in points2.iter().enumerate() {
+                            if i == 0 {
+                                p.move_to(*point);
+                            } else {
+                                p.line_to(*point);
+                            }
+                        }
+                        for point in points1.iter().take(points2.len()).rev() {
+                            p.line_to(*point);
+                        }
+                        p.close();
+                    });
+                    let fill = super::vertical_fill(
+                        size.height,
+                        self.colors.graph3,
+                        self.colors.gradient3,
+                    );
+                    frame.fill(&band, fill);
+                    frame.stroke(
+                        &super::polyline(&points2),
+                        super::line_stroke(super::opaque(self.colors.graph3)),
+                    );
                 }
             }
-        }
-
-        shade1.line_to(bottom_right);
-        if dual_graph {
-            shade2.line_to(bottom_right);
-        }
-
-        draw_graph(
-            &mut frame,
-            builder1.build(),
-            shade1.build(),
-            self.colors.color4,
-        );
-
-        if dual_graph {
-            draw_graph(
-                &mut frame,
-                builder2.build(),
-                shade2.build(),
-                self.colors.color3,
-            );
-        }
+        });
 
-        for i in 0..=corner_radius.trunc() as i32 {
-            let mut square = path::Builder::new();
-            square.rounded_rectangle(
-                Point {
-                    x: frame_start,
-                    y: frame_start,
-                },
-                frame_size,
-                i.into(),
-            );
-            frame.stroke(
-                &square.build(),
-                Stroke {
-                    style: Style::Solid(theme.cosmic().bg_color().into()),
-                    width: 1.0,
-                    ..Default::default()
-                },
-            );
+        if let Some(label) = &self.grid {
+            self.draw_grid(&mut frame, bounds, label);
         }
 
-        let mut square = path::Builder::new();
-        square.rounded_rectangle(
-            Point {
-                x: frame_start,
-                y: frame_start,
-            },
-            frame_size,
-            corner_radius.into(),
-        );
-        frame.stroke(
-            &square.build(),
-            Stroke {
-                style: Style::Solid(self.colors.color2),
-                width: 1.0,
-                ..Default::default()
-            },
-        );
+        super::draw_frame(&mut frame, radius, &self.colors);
 
         vec![frame.into_geometry()]
     }
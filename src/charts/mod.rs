@@ -1,17 +1,43 @@
-use crate::config::ChartColors;
+//! Canvas versions of the charts in `svg_graph`.
+//!
+//! They draw the same shapes straight into the frame, which skips building and
+//! rasterising an SVG string on every refresh. Which renderer is used is a runtime
+//! choice, see `DisplayOptions` in `sensors`.
+
+use cosmic::iced::{Color, Point, Rectangle, Size, Vector};
+use cosmic::widget::canvas::{self, Frame, Path, Stroke, Style};
+
+use crate::config::{ChartColors, Gradient};
+
+pub mod bars;
+pub mod gauge;
+pub mod heat;
+pub mod line;
+pub mod ring;
+pub mod sleeping;
+pub mod sparkline;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ChartColorsIced {
-    pub color1: cosmic::iced::Color,
-    pub color2: cosmic::iced::Color,
-    pub color3: cosmic::iced::Color,
-    pub color4: cosmic::iced::Color,
+    pub background: Color,
+    pub frame: Color,
+    pub text: Color,
+    pub graph1: Color,
+    pub graph2: Color,
+    pub graph3: Color,
+    /// Stops of the graphs with a gradient fill
+    pub gradient1: Option<GradientStops>,
+    pub gradient2: Option<GradientStops>,
+    pub gradient3: Option<GradientStops>,
 }
 
+/// Offsets from 0.0 to 1.0 and colors, a two stop gradient repeats its last stop
+pub type GradientStops = [(f32, Color); 3];
+
 impl From<ChartColors> for ChartColorsIced {
     fn from(colors: ChartColors) -> Self {
-        fn to_iced_color(srgba: cosmic::cosmic_theme::palette::Srgba<u8>) -> cosmic::iced::Color {
-            cosmic::iced::Color {
+        fn to_iced_color(srgba: cosmic::cosmic_theme::palette::Srgba<u8>) -> Color {
+            Color {
                 r: srgba.color.red as f32 / 255.0,
                 g: srgba.color.green as f32 / 255.0,
                 b: srgba.color.blue as f32 / 255.0,
@@ -19,15 +45,189 @@ impl From<ChartColors> for ChartColorsIced {
             }
         }
 
+        fn gradient_stops(
+            gradient: Option<Gradient>,
+            color: cosmic::cosmic_theme::palette::Srgba<u8>,
+        ) -> Option<GradientStops> {
+            gradient.map(|gradient| {
+                let stops = gradient.stops(color);
+                std::array::from_fn(|i| {
+                    let (offset, color) = stops[i.min(stops.len() - 1)];
+                    (offset, to_iced_color(color))
+                })
+            })
+        }
+
         ChartColorsIced {
-            color1: to_iced_color(colors.color1),
-            color2: to_iced_color(colors.color2),
-            color3: to_iced_color(colors.color3),
-            color4: to_iced_color(colors.color4),
+            background: to_iced_color(colors.background),
+            frame: to_iced_color(colors.frame),
+            text: to_iced_color(colors.text),
+            graph1: to_iced_color(colors.graph1),
+            graph2: to_iced_color(colors.graph2),
+            graph3: to_iced_color(colors.graph3),
+            gradient1: gradient_stops(colors.gradient1, colors.graph1),
+            gradient2: gradient_stops(colors.gradient2, colors.graph2),
+            gradient3: gradient_stops(colors.gradient3, colors.graph3),
         }
     }
 }
 
-pub mod ring;
-pub mod heat;
-pub mod line;
\ No newline at end of file
+/// The SVG charts stroke their lines with the graph color without its alpha
+pub(crate) fn opaque(color: Color) -> Color {
+    Color { a: 1.0, ..color }
+}
+
+/// Rounded rectangle inset by half a pixel, so a 1px stroke stays on whole pixels
+pub(crate) fn rounded_frame(size: Size, radius: f32) -> Path {
+    Path::new(|p| {
+        p.rounded_rectangle(
+            Point::new(0.5, 0.5),
+            Size::new(size.width - 1.0, size.height - 1.0),
+            radius.into(),
+        );
+    })
+}
+
+/// Fill the chart background, drawn before the graph
+pub(crate) fn draw_background(frame: &mut Frame, radius: f32, colors: &ChartColorsIced) {
+    frame.fill(&rounded_frame(frame.size(), radius), colors.background);
+}
+
+/// Draw the graph clipped to the rounded background, like the clip path of the SVG charts.
+///
+/// A canvas only clips to rectangles, so the rounded outline is covered by one rectangle
+/// for the straight sides and one per pixel row of the corners, each drawing the graph
+/// again. Nothing is painted over the corners, so the panel shows through whatever its
+/// color and opacity. `draw` gets the size of the whole chart, the clipped frame only
+/// knows its own region.
+pub(crate) fn draw_clipped(frame: &mut Frame, radius: f32, draw: impl Fn(&mut Frame, Size)) {
+    let size = frame.size();
+    let rows = radius.ceil().min((size.height / 2.0).floor()).max(0.0);
+
+    let mut regions = vec![Rectangle::new(
+        Point::new(0.0, rows),
+        Size::new(size.width, size.height - 2.0 * rows),
+    )];
+    for row in 0..rows as usize {
+        let top = row as f32;
+        // Height of the middle of the row above the center of the rounding
+        let dy = radius - (top + 0.5);
+        let inset = (radius - (radius * radius - dy * dy).max(0.0).sqrt()).round();
+        let row_size = Size::new(size.width - 2.0 * inset, 1.0);
+        regions.push(Rectangle::new(Point::new(inset, top), row_size));
+        regions.push(Rectangle::new(
+            Point::new(inset, size.height - 1.0 - top),
+            row_size,
+        ));
+    }
+
+    for region in regions {
+        frame.with_clip(region, |clipped| {
+            clipped.translate(Vector::new(-region.x, -region.y));
+            draw(clipped, size);
+        });
+    }
+}
+
+/// Stroke the frame around the chart, drawn last
+pub(crate) fn draw_frame(frame: &mut Frame, radius: f32, colors: &ChartColorsIced) {
+    frame.stroke(
+        &rounded_frame(frame.size(), radius),
+        Stroke {
+            style: Style::Solid(colors.frame),
+            width: 1.0,
+            ..Default::default()
+        },
+    );
+}
+
+/// The corner radius of the SVG charts, 7 on a 42 pixel chart
+pub(crate) fn corner_radius(size: Size) -> f32 {
+    size.width.min(size.height) / 6.0
+}
+
+/// A solid fill in `color`, or with `stops` a gradient running from `from` to `to`
+pub(crate) fn fill(
+    color: Color,
+    stops: Option<GradientStops>,
+    from: Point,
+    to: Point,
+) -> canvas::Fill {
+    match stops {
+        Some(stops) => {
+            let gradient = stops.iter().fold(
+                canvas::gradient::Linear::new(from, to),
+                |gradient, (offset, color)| gradient.add_stop(*offset, *color),
+            );
+            canvas::Fill {
+                style: Style::Gradient(canvas::Gradient::Linear(gradient)),
+                ..Default::default()
+            }
+        }
+        None => color.into(),
+    }
+}
+
+/// Fill from the bottom of the graph area to the top of a chart `height` high, like the
+/// SVG charts
+pub(crate) fn vertical_fill(
+    height: f32,
+    color: Color,
+    stops: Option<GradientStops>,
+) -> canvas::Fill {
+    fill(
+        color,
+        stops,
+        Point::new(0.0, height - 1.0),
+        Point::new(0.0, 1.0),
+    )
+}
+
+/// Fill under a graph line and stroke the line itself, on a chart `height` high
+pub(crate) fn draw_graph(
+    frame: &mut Frame,
+    height: f32,
+    points: &[Point],
+    color: Color,
+    stops: Option<GradientStops>,
+) {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return;
+    };
+    let bottom = height - 1.0;
+
+    let shade = Path::new(|p| {
+        p.move_to(*first);
+        for point in &points[1..] {
+            p.line_to(*point);
+        }
+        p.line_to(Point::new(last.x, bottom));
+        p.line_to(Point::new(first.x, bottom));
+        p.close();
+    });
+    let fill = vertical_fill(height, color, stops);
+    frame.fill(&shade, fill);
+
+    frame.stroke(&polyline(points), line_stroke(opaque(color)));
+}
+
+pub(crate) fn polyline(points: &[Point]) -> Path {
+    Path::new(|p| {
+        for (i, point) in points.iter().enumerate() {
+            if i == 0 {
+                p.move_to(*point);
+            } else {
+                p.line_to(*point);
+            }
+        }
+    })
+}
+
+pub(crate) fn line_stroke(color: Color) -> Stroke<'static> {
+    Stroke {
+        style: Style::Solid(color),
+        width: 1.0,
+        line_join: canvas::LineJoin::Round,
+        ..Default::default()
+    }
+}
@@ -2,6 +2,7 @@ use cosmic::Renderer;
 use cosmic::iced::Point;
 use cosmic::iced::Radians;
 use cosmic::iced::Rectangle;
+use cosmic::iced::alignment::{Horizontal, Vertical};
 use cosmic::iced::mouse::Cursor;
 use cosmic::theme;
 use cosmic::widget::canvas;
@@ -18,20 +19,31 @@ use crate::config::ChartColors;
 
 use super::ChartColorsIced;
 
+// The SVG ring is drawn on a 34x34 view box, sizes below are in those units
+const VIEW_BOX: f32 = 34.0;
+const RING_RADIUS: f32 = 15.9155;
+const RING_WIDTH: f32 = 2.0;
+const INNER_RADIUS: f32 = 12.9155;
+const FONT_SIZE: f32 = 19.2;
+
 #[derive(Debug)]
 pub struct RingChart {
-    // How much if the ring is filled. 0..100
+    // How much of the ring is filled. 0..100
     pub percent: f32,
 
+    // How much of the inside is filled from the bottom, if at all. 0..100
+    pub percent_inner: Option<f32>,
+
     //Text to display inside, if any
     pub text: String,
     pub colors: ChartColorsIced,
 }
 
 impl RingChart {
-    pub fn new(percent: f32, text: &str, colors: &ChartColors) -> Self {
+    pub fn new(text: &str, percent: u8, percent_inner: Option<u8>, colors: &ChartColors) -> Self {
         RingChart {
-            percent: if percent <= 100.0 { percent } else { 100.0 },
+            percent: f32::from(percent.min(100)),
+            percent_inner: percent_inner.map(|p| f32::from(p.min(100))),
             text: text.to_string(),
             colors: (*colors).into(),
         }
@@ -51,52 +63,80 @@ impl canvas::Program<Message, theme::Theme> for RingChart {
     ) -> Vec<Geometry<Renderer>> {
         let mut frame = canvas::Frame::new(renderer, bounds.size());
 
-        // The starting poing of the Ring graph, bottom/6pm
+        // The starting point of the Ring graph, bottom/6pm, filling clockwise
         let starting_point = PI / 2.0;
 
-        // Max height/width of chart/widget. Side length in a square
-        let limit = bounds.width.min(bounds.height)-2.0;
-
-        // Width and radius of ring
-        let stroke_width = 0.08 * limit;
-        let radius = (limit / 2.0) - stroke_width / 2.0;
+        // Side length of the square the ring fits in, scaled from the SVG view box
+        let scale = bounds.width.min(bounds.height) / VIEW_BOX;
         let center = Point::new(bounds.width / 2.0, bounds.height / 2.0);
+        let radius = RING_RADIUS * scale;
+        let stroke_width = RING_WIDTH * scale;
 
-        // Draw outer background ring segment as circle
-        let outer_circle = Path::circle(center, radius+(stroke_width / 2.0));
-        frame.fill(&outer_circle, self.colors.color3);
-
-        // Fill background color inside ring
-        let inner_circle = Path::circle(center, radius - stroke_width / 2.0);
-        frame.fill(&inner_circle, self.colors.color1);
-
-        // Draw highlighted ring segment showing status/percentage
-        let ring = Path::new(|p| {
-            p.arc(Arc {
-                center,
-                radius,
-                start_angle: Radians::from(starting_point),
-                end_angle: Radians::from(starting_point + (PI * 2.0 * (self.percent / 100.0))),
-            });
-        });
-
+        // Background inside the ring, and the ring track
+        let circle = Path::circle(center, radius);
+        frame.fill(&circle, self.colors.background);
         frame.stroke(
-            &ring,
+            &circle,
             canvas::Stroke {
-                style: canvas::Style::Solid(self.colors.color4),
+                style: canvas::Style::Solid(self.colors.graph2),
                 width: stroke_width,
                 ..Default::default()
             },
         );
 
-        // Create text object
+        // Highlighted ring segment showing status/percentage
+        if self.percent > 0.0 {
+            let ring = Path::new(|p| {
+                p.arc(Arc {
+                    center,
+                    radius,
+                    start_angle: Radians::from(starting_point),
+                    end_angle: Radians::from(starting_point + (PI * 2.0 * (self.percent / 100.0))),
+                });
+            });
+
+            frame.stroke(
+                &ring,
+                canvas::Stroke {
+                    style: canvas::Style::Solid(self.colors.graph1),
+                    width: stroke_width,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Inner circle filled from the bottom up
+        if let Some(percent) = self.percent_inner {
+            let inner = INNER_RADIUS * scale;
+            frame.fill(&Path::circle(center, inner), self.colors.background);
+
+            if percent >= 100.0 {
+                frame.fill(&Path::circle(center, inner), self.colors.graph3);
+            } else if percent > 0.0 {
+                // The segment below the fill level, between the angles where the level
+                // crosses the circle. Angles grow clockwise, so down is positive.
+                let level = 1.0 - 2.0 * percent / 100.0;
+                let start = level.asin();
+                let segment = Path::new(|p| {
+                    p.arc(Arc {
+                        center,
+                        radius: inner,
+                        start_angle: Radians::from(start),
+                        end_angle: Radians::from(PI - start),
+                    });
+                    p.close();
+                });
+                frame.fill(&segment, self.colors.graph3);
+            }
+        }
+
         let text = Text {
             content: self.text.clone(),
             position: center,
-            color: self.colors.color2,
-            size: cosmic::iced::Pixels(radius * 0.93),
-            horizontal_alignment: cosmic::iced::alignment::Horizontal::Center,
-            vertical_alignment: cosmic::iced::alignment::Vertical::Center,
+            color: self.colors.text,
+            size: cosmic::iced::Pixels(FONT_SIZE * scale),
+            align_x: Horizontal::Center.into(),
+            align_y: Vertical::Center,
             ..Default::default()
         };
 
@@ -0,0 +1,99 @@
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::mouse::Cursor;
+use cosmic::iced::{Pixels, Point, Rectangle, Renderer};
+use cosmic::iced_widget::canvas::Geometry;
+use cosmic::theme;
+use cosmic::widget::canvas::{self, LineDash, Path, Stroke, Style, Text};
+
+use crate::app::Message;
+use crate::config::ChartColors;
+
+use super::ChartColorsIced;
+
+// The SVG ring is drawn on a 34x34 view box and the other charts are 42 high
+const RING_VIEW_BOX: f32 = 34.0;
+const RING_RADIUS: f32 = 15.9155;
+const RING_WIDTH: f32 = 2.0;
+const RING_FONT_SIZE: f32 = 13.0;
+const CHART_HEIGHT: f32 = 42.0;
+const FONT_SIZE: f32 = 15.0;
+const DASH: f32 = 3.0;
+
+// The canvas counterpart of svg_graph::sleeping, a dashed outline in the shape of the chart
+// with "zZ" instead of a value
+#[derive(Debug)]
+pub struct SleepingChart {
+    pub ring: bool,
+    pub colors: ChartColorsIced,
+}
+
+impl SleepingChart {
+    pub fn new(ring: bool, colors: &ChartColors) -> Self {
+        SleepingChart {
+            ring,
+            colors: (*colors).into(),
+        }
+    }
+}
+
+impl canvas::Program<Message, theme::Theme> for SleepingChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &theme::Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let center = Point::new(bounds.width / 2.0, bounds.height / 2.0);
+
+        let (outline, width, scale, font_size) = if self.ring {
+            let scale = bounds.width.min(bounds.height) / RING_VIEW_BOX;
+            (
+                Path::circle(center, RING_RADIUS * scale),
+                RING_WIDTH * scale,
+                scale,
+                RING_FONT_SIZE * scale,
+            )
+        } else {
+            let scale = bounds.height / CHART_HEIGHT;
+            let radius = super::corner_radius(bounds.size());
+            (
+                super::rounded_frame(bounds.size(), radius),
+                1.0,
+                scale,
+                FONT_SIZE * scale,
+            )
+        };
+
+        frame.fill(&outline, self.colors.background);
+        let dash = [DASH * scale; 2];
+        frame.stroke(
+            &outline,
+            Stroke {
+                style: Style::Solid(self.colors.frame),
+                width,
+                line_dash: LineDash {
+                    segments: &dash,
+                    offset: 0,
+                },
+                ..Default::default()
+            },
+        );
+
+        frame.fill_text(Text {
+            content: "zZ".to_string(),
+            position: center,
+            color: self.colors.text,
+            size: Pixels(font_size),
+            align_x: Horizontal::Center.into(),
+            align_y: Vertical::Center,
+            ..Default::default()
+        });
+
+        vec![frame.into_geometry()]
+    }
+}
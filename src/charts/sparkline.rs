@@ -0,0 +1,99 @@
+use cosmic::iced::mouse::Cursor;
+use cosmic::iced::{Point, Rectangle, Renderer};
+use cosmic::iced_widget::canvas::Geometry;
+use cosmic::theme;
+use cosmic::widget::canvas::{self, Stroke, Style};
+use std::collections::VecDeque;
+
+use crate::app::Message;
+use crate::config::ChartColors;
+
+use super::ChartColorsIced;
+
+// The canvas counterpart of svg_graph::sparkline, only the lines without a background or frame
+#[derive(Debug)]
+pub struct SparklineChart {
+    pub steps: usize,
+    pub samples1: Vec<f64>,
+    pub samples2: Option<Vec<f64>>,
+    pub max_y: f64,
+    pub colors: ChartColorsIced,
+}
+
+impl SparklineChart {
+    pub fn new(
+        samples: &VecDeque<f64>,
+        samples2: Option<&VecDeque<f64>>,
+        steps: usize,
+        max_y: f64,
+        colors: &ChartColors,
+    ) -> Self {
+        let last = |samples: &VecDeque<f64>| -> Vec<f64> {
+            let start = samples.len().saturating_sub(steps);
+            samples.iter().skip(start).copied().collect()
+        };
+        SparklineChart {
+            steps,
+            samples1: last(samples),
+            samples2: samples2.map(last),
+            max_y,
+            colors: (*colors).into(),
+        }
+    }
+
+    fn points(&self, samples: &[f64], bounds: Rectangle) -> Vec<Point> {
+        let width = bounds.width - 2.0;
+        let height = bounds.height - 2.0;
+        let step = width / self.steps.saturating_sub(1).max(1) as f32;
+        let scaling = if self.max_y > 0.0 {
+            height / self.max_y as f32
+        } else {
+            0.0
+        };
+
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                let value = sample.clamp(0.0, self.max_y) as f32;
+                Point::new(1.0 + step * i as f32, 1.0 + height - value * scaling)
+            })
+            .collect()
+    }
+}
+
+impl canvas::Program<Message, theme::Theme> for SparklineChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &theme::Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+
+        let lines = std::iter::once((&self.samples1, self.colors.graph1)).chain(
+            self.samples2
+                .iter()
+                .map(|samples| (samples, self.colors.graph2)),
+        );
+        for (samples, color) in lines {
+            let points = self.points(samples, bounds);
+            frame.stroke(
+                &super::polyline(&points),
+                Stroke {
+                    style: Style::Solid(super::opaque(color)),
+                    width: 2.0,
+                    line_cap: canvas::LineCap::Round,
+                    line_join: canvas::LineJoin::Round,
+                    ..Default::default()
+                },
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
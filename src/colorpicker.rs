@@ -14,14 +14,14 @@ use cosmic::{
     },
     theme,
     widget::{
-        self,
+        self, settings,
         slider::{self, HandleShape},
     },
 };
 use theme::iced::Slider;
 
 use crate::app::Message;
-use crate::config::{ChartColors, ChartKind, ColorVariant, DeviceKind};
+use crate::config::{ChartColors, ChartKind, ColorVariant, DeviceKind, Gradient, GradientStop};
 use crate::fl;
 use log::info;
 
@@ -114,6 +114,22 @@ pub static COLOR_STOPS_BLUE_HIGH: LazyLock<Mutex<[ColorStop; 2]>> = LazyLock::ne
     ])
 });
 
+/// The color of a gradient fill the sliders edit, `Start` is the graph color itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopKind {
+    Start,
+    Middle,
+    End,
+}
+
+pub static STOP_CHOICES: LazyLock<[(&'static str, StopKind); 3]> = LazyLock::new(|| {
+    [
+        (fl!("colorpicker-stop-start").leak(), StopKind::Start),
+        (fl!("colorpicker-stop-middle").leak(), StopKind::Middle),
+        (fl!("colorpicker-stop-end").leak(), StopKind::End),
+    ]
+});
+
 const ERROR: &str = "<svg width=\"800px\" height=\"800px\" viewBox=\"0 0 25 25\" fill=\"none\" xmlns=\"http://www.w3.org/2000/svg\">
 <path d=\"M12.5 16V14.5M12.5 9V13M20.5 12.5C20.5 16.9183 16.9183 20.5 12.5 20.5C8.08172 20.5 4.5 16.9183 4.5 12.5C4.5 8.08172 8.08172 4.5 12.5 4.5C16.9183 4.5 20.5 8.08172 20.5 12.5Z\" stroke=\"red\" stroke-width=\"1.2\"/>
 </svg>";
@@ -133,6 +149,8 @@ pub struct ColorPicker {
     device: DeviceKind,
     // Current field being adjusted background/text/etc.
     color_variant: ColorVariant,
+    // Stop of the gradient being adjusted, if the color has one
+    stop: StopKind,
     ///Current slider values
     slider_red_val: u8,
     slider_green_val: u8,
@@ -146,6 +164,7 @@ impl Default for ColorPicker {
             demo_chart: None,
             device: DeviceKind::Cpu,
             color_variant: ColorVariant::Background,
+            stop: StopKind::Start,
             slider_red_val: 0,
             slider_green_val: 0,
             slider_blue_val: 0,
@@ -167,6 +186,7 @@ impl ColorPicker {
         info!("colorpicker::activate({device:?})");
         self.device = device;
         self.color_variant = ColorVariant::Background;
+        self.stop = StopKind::Start;
         self.demo_chart = Some(demo_chart);
     }
 
@@ -284,7 +304,19 @@ impl ColorPicker {
 
         if let Some(dmo) = self.demo_chart.as_mut() {
             let mut col = *dmo.colors();
-            col.set_color(color, self.color_variant);
+            match (self.stop, col.gradient(self.color_variant)) {
+                (StopKind::Middle, Some(mut gradient)) => {
+                    if let Some(middle) = gradient.middle.as_mut() {
+                        middle.color = color;
+                    }
+                    col.set_gradient(Some(gradient), self.color_variant);
+                }
+                (StopKind::End, Some(mut gradient)) => {
+                    gradient.end.color = color;
+                    col.set_gradient(Some(gradient), self.color_variant);
+                }
+                _ => col.set_color(color, self.color_variant),
+            }
             dmo.set_colors(&col);
 
             // Set the shading for sliders, this is required to be static lifetime
@@ -311,6 +343,7 @@ impl ColorPicker {
         if let Some(dmo) = self.demo_chart.as_mut() {
             let colors = ChartColors::new(self.device, dmo.kind());
             dmo.set_colors(&colors);
+            self.stop = StopKind::Start;
             self.update_color(colors.get_color(self.color_variant));
         } else {
             log::error!("Colorpicker::default_colors() No demo graph object!");
@@ -324,6 +357,7 @@ impl ColorPicker {
     pub fn set_color_variant(&mut self, variant: ColorVariant) {
         if let Some(dmo) = self.demo_chart.as_mut() {
             self.color_variant = variant;
+            self.stop = StopKind::Start;
             let color = dmo.colors().get_color(variant);
             self.update_color(color);
         } else {
@@ -331,6 +365,164 @@ impl ColorPicker {
         }
     }
 
+    /// Only the charts that fill below or along their graph draw gradients, rings,
+    /// stacked bars and sparklines keep solid colors
+    fn gradient_supported(&self) -> bool {
+        self.demo_chart.as_ref().is_some_and(|dmo| {
+            matches!(
+                dmo.kind(),
+                ChartKind::Line | ChartKind::Heat | ChartKind::Gauge
+            )
+        })
+    }
+
+    fn graph_color_selected(&self) -> bool {
+        matches!(
+            self.color_variant,
+            ColorVariant::Graph1 | ColorVariant::Graph2 | ColorVariant::Graph3
+        )
+    }
+
+    // The color of the selected stop, the graph color for a solid fill
+    fn stop_color(&self, colors: &ChartColors) -> Srgba<u8> {
+        match (self.stop, colors.gradient(self.color_variant)) {
+            (
+                StopKind::Middle,
+                Some(Gradient {
+                    middle: Some(middle),
+                    ..
+                }),
+            ) => middle.color,
+            (StopKind::End, Some(gradient)) => gradient.end.color,
+            _ => colors.get_color(self.color_variant),
+        }
+    }
+
+    /// Switches the selected graph color between a solid and a gradient fill
+    pub fn set_gradient(&mut self, enabled: bool) {
+        if let Some(dmo) = self.demo_chart.as_mut() {
+            let mut colors = *dmo.colors();
+            let gradient = enabled.then(|| {
+                // Fades out to the top until another end color is picked
+                let color = colors.get_color(self.color_variant);
+                Gradient::new(Srgba::new(color.red, color.green, color.blue, 0))
+            });
+            colors.set_gradient(gradient, self.color_variant);
+            dmo.set_colors(&colors);
+            self.select_stop(StopKind::Start);
+        } else {
+            log::error!("Colorpicker::set_gradient() No demo graph object!");
+        }
+    }
+
+    /// Adds a stop halfway between the first and the last one, or removes it
+    pub fn set_middle_stop(&mut self, enabled: bool) {
+        if let Some(dmo) = self.demo_chart.as_mut()
+            && let Some(mut gradient) = dmo.colors().gradient(self.color_variant)
+        {
+            let mut colors = *dmo.colors();
+            gradient.middle = enabled.then(|| GradientStop {
+                color: colors.get_color(self.color_variant),
+                offset: ((u16::from(gradient.start) + u16::from(gradient.end.offset)) / 2) as u8,
+            });
+            colors.set_gradient(Some(gradient), self.color_variant);
+            dmo.set_colors(&colors);
+            self.select_stop(if enabled {
+                StopKind::Middle
+            } else {
+                StopKind::Start
+            });
+        } else {
+            log::error!("Colorpicker::set_middle_stop() No gradient to change!");
+        }
+    }
+
+    pub fn select_stop(&mut self, stop: StopKind) {
+        if let Some(dmo) = self.demo_chart.as_ref() {
+            self.stop = stop;
+            let color = self.stop_color(dmo.colors());
+            self.update_color(color);
+        } else {
+            log::error!("Colorpicker::select_stop() No demo graph object!");
+        }
+    }
+
+    /// Moves the selected stop, `offset` in percent from the bottom of the chart
+    pub fn set_stop_offset(&mut self, offset: u8) {
+        let offset = offset.min(100);
+        if let Some(dmo) = self.demo_chart.as_mut()
+            && let Some(mut gradient) = dmo.colors().gradient(self.color_variant)
+        {
+            match self.stop {
+                StopKind::Start => gradient.start = offset,
+                StopKind::Middle => {
+                    if let Some(middle) = gradient.middle.as_mut() {
+                        middle.offset = offset;
+                    }
+                }
+                StopKind::End => gradient.end.offset = offset,
+            }
+            let mut colors = *dmo.colors();
+            colors.set_gradient(Some(gradient), self.color_variant);
+            dmo.set_colors(&colors);
+        }
+    }
+
+    // Gradient toggles, the stop the sliders edit and where it sits on the chart
+    fn view_gradient(&self, colors: &ChartColors) -> Element<'_, Message> {
+        let gradient = colors.gradient(self.color_variant);
+
+        let mut controls = column!(settings::item(
+            fl!("colorpicker-gradient"),
+            widget::toggler(gradient.is_some()).on_toggle(Message::ColorPickerGradient),
+        ))
+        .spacing(5);
+
+        if let Some(gradient) = gradient {
+            let stops = STOP_CHOICES
+                .iter()
+                .filter(|(_, stop)| *stop != StopKind::Middle || gradient.middle.is_some())
+                .map(|(label, stop)| {
+                    widget::radio(
+                        *label,
+                        *stop,
+                        Some(self.stop),
+                        Message::ColorPickerSelectStop,
+                    )
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+
+            let offset = match self.stop {
+                StopKind::Start => gradient.start,
+                StopKind::Middle => gradient.middle.map_or(0, |middle| middle.offset),
+                StopKind::End => gradient.end.offset,
+            };
+
+            controls = controls
+                .push(settings::item(
+                    fl!("colorpicker-middle-stop"),
+                    widget::toggler(gradient.middle.is_some())
+                        .on_toggle(Message::ColorPickerMiddleStop),
+                ))
+                .push(widget::row::with_children(stops).spacing(20))
+                .push(
+                    row!(
+                        widget::text::body(fl!("colorpicker-stop-position")),
+                        widget::space::horizontal(),
+                        widget::slider(0..=100, offset, Message::ColorPickerStopOffset)
+                            .width(Length::Fixed(220.0))
+                            .step(1),
+                        widget::text::body(format!("{offset}%")).width(50),
+                    )
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                );
+        }
+
+        controls.into()
+    }
+
     pub fn colors(&self) -> &ChartColors {
         if let Some(dmo) = self.demo_chart.as_ref() {
             dmo.colors()
@@ -373,7 +565,7 @@ impl ColorPicker {
                 cosmic::widget::row::with_children(children2)
             );
 
-            let c = widget::list_column()
+            let mut c = widget::list_column()
                 .add(
                     widget::text::title2(title)
                         .width(Length::Fill)
@@ -467,28 +659,39 @@ impl ColorPicker {
                         .align_y(Alignment::Center)
                     ),
                 ))
-                .add(fields)
-                .add(
+                .add(fields);
+
+            if self.graph_color_selected() {
+                if self.gradient_supported() {
+                    c = c.add(self.view_gradient(dmo.colors()));
+                } else {
+                    c = c.add(widget::text::caption(fl!(
+                        "colorpicker-gradient-unsupported"
+                    )));
+                }
+            }
+
+            c = c.add(
+                row!(
+                    widget::button::standard(fl!("colorpicker-defaults"))
+                        .on_press(Message::ColorPickerDefaults),
+                    widget::button::standard(fl!("colorpicker-accent"))
+                        .on_press(Message::ColorPickerAccent),
                     row!(
-                        widget::button::standard(fl!("colorpicker-defaults"))
-                            .on_press(Message::ColorPickerDefaults),
-                        widget::button::standard(fl!("colorpicker-accent"))
-                            .on_press(Message::ColorPickerAccent),
-                        row!(
-                            widget::space::horizontal(),
-                            widget::button::destructive(fl!("colorpicker-cancel"))
-                                .on_press(Message::ColorPickerClose(false, dmo.id())),
-                            widget::button::suggested(fl!("colorpicker-save"))
-                                .on_press(Message::ColorPickerClose(true, dmo.id()))
-                        )
-                        .width(Length::Fill)
-                        .spacing(5)
-                        .align_y(Alignment::End)
+                        widget::space::horizontal(),
+                        widget::button::destructive(fl!("colorpicker-cancel"))
+                            .on_press(Message::ColorPickerClose(false, dmo.id())),
+                        widget::button::suggested(fl!("colorpicker-save"))
+                            .on_press(Message::ColorPickerClose(true, dmo.id()))
                     )
-                    .padding(5)
+                    .width(Length::Fill)
                     .spacing(5)
-                    .width(Length::Fill),
-                );
+                    .align_y(Alignment::End)
+                )
+                .padding(5)
+                .spacing(5)
+                .width(Length::Fill),
+            );
 
             c.into()
         } else {
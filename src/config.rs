@@ -24,6 +24,8 @@ pub enum ChartKind {
     Line,
     Heat,
     StackedBars,
+    Gauge,
+    Sparkline,
 }
 
 impl From<usize> for ChartKind {
@@ -33,6 +35,8 @@ impl From<usize> for ChartKind {
             1 => ChartKind::Line,
             2 => ChartKind::Heat,
             3 => ChartKind::StackedBars,
+            4 => ChartKind::Gauge,
+            5 => ChartKind::Sparkline,
             _ => {
                 log::error!("GrapKind::From({}) Invalid index for ChartKind", index);
                 ChartKind::Line
@@ -48,6 +52,84 @@ impl From<ChartKind> for usize {
             ChartKind::Line => 1,
             ChartKind::Heat => 2,
             ChartKind::StackedBars => 3,
+            ChartKind::Gauge => 4,
+            ChartKind::Sparkline => 5,
+        }
+    }
+}
+
+/// Length to thickness of the charts in the panel, width to height on a horizontal panel
+/// and height to width on a vertical one. Rings always stay round.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChartAspect {
+    #[default]
+    Square,
+    Wide2,
+    Wide3,
+}
+
+impl ChartAspect {
+    pub fn ratio(self) -> u16 {
+        match self {
+            ChartAspect::Square => 1,
+            ChartAspect::Wide2 => 2,
+            ChartAspect::Wide3 => 3,
+        }
+    }
+}
+
+impl From<usize> for ChartAspect {
+    fn from(index: usize) -> Self {
+        match index {
+            0 => ChartAspect::Square,
+            1 => ChartAspect::Wide2,
+            2 => ChartAspect::Wide3,
+            _ => {
+                log::error!("ChartAspect::From({}) Invalid index for ChartAspect", index);
+                ChartAspect::Square
+            }
+        }
+    }
+}
+
+impl From<ChartAspect> for usize {
+    fn from(aspect: ChartAspect) -> Self {
+        match aspect {
+            ChartAspect::Square => 0,
+            ChartAspect::Wide2 => 1,
+            ChartAspect::Wide3 => 2,
+        }
+    }
+}
+
+/// Prefixes of sizes and rates, see `crate::units`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitSystem {
+    /// kB, MB, powers of 1000
+    #[default]
+    Si,
+    /// KiB, MiB, powers of 1024
+    Iec,
+}
+
+impl From<usize> for UnitSystem {
+    fn from(index: usize) -> Self {
+        match index {
+            0 => UnitSystem::Si,
+            1 => UnitSystem::Iec,
+            _ => {
+                log::error!("UnitSystem::From({}) Invalid index for UnitSystem", index);
+                UnitSystem::Si
+            }
+        }
+    }
+}
+
+impl From<UnitSystem> for usize {
+    fn from(system: UnitSystem) -> Self {
+        match system {
+            UnitSystem::Si => 0,
+            UnitSystem::Iec => 1,
         }
     }
 }
@@ -62,6 +144,8 @@ pub enum DeviceKind {
     Gpu,
     Vram,
     GpuTemp,
+    Power,
+    Battery,
 }
 
 impl std::fmt::Display for DeviceKind {
@@ -75,10 +159,66 @@ impl std::fmt::Display for DeviceKind {
             DeviceKind::Gpu => write!(f, "{}", fl!("sensor-gpu")),
             DeviceKind::Vram => write!(f, "{}", fl!("sensor-vram")),
             DeviceKind::GpuTemp => write!(f, "{}", fl!("sensor-gpu-temp")),
+            DeviceKind::Power => write!(f, "{}", fl!("sensor-power")),
+            DeviceKind::Battery => write!(f, "{}", fl!("sensor-battery")),
         }
     }
 }
 
+/// A color of a gradient fill, `offset` in percent from the bottom of the chart
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GradientStop {
+    pub color: Srgba<u8>,
+    pub offset: u8,
+}
+
+/// A graph fill that blends from the graph color at the bottom of the chart to `end`
+/// at the top, optionally through `middle`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Gradient {
+    /// Offset of the graph color, the first stop
+    pub start: u8,
+    pub middle: Option<GradientStop>,
+    pub end: GradientStop,
+}
+
+impl Gradient {
+    /// Two stops, from the graph color at the bottom to `end` at the top
+    pub fn new(end: Srgba<u8>) -> Self {
+        Gradient {
+            start: 0,
+            middle: None,
+            end: GradientStop {
+                color: end,
+                offset: 100,
+            },
+        }
+    }
+
+    /// The stops from the bottom up, the graph color `start_color` first, with offsets
+    /// from 0.0 to 1.0. A stop set below the one before it moves up to it.
+    pub fn stops(&self, start_color: Srgba<u8>) -> Vec<(f32, Srgba<u8>)> {
+        let mut offset = 0;
+        std::iter::once((self.start, start_color))
+            .chain(self.middle.map(|stop| (stop.offset, stop.color)))
+            .chain(std::iter::once((self.end.offset, self.end.color)))
+            .map(|(stop_offset, color)| {
+                offset = stop_offset.clamp(offset, 100);
+                (f32::from(offset) / 100.0, color)
+            })
+            .collect()
+    }
+}
+
+/// Format of saved chart colors, kept in `ChartColors::version`. Version 2 added the
+/// gradient fills.
+///
+/// cosmic-config only uses the `#[version]` of `MinimonConfig`, which names the directory
+/// of the whole config, so bumping it would drop every setting. The colors are saved
+/// inside the fields of `MinimonConfig`, and record their own version instead so a
+/// deserializer can upgrade old ones, see `heat_colors`.
+const CHART_COLORS_VERSION: u64 = 2;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
 #[version = 1]
 pub struct ChartColors {
@@ -88,6 +228,16 @@ pub struct ChartColors {
     pub graph1: Srgba<u8>,
     pub graph2: Srgba<u8>,
     pub graph3: Srgba<u8>,
+    #[serde(default)]
+    pub gradient1: Option<Gradient>,
+    #[serde(default)]
+    pub gradient2: Option<Gradient>,
+    #[serde(default)]
+    pub gradient3: Option<Gradient>,
+    /// `CHART_COLORS_VERSION` the colors were saved with, 0 for colors saved before it
+    /// was recorded
+    #[serde(default)]
+    pub version: u64,
 }
 
 macro_rules! rgba {
@@ -105,12 +255,22 @@ impl Default for ChartColors {
             graph1: rgba!(255, 6, 0, 255),
             graph2: rgba!(85, 85, 85, 255),
             graph3: rgba!(255, 165, 0, 255),
+            gradient1: None,
+            gradient2: None,
+            gradient3: None,
+            version: CHART_COLORS_VERSION,
         }
     }
 }
 
 impl ChartColors {
     pub fn new(device: DeviceKind, chart: ChartKind) -> Self {
+        // Gauges and sparklines draw in the solid graph colors of the ring
+        let chart = match chart {
+            ChartKind::Gauge | ChartKind::Sparkline => ChartKind::Ring,
+            ChartKind::Heat => return ChartColors::heat(),
+            chart => chart,
+        };
         match device {
             DeviceKind::Cpu => match chart {
                 ChartKind::Ring => ChartColors {
@@ -126,7 +286,7 @@ impl ChartColors {
                     graph2: rgba!(255, 0, 0, 255),
                     ..Default::default()
                 },
-                ChartKind::Heat => ChartColors::default(),
+                ChartKind::Heat => ChartColors::heat(),
             },
 
             DeviceKind::CpuTemp => match chart {
@@ -155,16 +315,30 @@ impl ChartColors {
                 _ => ChartColors::default(),
             },
 
-            DeviceKind::Network(_) => ChartColors {
-                graph1: rgba!(47, 141, 255, 85),
-                graph2: rgba!(0, 255, 0, 85),
-                ..Default::default()
+            DeviceKind::Network(_) => match chart {
+                ChartKind::Ring => ChartColors {
+                    graph1: rgba!(47, 141, 255, 255),
+                    graph2: rgba!(0, 255, 0, 255),
+                    ..Default::default()
+                },
+                _ => ChartColors {
+                    graph1: rgba!(47, 141, 255, 85),
+                    graph2: rgba!(0, 255, 0, 85),
+                    ..Default::default()
+                },
             },
 
-            DeviceKind::Disks(_) => ChartColors {
-                graph1: rgba!(255, 102, 0, 85),
-                graph2: rgba!(255, 255, 0, 85),
-                ..Default::default()
+            DeviceKind::Disks(_) => match chart {
+                ChartKind::Ring => ChartColors {
+                    graph1: rgba!(255, 102, 0, 255),
+                    graph2: rgba!(255, 255, 0, 255),
+                    ..Default::default()
+                },
+                _ => ChartColors {
+                    graph1: rgba!(255, 102, 0, 85),
+                    graph2: rgba!(255, 255, 0, 85),
+                    ..Default::default()
+                },
             },
             DeviceKind::Gpu => match chart {
                 ChartKind::Ring => ChartColors {
@@ -199,6 +373,44 @@ impl ChartColors {
                 },
                 _ => ChartColors::default(),
             },
+            DeviceKind::Power => match chart {
+                ChartKind::Ring => ChartColors {
+                    graph1: rgba!(255, 200, 0, 255),
+                    ..Default::default()
+                },
+                ChartKind::Line => ChartColors {
+                    graph1: rgba!(255, 200, 0, 85),
+                    ..Default::default()
+                },
+                _ => ChartColors::default(),
+            },
+            DeviceKind::Battery => match chart {
+                ChartKind::Ring => ChartColors {
+                    graph1: rgba!(80, 200, 80, 255),
+                    ..Default::default()
+                },
+                ChartKind::Line => ChartColors {
+                    graph1: rgba!(80, 200, 80, 85),
+                    ..Default::default()
+                },
+                _ => ChartColors::default(),
+            },
+        }
+    }
+
+    /// Heat charts fill orange at the bottom to red from 90% up
+    fn heat() -> Self {
+        ChartColors {
+            graph1: rgba!(255, 165, 0, 255),
+            gradient1: Some(Gradient {
+                start: 0,
+                middle: None,
+                end: GradientStop {
+                    color: rgba!(255, 0, 0, 255),
+                    offset: 90,
+                },
+            }),
+            ..Default::default()
         }
     }
 
@@ -223,15 +435,92 @@ impl ChartColors {
             ColorVariant::Graph3 => self.graph3,
         }
     }
+
+    /// The gradient fill of a graph color, `None` for a solid fill or another variant
+    pub fn gradient(self, variant: ColorVariant) -> Option<Gradient> {
+        match variant {
+            ColorVariant::Graph1 => self.gradient1,
+            ColorVariant::Graph2 => self.gradient2,
+            ColorVariant::Graph3 => self.gradient3,
+            _ => None,
+        }
+    }
+
+    pub fn set_gradient(&mut self, gradient: Option<Gradient>, variant: ColorVariant) {
+        match variant {
+            ColorVariant::Graph1 => self.gradient1 = gradient,
+            ColorVariant::Graph2 => self.gradient2 = gradient,
+            ColorVariant::Graph3 => self.gradient3 = gradient,
+            _ => log::error!("ChartColors::set_gradient({variant:?}) not a graph color"),
+        }
+    }
+}
+
+// Heat colors saved before version 2 had a fixed orange to red fill, keep it
+fn heat_colors<'de, D>(deserializer: D) -> Result<ChartColors, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let colors = ChartColors::deserialize(deserializer)?;
+    if colors.version < 2 {
+        Ok(ChartColors {
+            background: colors.background,
+            frame: colors.frame,
+            text: colors.text,
+            ..ChartColors::heat()
+        })
+    } else {
+        Ok(colors)
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
 #[version = 1]
+#[serde(from = "StoredColors")]
 pub struct Colors {
     ring: ChartColors,
     line: ChartColors,
     heat: ChartColors,
     stackedbars: ChartColors,
+    gauge: ChartColors,
+    sparkline: ChartColors,
+}
+
+// Colors as saved, configs from before the gauge and sparkline have no colors for them
+#[derive(Deserialize)]
+struct StoredColors {
+    ring: ChartColors,
+    line: ChartColors,
+    #[serde(deserialize_with = "heat_colors")]
+    heat: ChartColors,
+    stackedbars: ChartColors,
+    #[serde(default, deserialize_with = "saved_colors")]
+    gauge: Option<ChartColors>,
+    #[serde(default, deserialize_with = "saved_colors")]
+    sparkline: Option<ChartColors>,
+}
+
+// Saved as plain colors, not as an option, only missing ones are None
+fn saved_colors<'de, D>(deserializer: D) -> Result<Option<ChartColors>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    ChartColors::deserialize(deserializer).map(Some)
+}
+
+impl From<StoredColors> for Colors {
+    // The defaults of gauges and sparklines are the ring colors of the device, see
+    // `ChartColors::new`, so take them from the saved ring
+    fn from(stored: StoredColors) -> Self {
+        Colors {
+            ring: stored.ring,
+            line: stored.line,
+            heat: stored.heat,
+            stackedbars: stored.stackedbars,
+            gauge: stored.gauge.unwrap_or(stored.ring),
+            sparkline: stored.sparkline.unwrap_or(stored.ring),
+        }
+    }
 }
 
 impl Colors {
@@ -241,6 +530,8 @@ impl Colors {
             line: ChartColors::new(device, ChartKind::Line),
             heat: ChartColors::new(device, ChartKind::Heat),
             stackedbars: ChartColors::new(device, ChartKind::StackedBars),
+            gauge: ChartColors::new(device, ChartKind::Gauge),
+            sparkline: ChartColors::new(device, ChartKind::Sparkline),
         }
     }
 
@@ -250,6 +541,8 @@ impl Colors {
             ChartKind::Line => &self.line,
             ChartKind::Heat => &self.heat,
             ChartKind::StackedBars => &self.stackedbars,
+            ChartKind::Gauge => &self.gauge,
+            ChartKind::Sparkline => &self.sparkline,
         }
     }
 
@@ -259,6 +552,8 @@ impl Colors {
             ChartKind::Line => &mut self.line,
             ChartKind::Heat => &mut self.heat,
             ChartKind::StackedBars => &mut self.stackedbars,
+            ChartKind::Gauge => &mut self.gauge,
+            ChartKind::Sparkline => &mut self.sparkline,
         }
     }
 }
@@ -273,6 +568,10 @@ macro_rules! make_config {
             value_visible: bool,
             label_visible: bool,
             icon_visible: bool,
+            // Gridlines and a scale label on line charts
+            grid_visible: bool,
+            // Text of the panel value, see `crate::template`. Empty for the built-in one
+            pub template: String,
             pub chart: ChartKind,
             colors: Colors,
             $($extra)*
@@ -282,6 +581,10 @@ macro_rules! make_config {
               pub fn visible(&self) -> bool {
                 self.chart_visible() || self.value_visible()
             }
+            // Charts are left out in text-only mode
+            pub fn shown(&self, text_only: bool) -> bool {
+                self.value_visible() || (!text_only && self.chart_visible())
+            }
             pub fn chart_visible(&self) -> bool {
                 self.chart_visible
             }
@@ -294,6 +597,9 @@ macro_rules! make_config {
             pub fn icon_visible(&self) -> bool {
                 self.icon_visible
             }
+            pub fn grid_visible(&self) -> bool {
+                self.grid_visible
+            }
             pub fn show_chart(&mut self, visible: bool) {
                 self.chart_visible = visible;
             }
@@ -306,6 +612,9 @@ macro_rules! make_config {
             pub fn show_icon(&mut self, visible: bool) {
                 self.icon_visible = visible;
             }
+            pub fn show_grid(&mut self, visible: bool) {
+                self.grid_visible = visible;
+            }
             pub fn colors(&self) -> &ChartColors {
                 self.colors.get(self.chart)
             }
@@ -329,6 +638,8 @@ impl Default for CpuConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Ring,
             colors: Colors::new(DeviceKind::Cpu),
             no_decimals: false,
@@ -350,6 +661,8 @@ impl Default for CpuTempConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Heat,
             colors: Colors::new(DeviceKind::CpuTemp),
             unit: TempUnit::Celsius,
@@ -371,6 +684,8 @@ impl Default for MemoryConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Ring,
             colors: Colors::new(DeviceKind::Memory),
             percentage: false,
@@ -393,6 +708,9 @@ make_config!(NetworkConfig {
     pub unit: Option<usize>,
     pub variant: NetworkVariant,
     pub show_bytes: bool,
+    pub log_scale: bool,
+    // With the adaptive scale, let it fall slowly after a peak
+    pub sticky_peak: bool,
 });
 
 impl Default for NetworkConfig {
@@ -402,6 +720,8 @@ impl Default for NetworkConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Line,
             colors: Colors::new(DeviceKind::Network(NetworkVariant::Combined)),
             adaptive: true,
@@ -409,6 +729,8 @@ impl Default for NetworkConfig {
             unit: Some(0),
             variant: NetworkVariant::Combined,
             show_bytes: false,
+            log_scale: false,
+            sticky_peak: false,
         }
     }
 }
@@ -422,6 +744,13 @@ pub enum DisksVariant {
 
 make_config!(DisksConfig {
     pub variant: DisksVariant,
+    pub adaptive: bool,
+    pub bandwidth: u64,
+    pub unit: Option<usize>,
+    pub log_scale: bool,
+    // With the adaptive scale, let it fall slowly after a peak
+    pub sticky_peak: bool,
+    pub show_bits: bool,
 });
 
 impl Default for DisksConfig {
@@ -431,9 +760,17 @@ impl Default for DisksConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Line,
             colors: Colors::new(DeviceKind::Disks(DisksVariant::Combined)),
             variant: DisksVariant::Combined,
+            adaptive: true,
+            bandwidth: 500,
+            unit: Some(2),
+            log_scale: false,
+            sticky_peak: false,
+            show_bits: false,
         }
     }
 }
@@ -447,6 +784,8 @@ impl Default for GpuUsageConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Ring,
             colors: Colors::new(DeviceKind::Gpu),
         }
@@ -462,15 +801,40 @@ impl Default for GpuVramConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Ring,
             colors: Colors::new(DeviceKind::Vram),
         }
     }
 }
 
+/// Which GPU temperature sensor to show
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GpuTempSensor {
+    #[default]
+    Edge,
+    Junction,
+    Memory,
+    /// Hottest of the available sensors
+    Max,
+}
+
+impl std::fmt::Display for GpuTempSensor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GpuTempSensor::Edge => write!(f, "{}", fl!("gpu-temp-edge")),
+            GpuTempSensor::Junction => write!(f, "{}", fl!("gpu-temp-junction")),
+            GpuTempSensor::Memory => write!(f, "{}", fl!("gpu-temp-memory")),
+            GpuTempSensor::Max => write!(f, "{}", fl!("gpu-temp-max")),
+        }
+    }
+}
+
 make_config!(GpuTempConfig {
         pub unit: TempUnit,
         pub min_temp: f64,
+        pub sensor: GpuTempSensor,
 });
 
 impl Default for GpuTempConfig {
@@ -480,28 +844,44 @@ impl Default for GpuTempConfig {
             value_visible: false,
             label_visible: false,
             icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
             chart: ChartKind::Ring,
             colors: Colors::new(DeviceKind::GpuTemp),
             unit: TempUnit::Celsius,
             min_temp: 0.0,
+            sensor: GpuTempSensor::Edge,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq)]
+#[serde(default)]
 #[version = 1]
 pub struct GpuConfig {
     pub usage: GpuUsageConfig,
     pub vram: GpuVramConfig,
     pub temp: GpuTempConfig,
     pub pause_on_battery: bool,
+    /// Also draws the GTT used on top of the VRAM on APUs
     pub stack_values: bool,
+    pub show_extended: bool,
+    /// Shown instead of the detected name when not empty
+    pub name: String,
+    /// Not shown in the panel and not polled
+    pub hidden: bool,
+    /// Position among the GPUs, ties are ordered by id
+    pub order: u32,
 }
 
 impl GpuConfig {
     pub fn is_visible(&self) -> bool {
         self.usage.visible() || self.vram.visible() || self.temp.visible()
     }
+
+    pub fn is_shown(&self, text_only: bool) -> bool {
+        self.usage.shown(text_only) || self.vram.shown(text_only) || self.temp.shown(text_only)
+    }
 }
 
 impl Default for GpuConfig {
@@ -512,6 +892,172 @@ impl Default for GpuConfig {
             temp: GpuTempConfig::default(),
             pause_on_battery: true,
             stack_values: true,
+            show_extended: false,
+            name: String::new(),
+            hidden: false,
+            order: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PowerVariant {
+    Total,
+    Cpu,
+    Gpu,
+    Battery,
+}
+
+make_config!(PowerConfig {
+    pub variant: PowerVariant,
+    pub max_watts: u32,
+});
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            chart_visible: false,
+            value_visible: false,
+            label_visible: false,
+            icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
+            chart: ChartKind::Line,
+            colors: Colors::new(DeviceKind::Power),
+            variant: PowerVariant::Total,
+            max_watts: 0, // adaptive
+        }
+    }
+}
+
+make_config!(BatteryConfig {
+    pub show_time: bool,
+});
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            chart_visible: false,
+            value_visible: false,
+            label_visible: false,
+            icon_visible: false,
+            grid_visible: false,
+            template: String::new(),
+            chart: ChartKind::Ring,
+            colors: Colors::new(DeviceKind::Battery),
+            show_time: false,
+        }
+    }
+}
+
+/// Power profile from power-profiles-daemon, or running on battery
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PowerCondition {
+    PowerSaver,
+    #[default]
+    Balanced,
+    Performance,
+    OnBattery,
+}
+
+impl PowerCondition {
+    /// Parse the `ActiveProfile` property of the PowerProfiles service
+    pub fn from_profile_name(name: &str) -> Option<Self> {
+        match name {
+            "power-saver" => Some(PowerCondition::PowerSaver),
+            "balanced" => Some(PowerCondition::Balanced),
+            "performance" => Some(PowerCondition::Performance),
+            _ => None,
+        }
+    }
+}
+
+impl From<usize> for PowerCondition {
+    fn from(index: usize) -> Self {
+        match index {
+            0 => PowerCondition::PowerSaver,
+            2 => PowerCondition::Performance,
+            3 => PowerCondition::OnBattery,
+            _ => PowerCondition::Balanced,
+        }
+    }
+}
+
+impl From<PowerCondition> for usize {
+    fn from(condition: PowerCondition) -> Self {
+        match condition {
+            PowerCondition::PowerSaver => 0,
+            PowerCondition::Balanced => 1,
+            PowerCondition::Performance => 2,
+            PowerCondition::OnBattery => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProfileOverride {
+    /// Slowest refresh rate in ms while active, 0 keeps the configured rate
+    pub refresh_rate: u32,
+    /// Stop polling costly sensors such as NVML
+    pub suspend_expensive: bool,
+    pub hide_gpu_charts: bool,
+}
+
+impl ProfileOverride {
+    /// Combine two active overrides, the most power saving setting wins
+    pub fn merge(self, other: ProfileOverride) -> ProfileOverride {
+        ProfileOverride {
+            refresh_rate: self.refresh_rate.max(other.refresh_rate),
+            suspend_expensive: self.suspend_expensive || other.suspend_expensive,
+            hide_gpu_charts: self.hide_gpu_charts || other.hide_gpu_charts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProfileOverrides {
+    pub power_saver: ProfileOverride,
+    pub balanced: ProfileOverride,
+    pub performance: ProfileOverride,
+    pub on_battery: ProfileOverride,
+}
+
+impl ProfileOverrides {
+    pub fn get(&self, condition: PowerCondition) -> &ProfileOverride {
+        match condition {
+            PowerCondition::PowerSaver => &self.power_saver,
+            PowerCondition::Balanced => &self.balanced,
+            PowerCondition::Performance => &self.performance,
+            PowerCondition::OnBattery => &self.on_battery,
+        }
+    }
+
+    pub fn get_mut(&mut self, condition: PowerCondition) -> &mut ProfileOverride {
+        match condition {
+            PowerCondition::PowerSaver => &mut self.power_saver,
+            PowerCondition::Balanced => &mut self.balanced,
+            PowerCondition::Performance => &mut self.performance,
+            PowerCondition::OnBattery => &mut self.on_battery,
+        }
+    }
+}
+
+// Pausing GPU sampling is opt-in, upgrading shouldn't make the GPU values disappear
+impl Default for ProfileOverrides {
+    fn default() -> Self {
+        Self {
+            power_saver: ProfileOverride {
+                refresh_rate: 3000,
+                suspend_expensive: false,
+                hide_gpu_charts: false,
+            },
+            balanced: ProfileOverride::default(),
+            performance: ProfileOverride::default(),
+            on_battery: ProfileOverride {
+                refresh_rate: 2000,
+                suspend_expensive: false,
+                hide_gpu_charts: false,
+            },
         }
     }
 }
@@ -524,6 +1070,8 @@ pub enum ContentType {
     NetworkUsage,
     DiskUsage,
     GpuInfo,
+    PowerDraw,
+    Battery,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
@@ -542,17 +1090,34 @@ impl Default for ContentOrder {
                 ContentType::NetworkUsage,
                 ContentType::DiskUsage,
                 ContentType::GpuInfo,
+                ContentType::PowerDraw,
+                ContentType::Battery,
             ],
         }
     }
 }
 
+impl ContentOrder {
+    /// Orders saved by older versions lack newer sensors, append them at the end
+    pub fn add_missing(&mut self) {
+        for content in ContentOrder::default().order {
+            if !self.order.contains(&content) {
+                self.order.push(content);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq)]
 #[version = 1]
 pub struct MinimonConfig {
     pub refresh_rate: u32,
     pub value_size_default: u16,
     pub monospace_values: bool,
+    pub canvas_charts: bool,
+    pub text_only: bool,
+    pub chart_aspect: ChartAspect,
+    pub unit_system: UnitSystem,
 
     pub cpu: CpuConfig,
     pub cputemp: CpuTempConfig,
@@ -566,6 +1131,12 @@ pub struct MinimonConfig {
 
     pub gpus: HashMap<String, GpuConfig>,
 
+    pub power: PowerConfig,
+
+    pub battery: BatteryConfig,
+
+    pub profile_overrides: ProfileOverrides,
+
     pub sysmon: Option<String>,
 
     pub panel_spacing: u16,
@@ -579,6 +1150,10 @@ impl Default for MinimonConfig {
             refresh_rate: 1000,
             value_size_default: 11,
             monospace_values: false,
+            canvas_charts: false,
+            text_only: false,
+            chart_aspect: ChartAspect::Square,
+            unit_system: UnitSystem::Si,
             cpu: CpuConfig::default(),
             cputemp: CpuTempConfig::default(),
             memory: MemoryConfig::default(),
@@ -599,6 +1174,9 @@ impl Default for MinimonConfig {
                 ..Default::default()
             },
             gpus: HashMap::new(),
+            power: PowerConfig::default(),
+            battery: BatteryConfig::default(),
+            profile_overrides: ProfileOverrides::default(),
             sysmon: None,
             panel_spacing: 3, // Slider setting for cosmic.space_xs()
             content_order: ContentOrder::default(),
@@ -0,0 +1,247 @@
+use cosmic::iced::Subscription;
+use cosmic::iced::futures::SinkExt;
+use log::{error, info};
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use crate::app::Message;
+
+// Multicast group of the uevents sent by the kernel itself, udev rebroadcasts on group 2
+const KERNEL_UEVENT_GROUP: u32 = 1;
+
+// Large enough for any uevent
+const UEVENT_BUFFER_SIZE: usize = 8192;
+
+/// A GPU appearing or going away, with the name of its card node, like card1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrmEvent {
+    Added(String),
+    Removed(String),
+}
+
+/// Emits `Message::GpuHotplug` when a DRM card node is added or removed
+pub fn drm_hotplug_subscription() -> Subscription<Message> {
+    struct DrmHotplug;
+
+    Subscription::run_with_id(
+        std::any::TypeId::of::<DrmHotplug>(),
+        cosmic::iced::stream::channel(4, |output| async move {
+            match open_uevent_socket() {
+                Ok(socket) => {
+                    // Reading the socket blocks, keep it off the executor
+                    let spawned = std::thread::Builder::new()
+                        .name("drm-hotplug".to_string())
+                        .spawn(move || watch_uevents(socket, output));
+                    if let Err(e) = spawned {
+                        error!("Hotplug: could not start watcher thread: {e}");
+                    }
+                }
+                Err(e) => error!("Hotplug: could not open uevent socket: {e}"),
+            }
+
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+fn watch_uevents(
+    mut socket: File,
+    mut output: cosmic::iced::futures::channel::mpsc::Sender<Message>,
+) {
+    let mut buffer = [0u8; UEVENT_BUFFER_SIZE];
+    loop {
+        let len = match socket.read(&mut buffer) {
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                error!("Hotplug: reading uevents failed: {e}");
+                return;
+            }
+        };
+
+        if let Some(event) = parse_uevent(&buffer[..len]) {
+            info!("Hotplug: DRM card {event:?}");
+            // Each event names its card, so wait for room rather than dropping one
+            if zbus::block_on(output.send(Message::GpuHotplug(event))).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Open a netlink socket subscribed to the kernel's kobject uevents.
+///
+/// std has no netlink support and pulling in udev or a netlink crate for one socket
+/// isn't worth it, so this goes through libc, which is already a dependency. The
+/// unsafe is limited to the socket and bind calls and taking ownership of the fd,
+/// everything after that is a plain `File`.
+fn open_uevent_socket() -> io::Result<File> {
+    // SAFETY: plain socket creation, the result is checked before use
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+            libc::NETLINK_KOBJECT_UEVENT,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: fd is a freshly created socket that nothing else owns
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // SAFETY: sockaddr_nl is plain data, all zeroes is a valid value
+    let mut address: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+    address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    address.nl_groups = KERNEL_UEVENT_GROUP;
+
+    // SAFETY: address lives for the call and the length matches its type
+    let result = unsafe {
+        libc::bind(
+            fd.as_raw_fd(),
+            (&raw const address).cast::<libc::sockaddr>(),
+            std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(File::from(fd))
+}
+
+/// Parse a kernel uevent, which is `action@devpath` followed by `KEY=value` lines,
+/// all separated by NUL bytes.
+///
+/// Returns None for anything but a DRM card node being added or removed.
+pub fn parse_uevent(data: &[u8]) -> Option<DrmEvent> {
+    let mut action = None;
+    let mut is_drm = false;
+    let mut card = None;
+
+    for field in data.split(|b| *b == 0) {
+        let field = std::str::from_utf8(field).ok()?;
+        if let Some(value) = field.strip_prefix("ACTION=") {
+            action = Some(value);
+        } else if let Some(value) = field.strip_prefix("SUBSYSTEM=") {
+            is_drm = value == "drm";
+        } else if let Some(value) = field.strip_prefix("DEVNAME=") {
+            // dri/card0, not the render nodes or the connectors
+            card = value.strip_prefix("dri/").filter(|name| {
+                name.strip_prefix("card")
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            });
+        }
+    }
+
+    if !is_drm {
+        return None;
+    }
+    let card = card?.to_string();
+    match action? {
+        "add" => Some(DrmEvent::Added(card)),
+        "remove" => Some(DrmEvent::Removed(card)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // As read from the socket with an eGPU plugged in and out
+    const CARD_ADD: &[u8] = b"add@/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/card1\0\
+        ACTION=add\0\
+        DEVPATH=/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/card1\0\
+        SUBSYSTEM=drm\0\
+        DEVNAME=dri/card1\0\
+        DEVTYPE=drm_minor\0\
+        SEQNUM=5123\0\
+        MAJOR=226\0\
+        MINOR=1\0";
+
+    const CARD_REMOVE: &[u8] = b"remove@/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/card1\0\
+        ACTION=remove\0\
+        DEVPATH=/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/card1\0\
+        SUBSYSTEM=drm\0\
+        DEVNAME=dri/card1\0\
+        DEVTYPE=drm_minor\0\
+        SEQNUM=5187\0\
+        MAJOR=226\0\
+        MINOR=1\0";
+
+    const RENDER_ADD: &[u8] = b"add@/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/renderD128\0\
+        ACTION=add\0\
+        DEVPATH=/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/renderD128\0\
+        SUBSYSTEM=drm\0\
+        DEVNAME=dri/renderD128\0\
+        DEVTYPE=drm_minor\0\
+        SEQNUM=5124\0\
+        MAJOR=226\0\
+        MINOR=128\0";
+
+    const CONNECTOR_ADD: &[u8] =
+        b"add@/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/card1/card1-DP-1\0\
+        ACTION=add\0\
+        DEVPATH=/devices/pci0000:00/0000:00:01.1/0000:01:00.0/drm/card1/card1-DP-1\0\
+        SUBSYSTEM=drm\0\
+        SEQNUM=5125\0";
+
+    const CARD_CHANGE: &[u8] = b"change@/devices/pci0000:00/0000:00:02.0/drm/card0\0\
+        ACTION=change\0\
+        DEVPATH=/devices/pci0000:00/0000:00:02.0/drm/card0\0\
+        SUBSYSTEM=drm\0\
+        HOTPLUG=1\0\
+        DEVNAME=dri/card0\0\
+        DEVTYPE=drm_minor\0\
+        SEQNUM=4410\0\
+        MAJOR=226\0\
+        MINOR=0\0";
+
+    const USB_ADD: &[u8] = b"add@/devices/pci0000:00/0000:00:14.0/usb1/1-2\0\
+        ACTION=add\0\
+        DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-2\0\
+        SUBSYSTEM=usb\0\
+        DEVNAME=bus/usb/001/007\0\
+        DEVTYPE=usb_device\0\
+        SEQNUM=5201\0\
+        MAJOR=189\0\
+        MINOR=6\0";
+
+    #[test]
+    fn card_added_and_removed() {
+        assert_eq!(
+            parse_uevent(CARD_ADD),
+            Some(DrmEvent::Added("card1".to_string()))
+        );
+        assert_eq!(
+            parse_uevent(CARD_REMOVE),
+            Some(DrmEvent::Removed("card1".to_string()))
+        );
+    }
+
+    #[test]
+    fn other_drm_nodes_ignored() {
+        assert_eq!(parse_uevent(RENDER_ADD), None);
+        assert_eq!(parse_uevent(CONNECTOR_ADD), None);
+        assert_eq!(parse_uevent(CARD_CHANGE), None);
+    }
+
+    #[test]
+    fn other_subsystems_ignored() {
+        assert_eq!(parse_uevent(USB_ADD), None);
+    }
+
+    #[test]
+    fn malformed_ignored() {
+        assert_eq!(parse_uevent(b""), None);
+        assert_eq!(parse_uevent(b"libudev\0\xfe\xed\xca\xfe"), None);
+
+        // A trailing message without the final NUL still parses
+        assert_eq!(
+            parse_uevent(&CARD_ADD[..CARD_ADD.len() - 1]),
+            Some(DrmEvent::Added("card1".to_string()))
+        );
+    }
+}
@@ -12,11 +12,13 @@ use i18n_embed::{
 use log::info;
 use rust_embed::RustEmbed;
 
-/// Applies the requested language(s) to requested translations from the `fl!()` macro.
+/// Applies the requested language(s) to requested translations from the `fl!()` macro,
+/// and to the formatting of numbers.
 pub fn init(requested_languages: &[LanguageIdentifier]) {
     if let Err(why) = localizer().select(requested_languages) {
         info!("error while loading fluent localizations: {why}");
     }
+    crate::locale::init(requested_languages);
 }
 
 // Get the `Localizer` to be used for localizing this library.
@@ -0,0 +1,267 @@
+//! Number formatting that follows the desktop language.
+//!
+//! Sensors format the numbers of their values with `format_number`, never the text
+//! around them: the decimal point becomes the separator of the language, `unit_symbol`
+//! gives percent and degree signs the space some languages put before them, and the
+//! popup groups the digits of long numbers with `format_grouped`.
+
+use std::sync::OnceLock;
+
+use i18n_embed::unic_langid::LanguageIdentifier;
+use log::info;
+
+const NBSP: char = '\u{a0}';
+const NARROW_NBSP: char = '\u{202f}';
+
+/// Conventions for writing numbers in a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimal: char,
+    group: char,
+    /// Integer parts with fewer digits are not grouped
+    min_grouping: usize,
+    /// Put before `%` and `°`, if the language separates them from the number
+    unit_space: Option<char>,
+}
+
+const ENGLISH: NumberFormat = NumberFormat {
+    decimal: '.',
+    group: ',',
+    min_grouping: 4,
+    unit_space: None,
+};
+
+// The languages the applet has translations for
+const FORMATS: &[(&str, NumberFormat)] = &[
+    ("en", ENGLISH),
+    ("ar", ENGLISH),
+    ("zh", ENGLISH),
+    (
+        "bg",
+        NumberFormat {
+            decimal: ',',
+            group: NBSP,
+            min_grouping: 4,
+            unit_space: None,
+        },
+    ),
+    (
+        "cs",
+        NumberFormat {
+            decimal: ',',
+            group: NBSP,
+            min_grouping: 4,
+            unit_space: Some(NBSP),
+        },
+    ),
+    (
+        "da",
+        NumberFormat {
+            decimal: ',',
+            group: '.',
+            min_grouping: 4,
+            unit_space: Some(NBSP),
+        },
+    ),
+    (
+        "de",
+        NumberFormat {
+            decimal: ',',
+            group: '.',
+            min_grouping: 4,
+            unit_space: Some(NBSP),
+        },
+    ),
+    (
+        "es",
+        NumberFormat {
+            decimal: ',',
+            group: '.',
+            min_grouping: 5,
+            unit_space: Some(NBSP),
+        },
+    ),
+    (
+        "fr",
+        NumberFormat {
+            decimal: ',',
+            group: NARROW_NBSP,
+            min_grouping: 4,
+            unit_space: Some(NARROW_NBSP),
+        },
+    ),
+    (
+        "hu",
+        NumberFormat {
+            decimal: ',',
+            group: NBSP,
+            min_grouping: 4,
+            unit_space: None,
+        },
+    ),
+    (
+        "nl",
+        NumberFormat {
+            decimal: ',',
+            group: '.',
+            min_grouping: 4,
+            unit_space: None,
+        },
+    ),
+    (
+        "pl",
+        NumberFormat {
+            decimal: ',',
+            group: NBSP,
+            min_grouping: 5,
+            unit_space: None,
+        },
+    ),
+    (
+        "pt",
+        NumberFormat {
+            decimal: ',',
+            group: '.',
+            min_grouping: 4,
+            unit_space: None,
+        },
+    ),
+    (
+        "sv",
+        NumberFormat {
+            decimal: ',',
+            group: NBSP,
+            min_grouping: 4,
+            unit_space: Some(NBSP),
+        },
+    ),
+];
+
+static FORMAT: OnceLock<NumberFormat> = OnceLock::new();
+
+/// Picks the conventions of the first requested language that has them, as `i18n::init`
+/// picks the translation.
+pub fn init(requested_languages: &[LanguageIdentifier]) {
+    let format = requested_languages
+        .iter()
+        .find_map(|id| {
+            FORMATS
+                .iter()
+                .find(|(language, _)| *language == id.language.as_str())
+        })
+        .map_or(ENGLISH, |(language, format)| {
+            info!("Number format of {language}");
+            *format
+        });
+    let _ = FORMAT.set(format);
+}
+
+pub fn get() -> &'static NumberFormat {
+    FORMAT.get().unwrap_or(&ENGLISH)
+}
+
+/// `value` with `decimals` decimals, in the conventions of the desktop language
+pub fn format_number(value: f64, decimals: usize) -> String {
+    get().number(value, decimals, false)
+}
+
+/// As `format_number`, with the digits of long numbers grouped, for the popup
+pub fn format_grouped(value: f64, decimals: usize) -> String {
+    get().number(value, decimals, true)
+}
+
+/// A unit symbol to put after a number, with the space the language puts before `%`
+/// and `°`
+pub fn unit_symbol(symbol: &str) -> String {
+    get().symbol(symbol)
+}
+
+impl NumberFormat {
+    pub fn number(&self, value: f64, decimals: usize, grouped: bool) -> String {
+        let text = format!("{value:.decimals$}");
+        let (integer, fraction) = match text.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (text.as_str(), None),
+        };
+        let (sign, digits) = match integer.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", integer),
+        };
+
+        let mut result = String::with_capacity(text.len() + 4);
+        result.push_str(sign);
+        if grouped && digits.len() >= self.min_grouping {
+            for (n, digit) in digits.chars().enumerate() {
+                if n > 0 && (digits.len() - n) % 3 == 0 {
+                    result.push(self.group);
+                }
+                result.push(digit);
+            }
+        } else {
+            result.push_str(digits);
+        }
+        if let Some(fraction) = fraction {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    pub fn symbol(&self, symbol: &str) -> String {
+        match self.unit_space {
+            Some(space) if symbol.starts_with(['%', '°']) => format!("{space}{symbol}"),
+            _ => symbol.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(language: &str) -> NumberFormat {
+        FORMATS
+            .iter()
+            .find(|(l, _)| *l == language)
+            .map(|(_, format)| *format)
+            .unwrap()
+    }
+
+    #[test]
+    fn english() {
+        let en = format("en");
+        assert_eq!(en.number(12.345, 1, false), "12.3");
+        assert_eq!(en.number(1234.0, 0, false), "1234");
+        assert_eq!(en.number(1234.5, 1, true), "1,234.5");
+        assert_eq!(en.number(-0.5, 2, false), "-0.50");
+        assert_eq!(en.symbol("%"), "%");
+    }
+
+    #[test]
+    fn german() {
+        let de = format("de");
+        assert_eq!(de.number(12.5, 1, false), "12,5");
+        assert_eq!(de.number(1234.5, 1, false), "1234,5");
+        assert_eq!(de.number(1234.5, 1, true), "1.234,5");
+        assert_eq!(de.number(123.0, 0, true), "123");
+        assert_eq!(de.symbol("%"), "\u{a0}%");
+        assert_eq!(de.symbol("°C"), "\u{a0}°C");
+        assert_eq!(de.symbol("K"), "K");
+    }
+
+    #[test]
+    fn french() {
+        let fr = format("fr");
+        assert_eq!(fr.number(0.75, 2, false), "0,75");
+        assert_eq!(fr.number(1_234_567.0, 0, true), "1\u{202f}234\u{202f}567");
+        assert_eq!(fr.symbol("%"), "\u{202f}%");
+    }
+
+    #[test]
+    fn spanish_groups_from_five_digits() {
+        let es = format("es");
+        assert_eq!(es.number(1234.0, 0, true), "1234");
+        assert_eq!(es.number(12_345.25, 2, true), "12.345,25");
+        assert_eq!(es.symbol("%"), "\u{a0}%");
+    }
+}
@@ -4,15 +4,19 @@ use app::Minimon;
 
 mod app;
 mod barchart;
+mod charts;
 mod colorpicker;
 mod config;
+mod hotplug;
 mod i18n;
+mod locale;
+mod render_cache;
 mod sensors;
 mod svg_graph;
 mod system_monitors;
-
-#[cfg(feature = "lyon_charts")]
-mod charts;
+mod template;
+mod units;
+mod upower;
 
 use chrono::Local;
 use log::info;
@@ -0,0 +1,259 @@
+//! Caches for the SVG charts.
+//!
+//! `view()` runs on every event, not only on a refresh, and used to build every chart
+//! from scratch each time. The SVG text is now looked up by a hash of what the chart is
+//! drawn from, and the icon handle by a hash of the text. A chart whose samples didn't
+//! change costs a hash, and the renderer gets the same handle, so it keeps its image.
+//!
+//! The key includes the samples, so a refresh that adds a sample misses for every chart
+//! that shows it and each chart is still built once per tick. What the cache saves are
+//! the rebuilds between ticks, when the popup or the panel redraw for other events, and
+//! the charts of sensors whose samples didn't move, such as an idle disk.
+//!
+//! Setting `MINIMON_RENDER_STATS` logs how much time the charts take, see `RenderStats`.
+//! The `bench_charts` test in `svg_graph` times building every chart kind per tick,
+//! run it with `cargo test --release bench_charts -- --ignored --nocapture`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use cosmic::widget::icon;
+use log::info;
+
+// Entries kept per generation, a bit more than all charts of all sensors
+const CACHE_CAPACITY: usize = 64;
+
+// Ticks between two logs of the render stats
+const STATS_INTERVAL: u32 = 20;
+
+static STATS_ENABLED: LazyLock<bool> =
+    LazyLock::new(|| std::env::var_os("MINIMON_RENDER_STATS").is_some());
+
+thread_local! {
+    static SVG_CACHE: RefCell<RenderCache<String>> = RefCell::new(RenderCache::new(CACHE_CAPACITY));
+    static ICON_CACHE: RefCell<RenderCache<icon::Handle>> =
+        RefCell::new(RenderCache::new(CACHE_CAPACITY));
+    static STATS: RefCell<RenderStats> = RefCell::new(RenderStats::default());
+}
+
+/// A map that forgets what wasn't used for a while.
+///
+/// New entries go into the current generation. When that is full it becomes the
+/// previous one and the old previous one is dropped, entries found there move back.
+pub struct RenderCache<V> {
+    current: HashMap<u64, V>,
+    previous: HashMap<u64, V>,
+    capacity: usize,
+}
+
+impl<V: Clone> RenderCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        RenderCache {
+            current: HashMap::with_capacity(capacity),
+            previous: HashMap::new(),
+            capacity,
+        }
+    }
+
+    /// The value for `key`, created by `render` if it isn't cached.
+    /// Returns the value and whether it was cached.
+    pub fn get_or_insert_with(&mut self, key: u64, render: impl FnOnce() -> V) -> (V, bool) {
+        if let Some(value) = self.current.get(&key) {
+            return (value.clone(), true);
+        }
+
+        let (value, hit) = match self.previous.remove(&key) {
+            Some(value) => (value, true),
+            None => (render(), false),
+        };
+
+        if self.current.len() >= self.capacity {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        self.current.insert(key, value.clone());
+        (value, hit)
+    }
+
+    pub fn entries(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+}
+
+/// Hash of what a chart is drawn from, `kind` tells the chart functions apart
+pub fn chart_key(kind: &str, hash: impl FnOnce(&mut DefaultHasher)) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    kind.hash(&mut hasher);
+    hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash samples by their bits, f64 has no Hash
+pub fn hash_samples<'a>(hasher: &mut DefaultHasher, samples: impl IntoIterator<Item = &'a f64>) {
+    let mut count = 0usize;
+    for sample in samples {
+        sample.to_bits().hash(hasher);
+        count += 1;
+    }
+    count.hash(hasher);
+}
+
+/// The SVG for `key`, only built by `render` if it isn't cached
+pub fn cached_svg(key: u64, render: impl FnOnce() -> String) -> String {
+    SVG_CACHE.with_borrow_mut(|cache| {
+        let start = stats_enabled().then(Instant::now);
+        let (svg, hit) = cache.get_or_insert_with(key, render);
+        if let Some(start) = start {
+            STATS.with_borrow_mut(|stats| stats.record_svg(hit, start.elapsed()));
+        }
+        svg
+    })
+}
+
+/// An icon handle for the SVG, the same handle as last time if the SVG didn't change
+pub fn cached_icon(svg: &str) -> icon::Handle {
+    let mut hasher = DefaultHasher::new();
+    svg.hash(&mut hasher);
+    let key = hasher.finish();
+
+    ICON_CACHE.with_borrow_mut(|cache| {
+        let (handle, hit) = cache.get_or_insert_with(key, || {
+            icon::from_svg_bytes(svg.as_bytes().to_vec()).symbolic(false)
+        });
+        if stats_enabled() {
+            STATS.with_borrow_mut(|stats| stats.record_icon(hit));
+        }
+        handle
+    })
+}
+
+pub fn stats_enabled() -> bool {
+    *STATS_ENABLED
+}
+
+/// Count a panel view that took `elapsed` to build
+pub fn record_view(elapsed: Duration) {
+    STATS.with_borrow_mut(|stats| {
+        stats.views += 1;
+        stats.view_time += elapsed;
+    });
+}
+
+/// Called on every refresh, logs and resets the stats every `STATS_INTERVAL` ticks
+pub fn tick() {
+    if !stats_enabled() {
+        return;
+    }
+    STATS.with_borrow_mut(|stats| {
+        stats.ticks += 1;
+        if stats.ticks >= STATS_INTERVAL {
+            let cached = SVG_CACHE.with_borrow(RenderCache::entries);
+            info!("Render stats: {stats}, {cached} SVGs cached");
+            *stats = RenderStats::default();
+        }
+    });
+}
+
+/// Time spent on the charts since the last log
+#[derive(Debug, Default)]
+pub struct RenderStats {
+    ticks: u32,
+    views: u32,
+    view_time: Duration,
+    svg_hits: u32,
+    svg_misses: u32,
+    svg_time: Duration,
+    icon_hits: u32,
+    icon_misses: u32,
+}
+
+impl RenderStats {
+    fn record_svg(&mut self, hit: bool, elapsed: Duration) {
+        if hit {
+            self.svg_hits += 1;
+        } else {
+            self.svg_misses += 1;
+        }
+        self.svg_time += elapsed;
+    }
+
+    fn record_icon(&mut self, hit: bool) {
+        if hit {
+            self.icon_hits += 1;
+        } else {
+            self.icon_misses += 1;
+        }
+    }
+}
+
+impl std::fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let per = |total: Duration, count: u32| total.as_micros() / u128::from(count.max(1));
+        write!(
+            f,
+            "{} views in {} ticks, {} µs per view, {} µs per tick on SVGs, \
+             SVG cache {}/{} hits, icon cache {}/{} hits",
+            self.views,
+            self.ticks,
+            per(self.view_time, self.views),
+            per(self.svg_time, self.ticks),
+            self.svg_hits,
+            self.svg_hits + self.svg_misses,
+            self.icon_hits,
+            self.icon_hits + self.icon_misses,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_and_miss() {
+        let mut cache = RenderCache::new(4);
+        let mut renders = 0;
+        let mut render = || {
+            renders += 1;
+            renders
+        };
+
+        assert_eq!(cache.get_or_insert_with(1, &mut render), (1, false));
+        assert_eq!(cache.get_or_insert_with(1, &mut render), (1, true));
+        assert_eq!(cache.get_or_insert_with(2, &mut render), (2, false));
+        assert_eq!(renders, 2);
+        assert_eq!(cache.entries(), 2);
+    }
+
+    #[test]
+    fn generations() {
+        let mut cache = RenderCache::new(2);
+        cache.get_or_insert_with(1, || "one");
+        cache.get_or_insert_with(2, || "two");
+        // Full, 1 and 2 become the previous generation
+        cache.get_or_insert_with(3, || "three");
+        assert_eq!(cache.entries(), 3);
+
+        // Found in the previous generation and moved back
+        assert_eq!(cache.get_or_insert_with(1, || "new"), ("one", true));
+
+        // Full again, the previous generation with 2 is dropped
+        cache.get_or_insert_with(4, || "four");
+        assert_eq!(cache.get_or_insert_with(2, || "new"), ("new", false));
+        assert_eq!(cache.get_or_insert_with(1, || "new"), ("one", true));
+    }
+
+    #[test]
+    fn keys_differ_by_kind_and_content() {
+        let samples = [1.0, 2.0, 3.0];
+        let key = |kind: &str, samples: &[f64]| chart_key(kind, |h| hash_samples(h, samples));
+
+        assert_eq!(key("line", &samples), key("line", &samples));
+        assert_ne!(key("line", &samples), key("heat", &samples));
+        assert_ne!(key("line", &samples), key("line", &samples[..2]));
+        assert_ne!(key("line", &samples), key("line", &[1.0, 2.0, 4.0]));
+    }
+}
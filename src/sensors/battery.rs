@@ -0,0 +1,692 @@
+use cosmic::{Element, iced::Alignment::Center, widget::Container};
+
+use crate::{
+    charts::{line::LineChart, ring::RingChart},
+    colorpicker::DemoGraph,
+    config::{BatteryConfig, ChartColors, ChartKind, ColorVariant, DeviceKind},
+    fl,
+    sensors::INVALID_IMG,
+    svg_graph::{ChartSize, SvgColors},
+};
+
+use cosmic::widget;
+use cosmic::widget::{settings, toggler};
+use std::any::Any;
+
+use cosmic::{
+    iced::{
+        Alignment,
+        widget::{column, row},
+    },
+    widget::Row,
+};
+use log::info;
+
+use crate::app::Message;
+
+use bounded_vec_deque::BoundedVecDeque;
+use std::collections::HashMap;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use zbus::blocking::{Connection, Proxy};
+use zvariant::{OwnedObjectPath, OwnedValue};
+
+use super::{DisplayOptions, Sensor};
+
+const MAX_SAMPLES: usize = 21;
+
+// UPower device types and states, see org.freedesktop.UPower.Device
+const UPOWER_TYPE_BATTERY: u32 = 2;
+const UPOWER_STATE_CHARGING: u32 = 1;
+const UPOWER_STATE_DISCHARGING: u32 = 2;
+const UPOWER_STATE_FULLY_CHARGED: u32 = 4;
+const UPOWER_STATE_PENDING_CHARGE: u32 = 5;
+const UPOWER_STATE_PENDING_DISCHARGE: u32 = 6;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatteryState {
+    #[default]
+    Unknown,
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+}
+
+impl std::fmt::Display for BatteryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatteryState::Unknown => write!(f, "{}", fl!("battery-state-unknown")),
+            BatteryState::Charging => write!(f, "{}", fl!("battery-state-charging")),
+            BatteryState::Discharging => write!(f, "{}", fl!("battery-state-discharging")),
+            BatteryState::Full => write!(f, "{}", fl!("battery-state-full")),
+            BatteryState::NotCharging => write!(f, "{}", fl!("battery-state-not-charging")),
+        }
+    }
+}
+
+/// One reading of the battery
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BatteryStatus {
+    pub percentage: f64,
+    /// Charge or discharge rate in watts
+    pub rate: f64,
+    pub state: BatteryState,
+    /// Seconds until empty or full, None if unknown
+    pub time_to_empty: Option<u64>,
+    pub time_to_full: Option<u64>,
+    /// Full capacity relative to design capacity, in percent
+    pub health: Option<f64>,
+}
+
+impl BatteryStatus {
+    /// The status in the properties of a UPower device, as `GetAll` returns them
+    pub fn from_upower(properties: &HashMap<String, OwnedValue>) -> Option<BatteryStatus> {
+        let float = |name: &str| properties.get(name)?.downcast_ref::<f64>().ok();
+        let seconds = |name: &str| {
+            let seconds = properties.get(name)?.downcast_ref::<i64>().ok()?;
+            u64::try_from(seconds).ok().filter(|t| *t > 0)
+        };
+
+        let state = match properties.get("State")?.downcast_ref::<u32>().ok()? {
+            UPOWER_STATE_CHARGING => BatteryState::Charging,
+            UPOWER_STATE_DISCHARGING | UPOWER_STATE_PENDING_DISCHARGE => BatteryState::Discharging,
+            UPOWER_STATE_FULLY_CHARGED => BatteryState::Full,
+            UPOWER_STATE_PENDING_CHARGE => BatteryState::NotCharging,
+            _ => BatteryState::Unknown,
+        };
+
+        Some(BatteryStatus {
+            percentage: float("Percentage")?,
+            rate: float("EnergyRate").unwrap_or(0.0),
+            state,
+            time_to_empty: seconds("TimeToEmpty"),
+            time_to_full: seconds("TimeToFull"),
+            health: health(
+                float("EnergyFull").unwrap_or(0.0),
+                float("EnergyFullDesign").unwrap_or(0.0),
+            ),
+        })
+    }
+}
+
+/// Where battery data is read from
+#[derive(Debug)]
+enum BatterySource {
+    /// Read once here, then kept up to date by `upower::battery_subscription`
+    UPower(OwnedObjectPath),
+    Sysfs(PathBuf),
+}
+
+impl BatterySource {
+    /// The source and its status at start
+    fn find() -> Option<(BatterySource, Option<BatteryStatus>)> {
+        match Self::find_upower() {
+            Ok(Some((path, status))) => {
+                info!("Battery: using UPower device {}", path.as_str());
+                return Some((BatterySource::UPower(path), status));
+            }
+            Ok(None) => info!("Battery: no UPower battery device"),
+            Err(e) => info!("Battery: UPower not available: {e}"),
+        }
+
+        let path = Self::find_sysfs()?;
+        info!("Battery: using {path:?}");
+        let status = Self::read_sysfs(&path);
+        Some((BatterySource::Sysfs(path), status))
+    }
+
+    fn find_upower() -> zbus::Result<Option<(OwnedObjectPath, Option<BatteryStatus>)>> {
+        let connection = Connection::system()?;
+
+        let upower = Proxy::new(
+            &connection,
+            "org.freedesktop.UPower",
+            "/org/freedesktop/UPower",
+            "org.freedesktop.UPower",
+        )?;
+
+        let devices: Vec<OwnedObjectPath> = upower.call("EnumerateDevices", &())?;
+
+        for device_path in devices {
+            let device = Proxy::new(
+                &connection,
+                "org.freedesktop.UPower",
+                device_path.clone().into_inner(),
+                "org.freedesktop.DBus.Properties",
+            )?;
+            let properties: HashMap<String, OwnedValue> =
+                device.call("GetAll", &("org.freedesktop.UPower.Device",))?;
+
+            // Skip peripherals like mice and headsets, they also report Type 2 on some systems
+            let flag = |name: &str| properties.get(name)?.downcast_ref::<bool>().ok();
+            let kind = properties
+                .get("Type")
+                .and_then(|kind| kind.downcast_ref::<u32>().ok());
+            if kind == Some(UPOWER_TYPE_BATTERY) && flag("PowerSupply") == Some(true) {
+                return Ok(Some((device_path, BatteryStatus::from_upower(&properties))));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn find_sysfs() -> Option<PathBuf> {
+        sysfs_batteries().into_iter().next()
+    }
+
+    fn read_sysfs(path: &Path) -> Option<BatteryStatus> {
+        let read_f64 = |file: &str| -> Option<f64> {
+            fs::read_to_string(path.join(file))
+                .ok()?
+                .trim()
+                .parse::<f64>()
+                .ok()
+        };
+
+        let state = match fs::read_to_string(path.join("status")).ok()?.trim() {
+            "Charging" => BatteryState::Charging,
+            "Discharging" => BatteryState::Discharging,
+            "Full" => BatteryState::Full,
+            "Not charging" => BatteryState::NotCharging,
+            _ => BatteryState::Unknown,
+        };
+
+        // Batteries report either energy (µWh) or charge (µAh), convert charge using the voltage
+        let voltage = read_f64("voltage_min_design")
+            .or_else(|| read_f64("voltage_now"))
+            .map(|uv| uv / 1_000_000.0);
+        let energy = |name: &str| -> Option<f64> {
+            read_f64(&format!("energy_{name}"))
+                .or_else(|| Some(read_f64(&format!("charge_{name}"))? * voltage?))
+                .map(|uwh| uwh / 1_000_000.0)
+        };
+
+        let energy_now = energy("now");
+        let energy_full = energy("full");
+        let energy_full_design = energy("full_design");
+
+        let rate = read_f64("power_now")
+            .or_else(|| Some(read_f64("current_now")? * read_f64("voltage_now")? / 1_000_000.0))
+            .map_or(0.0, |uw| uw / 1_000_000.0);
+
+        let percentage = read_f64("capacity").or_else(|| match (energy_now, energy_full) {
+            (Some(now), Some(full)) if full > 0.0 => Some(now / full * 100.0),
+            _ => None,
+        })?;
+
+        let hours = |wh: f64| -> Option<u64> {
+            (rate > 0.0 && wh > 0.0).then(|| (wh / rate * 3600.0) as u64)
+        };
+        let time_to_empty = match (state, energy_now) {
+            (BatteryState::Discharging, Some(now)) => hours(now),
+            _ => None,
+        };
+        let time_to_full = match (state, energy_now, energy_full) {
+            (BatteryState::Charging, Some(now), Some(full)) => hours(full - now),
+            _ => None,
+        };
+
+        Some(BatteryStatus {
+            percentage,
+            rate,
+            state,
+            time_to_empty,
+            time_to_full,
+            health: health(
+                energy_full.unwrap_or(0.0),
+                energy_full_design.unwrap_or(0.0),
+            ),
+        })
+    }
+}
+
+/// The /sys/class/power_supply/BAT* devices, in directory order
+pub fn sysfs_batteries() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir("/sys/class/power_supply") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+        .map(|entry| entry.path())
+        .collect()
+}
+
+fn health(energy_full: f64, energy_full_design: f64) -> Option<f64> {
+    (energy_full > 0.0 && energy_full_design > 0.0)
+        .then(|| (energy_full / energy_full_design * 100.0).min(100.0))
+}
+
+/// Format seconds as h:mm
+fn format_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[derive(Debug)]
+pub struct Battery {
+    source: Option<BatterySource>,
+    status: BatteryStatus,
+    samples: BoundedVecDeque<f64>,
+    graph_options: &'static super::GraphOptions,
+    /// colors cached so we don't need to convert to string every time
+    svg_colors: SvgColors,
+    display: DisplayOptions,
+    config: BatteryConfig,
+}
+
+impl DemoGraph for Battery {
+    fn demo(&self) -> String {
+        match self.config.chart {
+            ChartKind::Ring => crate::svg_graph::ring("72", 72, None, &self.svg_colors),
+            ChartKind::Line => crate::svg_graph::line(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                100.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            ChartKind::Gauge => {
+                crate::svg_graph::gauge(&[0.4], &self.svg_colors, ChartSize::SQUARE)
+            }
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                DEMO_SAMPLES.len(),
+                100.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            _ => {
+                log::error!(
+                    "Graph type {:?} not supported for battery",
+                    self.config.chart
+                );
+                INVALID_IMG.to_string()
+            }
+        }
+    }
+
+    fn colors(&self) -> &ChartColors {
+        self.config.colors()
+    }
+
+    fn set_colors(&mut self, colors: &ChartColors) {
+        *self.config.colors_mut() = *colors;
+        self.svg_colors.set_colors(colors);
+    }
+
+    fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
+        if self.config.chart == ChartKind::Sparkline {
+            (*super::COLOR_CHOICES_SPARKLINE).into()
+        } else if matches!(self.config.chart, ChartKind::Line | ChartKind::Gauge) {
+            (*super::COLOR_CHOICES_LINE).into()
+        } else {
+            (*super::COLOR_CHOICES_RING).into()
+        }
+    }
+
+    fn id(&self) -> Option<String> {
+        None
+    }
+
+    fn kind(&self) -> ChartKind {
+        self.config.chart
+    }
+}
+
+impl Sensor for Battery {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
+        if let Some(cfg) = config.downcast_ref::<BatteryConfig>() {
+            self.config = cfg.clone();
+            self.display = display;
+            self.svg_colors.set_colors(cfg.colors());
+        }
+    }
+
+    fn graph_kind(&self) -> ChartKind {
+        self.config.chart
+    }
+
+    fn set_graph_kind(&mut self, kind: ChartKind) {
+        assert!(self.graph_options.contains(kind));
+        self.config.chart = kind;
+    }
+
+    fn update(&mut self) {
+        if let Some(BatterySource::Sysfs(path)) = &self.source
+            && let Some(status) = BatterySource::read_sysfs(path)
+        {
+            self.status = status;
+        }
+        self.samples.push_back(self.status.percentage);
+    }
+
+    fn demo_graph(&self) -> Box<dyn DemoGraph> {
+        let mut dmo = Battery::default();
+        dmo.update_config(&self.config, 0, self.display);
+        Box::new(dmo)
+    }
+
+    fn chart(
+        &'_ self,
+        height_hint: u16,
+        width_hint: u16,
+    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
+        match self.config.chart {
+            ChartKind::Gauge => {
+                return super::gauge_container(
+                    &[self.status.percentage / 100.0],
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    self.display.canvas,
+                );
+            }
+            ChartKind::Sparkline => {
+                return super::sparkline_container(
+                    &self.samples,
+                    None,
+                    MAX_SAMPLES,
+                    100.0,
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    self.display.canvas,
+                );
+            }
+            _ => (),
+        }
+        let canvas = self.display.canvas;
+        let svg = if self.config.chart == ChartKind::Ring {
+            let percentage = self.status.percentage.round().clamp(0.0, 100.0) as u8;
+            if canvas {
+                return super::canvas_container(RingChart::new(
+                    &percentage.to_string(),
+                    percentage,
+                    None,
+                    self.config.colors(),
+                ));
+            }
+            crate::svg_graph::ring(&percentage.to_string(), percentage, None, &self.svg_colors)
+        } else {
+            let grid = self.config.grid_visible().then(|| "100%".to_owned());
+            if canvas {
+                return super::canvas_container(
+                    LineChart::new(&self.samples, 100.0, self.config.colors()).grid(grid),
+                );
+            }
+            crate::svg_graph::with_grid(
+                crate::svg_graph::line(&self.samples, 100.0, &self.svg_colors, size),
+                100.0,
+                false,
+                grid.as_deref(),
+                &self.svg_colors,
+                size,
+            )
+        };
+        super::svg_icon_container::<Message>(svg)
+    }
+
+    fn settings_ui(&'_ self) -> Element<'_, crate::app::Message> {
+        let theme = cosmic::theme::active();
+        let cosmic = theme.cosmic();
+
+        let mut battery_elements = Vec::new();
+
+        let mut details = column!(
+            cosmic::widget::text::body(self.to_string(false))
+                .width(90)
+                .align_x(Alignment::Center),
+            cosmic::widget::text::caption(self.status.state.to_string())
+                .width(90)
+                .align_x(Alignment::Center)
+        );
+        if self.status.rate > 0.0 {
+            details = details.push(
+                cosmic::widget::text::caption(format!("{:.1} W", self.status.rate))
+                    .width(90)
+                    .align_x(Alignment::Center),
+            );
+        }
+        if let Some(remaining) = self.time_remaining_string() {
+            details = details.push(
+                cosmic::widget::text::caption(remaining)
+                    .width(90)
+                    .align_x(Alignment::Center),
+            );
+        }
+
+        battery_elements.push(Element::from(
+            column!(
+                Container::new(self.chart(60, 60).width(60).height(60))
+                    .width(90)
+                    .align_x(Alignment::Center),
+                details
+            )
+            .padding(5)
+            .align_x(Alignment::Center),
+        ));
+
+        let config = &self.config;
+        let selected = self.graph_options.index(self.graph_kind());
+        let graph_options = self.graph_options;
+        let battery_kind = self.graph_kind();
+
+        battery_elements.push(Element::from(
+            column!(
+                settings::item(
+                    fl!("enable-chart"),
+                    toggler(config.chart_visible()).on_toggle(Message::ToggleBatteryChart),
+                ),
+                settings::item(
+                    fl!("enable-value"),
+                    toggler(config.value_visible()).on_toggle(Message::ToggleBatteryValue),
+                ),
+                settings::item(
+                    fl!("enable-label"),
+                    toggler(config.label_visible()).on_toggle(Message::ToggleBatteryLabel),
+                ),
+                settings::item(
+                    fl!("enable-icon"),
+                    toggler(config.icon_visible()).on_toggle(Message::ToggleBatteryIcon),
+                ),
+                settings::item(
+                    fl!("battery-show-time"),
+                    toggler(config.show_time).on_toggle(Message::ToggleBatteryTime),
+                ),
+                crate::template::settings_ui(&config.template, |t| {
+                    Message::TemplateChanged(DeviceKind::Battery, t)
+                }),
+                settings::item(
+                    fl!("show-grid"),
+                    toggler(config.grid_visible())
+                        .on_toggle(|t| Message::ToggleGrid(DeviceKind::Battery, t)),
+                ),
+                row!(
+                    widget::text::body(fl!("chart-type")),
+                    widget::dropdown(graph_options.names(), selected, move |m| {
+                        Message::SelectGraphType(DeviceKind::Battery, graph_options.kind(m))
+                    },)
+                    .width(70),
+                    widget::space::horizontal(),
+                    widget::button::standard(fl!("change-colors")).on_press(
+                        Message::ColorPickerOpen(DeviceKind::Battery, battery_kind, None)
+                    ),
+                )
+                .align_y(Center)
+            )
+            .spacing(cosmic.space_xs()),
+        ));
+
+        let mut content = column!(
+            Row::with_children(battery_elements)
+                .align_y(Alignment::Center)
+                .spacing(0)
+        )
+        .spacing(cosmic.space_xs());
+
+        if let Some(health) = self.status.health {
+            content = content.push(settings::item(
+                fl!("battery-health"),
+                widget::text::body(format!("{health:.0}%")),
+            ));
+        }
+
+        content.into()
+    }
+}
+
+impl Default for Battery {
+    fn default() -> Self {
+        let mut battery = Battery {
+            source: None,
+            status: BatteryStatus::default(),
+            samples: BoundedVecDeque::from_iter(std::iter::repeat_n(0.0, MAX_SAMPLES), MAX_SAMPLES),
+            graph_options: &super::GRAPH_OPTIONS_RING_LINE,
+            svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
+            config: BatteryConfig::default(),
+        };
+        battery.set_colors(&ChartColors::default());
+        battery
+    }
+}
+
+impl Battery {
+    pub fn new(is_laptop: bool) -> Self {
+        let (source, status) = if is_laptop {
+            BatterySource::find().unzip()
+        } else {
+            (None, None)
+        };
+        let mut battery = Battery {
+            source,
+            ..Default::default()
+        };
+
+        // Fill the history with the current charge rather than zeros
+        if let Some(status) = status.flatten() {
+            battery.status = status;
+            for sample in battery.samples.iter_mut() {
+                *sample = status.percentage;
+            }
+        }
+        battery
+    }
+
+    pub fn is_found(&self) -> bool {
+        self.source.is_some()
+    }
+
+    pub fn status(&self) -> &BatteryStatus {
+        &self.status
+    }
+
+    /// The UPower device to watch for changes, None when the status comes from sysfs
+    pub fn upower_path(&self) -> Option<&OwnedObjectPath> {
+        match &self.source {
+            Some(BatterySource::UPower(path)) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// A status reported by UPower, sampled on the next update
+    pub fn set_status(&mut self, status: BatteryStatus) {
+        self.status = status;
+    }
+
+    /// Time to empty or full, whichever applies
+    pub fn time_remaining_string(&self) -> Option<String> {
+        match self.status.state {
+            BatteryState::Discharging => self
+                .status
+                .time_to_empty
+                .map(|t| format!("{} {}", format_duration(t), fl!("battery-to-empty"))),
+            BatteryState::Charging => self
+                .status
+                .time_to_full
+                .map(|t| format!("{} {}", format_duration(t), fl!("battery-to-full"))),
+            _ => None,
+        }
+    }
+
+    pub fn to_string(&self, vertical_panel: bool) -> String {
+        let percentage = format!(
+            "{}{}",
+            crate::locale::format_number(self.status.percentage.round(), 0),
+            crate::locale::unit_symbol("%")
+        );
+
+        if !self.config.show_time {
+            return percentage;
+        }
+
+        let remaining = match self.status.state {
+            BatteryState::Discharging => self.status.time_to_empty,
+            BatteryState::Charging => self.status.time_to_full,
+            _ => None,
+        };
+
+        match remaining {
+            Some(t) if vertical_panel => format!("{percentage}\n{}", format_duration(t)),
+            Some(t) => format!("{percentage} {}", format_duration(t)),
+            None => percentage,
+        }
+    }
+}
+
+const DEMO_SAMPLES: [f64; 21] = [
+    92.0, 91.0, 91.0, 90.0, 89.0, 88.0, 88.0, 87.0, 86.0, 85.0, 84.0, 84.0, 83.0, 82.0, 80.0, 79.0,
+    77.0, 76.0, 75.0, 73.0, 72.0,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zvariant::Value;
+
+    fn properties(values: Vec<(&str, Value<'static>)>) -> HashMap<String, OwnedValue> {
+        values
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn upower_status() {
+        let status = BatteryStatus::from_upower(&properties(vec![
+            ("Percentage", Value::from(42.5)),
+            ("EnergyRate", Value::from(8.25)),
+            ("State", Value::from(UPOWER_STATE_DISCHARGING)),
+            ("TimeToEmpty", Value::from(5400_i64)),
+            ("TimeToFull", Value::from(0_i64)),
+            ("EnergyFull", Value::from(45.0)),
+            ("EnergyFullDesign", Value::from(50.0)),
+        ]))
+        .unwrap();
+
+        assert_eq!(status.percentage, 42.5);
+        assert_eq!(status.rate, 8.25);
+        assert_eq!(status.state, BatteryState::Discharging);
+        assert_eq!(status.time_to_empty, Some(5400));
+        assert_eq!(status.time_to_full, None);
+        assert_eq!(status.health, Some(90.0));
+    }
+
+    #[test]
+    fn upower_status_needs_percentage_and_state() {
+        let state = ("State", Value::from(UPOWER_STATE_CHARGING));
+        let percentage = ("Percentage", Value::from(80.0));
+        assert!(BatteryStatus::from_upower(&properties(vec![state.clone()])).is_none());
+        assert!(BatteryStatus::from_upower(&properties(vec![percentage.clone()])).is_none());
+
+        let status = BatteryStatus::from_upower(&properties(vec![state, percentage])).unwrap();
+        assert_eq!(status.state, BatteryState::Charging);
+        assert_eq!(status.health, None);
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
     barchart::StackedBarSvg,
+    charts::{bars::BarChart, line::LineChart, ring::RingChart},
     colorpicker::DemoGraph,
     config::{ChartColors, ChartKind, ColorVariant, CpuConfig, DeviceKind},
     fl,
     sensors::INVALID_IMG,
-    svg_graph::SvgColors,
+    svg_graph::{ChartSize, SvgColors},
 };
 use bounded_vec_deque::BoundedVecDeque;
 use cosmic::{
@@ -31,7 +32,7 @@ use std::{
     path::Path,
 };
 
-use super::Sensor;
+use super::{DisplayOptions, Sensor};
 
 const MAX_SAMPLES: usize = 21;
 
@@ -52,12 +53,14 @@ pub static COLOR_CHOICES_LINE: LazyLock<[(&'static str, ColorVariant); 3]> = Laz
     ]
 });
 
-static GRAPH_OPTIONS_RING_LINE_BARS: LazyLock<[&'static str; 3]> = LazyLock::new(|| {
-    [
-        fl!("graph-type-ring").leak(),
-        fl!("graph-type-line").leak(),
-        fl!("graph-type-bars").leak(),
-    ]
+static GRAPH_OPTIONS_RING_LINE_BARS: LazyLock<super::GraphOptions> = LazyLock::new(|| {
+    super::GraphOptions::new(&[
+        ChartKind::Ring,
+        ChartKind::Line,
+        ChartKind::StackedBars,
+        ChartKind::Gauge,
+        ChartKind::Sparkline,
+    ])
 });
 
 pub static COLOR_CHOICES_BARS: LazyLock<[(&'static str, ColorVariant); 4]> = LazyLock::new(|| {
@@ -101,9 +104,10 @@ pub struct Cpu {
     samples_sum: BoundedVecDeque<f64>,
     // CPU load for the last MAX_SAMPLES updates, split into user and system
     samples_split: BoundedVecDeque<CpuLoad>,
-    graph_options: Vec<&'static str>,
+    graph_options: &'static super::GraphOptions,
     /// colors cached so we don't need to convert to string every time
     svg_colors: SvgColors,
+    display: DisplayOptions,
     config: CpuConfig,
 }
 
@@ -120,6 +124,7 @@ impl DemoGraph for Cpu {
                 &std::collections::VecDeque::from(DEMO_SAMPLES),
                 100.0,
                 &self.svg_colors,
+                ChartSize::SQUARE,
             ),
             ChartKind::Heat => {
                 log::error!("Wrong graph choice!");
@@ -157,6 +162,17 @@ impl DemoGraph for Cpu {
                 );
                 StackedBarSvg::default().svg(&map, &self.svg_colors)
             }
+            ChartKind::Gauge => {
+                crate::svg_graph::gauge(&[0.4], &self.svg_colors, ChartSize::SQUARE)
+            }
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                MAX_SAMPLES,
+                100.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
         }
     }
 
@@ -171,9 +187,10 @@ impl DemoGraph for Cpu {
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
         match self.config.chart {
-            ChartKind::Line => (*COLOR_CHOICES_LINE).into(),
+            ChartKind::Line | ChartKind::Gauge => (*COLOR_CHOICES_LINE).into(),
             ChartKind::Ring => (*COLOR_CHOICES_RING).into(),
             ChartKind::StackedBars => (*COLOR_CHOICES_BARS).into(),
+            ChartKind::Sparkline => (*super::COLOR_CHOICES_SPARKLINE).into(),
             _ => panic!(
                 "CPU color_choices {:?} wrong chart type!",
                 self.config.chart
@@ -191,9 +208,10 @@ impl DemoGraph for Cpu {
 }
 
 impl Sensor for Cpu {
-    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<CpuConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors.set_colors(cfg.colors());
         }
     }
@@ -203,9 +221,7 @@ impl Sensor for Cpu {
     }
 
     fn set_graph_kind(&mut self, kind: ChartKind) {
-        assert!(
-            kind == ChartKind::Line || kind == ChartKind::Ring || kind == ChartKind::StackedBars
-        );
+        assert!(self.graph_options.contains(kind));
         self.config.chart = kind;
     }
 
@@ -218,47 +234,17 @@ impl Sensor for Cpu {
 
     fn demo_graph(&self) -> Box<dyn DemoGraph> {
         let mut dmo = Cpu::new(true);
-        dmo.update_config(&self.config, 0);
+        dmo.update_config(&self.config, 0, self.display);
         Box::new(dmo)
     }
 
-    #[cfg(feature = "lyon_charts")]
-    fn chart<'a>(&self) -> cosmic::widget::Container<crate::app::Message, Theme, Renderer> {
-        if self.config.kind == ChartKind::Ring {
-            let latest = self.latest_sample();
-            let mut value = String::with_capacity(10);
-
-            if self.config.no_decimals {
-                write!(value, "{}%", latest.round()).unwrap();
-            } else if latest < 10.0 {
-                write!(value, "{latest:.2}").unwrap()
-            } else if latest <= 99.9 {
-                write!(value, "{latest:.1}").unwrap();
-            } else {
-                write!(value, "100").unwrap();
-            }
-            chart_container!(crate::charts::ring::RingChart::new(
-                latest as f32,
-                &value,
-                &self.config.colors,
-            ))
-        } else {
-            chart_container!(crate::charts::line::LineChart::new(
-                MAX_SAMPLES,
-                &self.samples_sum,
-                &VecDeque::new(),
-                Some(100.0),
-                &self.config.colors,
-            ))
-        }
-    }
-
-    #[cfg(not(feature = "lyon_charts"))]
     fn chart(
         &'_ self,
         height_hint: u16,
-        _width_hint: u16,
+        width_hint: u16,
     ) -> cosmic::widget::Container<'_, crate::app::Message, Theme, Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
+        let canvas = self.display.canvas;
         let svg = match self.config.chart {
             ChartKind::Ring => {
                 let latest = self.latest_sample();
@@ -276,13 +262,65 @@ impl Sensor for Cpu {
 
                 let percentage: u8 = latest.round().clamp(0.0, 100.0) as u8;
 
+                if canvas {
+                    return super::canvas_container(RingChart::new(
+                        &value,
+                        percentage,
+                        None,
+                        self.config.colors(),
+                    ));
+                }
                 crate::svg_graph::ring(&value, percentage, None, &self.svg_colors)
             }
-            ChartKind::Line => crate::svg_graph::line(&self.samples_sum, 100.0, &self.svg_colors),
+            ChartKind::Line => {
+                let grid = self.config.grid_visible().then(|| "100%".to_owned());
+                if canvas {
+                    return super::canvas_container(
+                        LineChart::new(&self.samples_sum, 100.0, self.config.colors()).grid(grid),
+                    );
+                }
+                crate::svg_graph::with_grid(
+                    crate::svg_graph::line(&self.samples_sum, 100.0, &self.svg_colors, size),
+                    100.0,
+                    false,
+                    grid.as_deref(),
+                    &self.svg_colors,
+                    size,
+                )
+            }
             ChartKind::StackedBars => {
+                if canvas {
+                    return super::canvas_container(BarChart::new(
+                        &self.core_loads,
+                        self.config.bar_width,
+                        self.config.bar_spacing,
+                        self.config.colors(),
+                    ));
+                }
                 StackedBarSvg::new(self.config.bar_width, height_hint, self.config.bar_spacing)
                     .svg(&self.core_loads, &self.svg_colors)
             }
+            ChartKind::Gauge => {
+                return super::gauge_container(
+                    &[self.latest_sample() / 100.0],
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    canvas,
+                );
+            }
+            ChartKind::Sparkline => {
+                return super::sparkline_container(
+                    &self.samples_sum,
+                    None,
+                    MAX_SAMPLES,
+                    100.0,
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    canvas,
+                );
+            }
             ChartKind::Heat => {
                 log::error!("Heat not supported!");
                 INVALID_IMG.to_string()
@@ -322,13 +360,8 @@ impl Sensor for Cpu {
             )));
         };
 
-        // A bit ugly and error prone, the Heat type is not supported here so bars takes its place
-        // in numbering for the dropdown
-        let selected: Option<usize> = if self.graph_kind() == ChartKind::StackedBars {
-            Some(2)
-        } else {
-            Some(self.graph_kind().into())
-        };
+        let selected = self.graph_options.index(self.graph_kind());
+        let graph_options = self.graph_options;
 
         let config = &self.config;
         let cpu_kind = self.graph_kind();
@@ -400,15 +433,22 @@ impl Sensor for Cpu {
                 .into(),
             );
         }
+        cpu_column.push(crate::template::settings_ui(&config.template, |t| {
+            Message::TemplateChanged(DeviceKind::Cpu, t)
+        }));
+        cpu_column.push(
+            settings::item(
+                fl!("show-grid"),
+                toggler(config.grid_visible())
+                    .on_toggle(|t| Message::ToggleGrid(DeviceKind::Cpu, t)),
+            )
+            .into(),
+        );
         cpu_column.push(
             row!(
                 widget::text::body(fl!("chart-type")),
-                widget::dropdown(&self.graph_options, selected, move |m| {
-                    let mut choice: ChartKind = m.into();
-                    if choice != ChartKind::Ring && choice != ChartKind::Line {
-                        choice = ChartKind::StackedBars
-                    };
-                    Message::SelectGraphType(DeviceKind::Cpu, choice)
+                widget::dropdown(graph_options.names(), selected, move |m| {
+                    Message::SelectGraphType(DeviceKind::Cpu, graph_options.kind(m))
                 })
                 .width(70),
                 widget::space::horizontal(),
@@ -442,10 +482,10 @@ impl Cpu {
         let mut value = String::with_capacity(6);
         value.push('0');
 
-        let graph_opts: Vec<&'static str> = if is_horizontal {
-            (*GRAPH_OPTIONS_RING_LINE_BARS).into()
+        let graph_options: &'static super::GraphOptions = if is_horizontal {
+            &GRAPH_OPTIONS_RING_LINE_BARS
         } else {
-            (*super::GRAPH_OPTIONS_RING_LINE).into()
+            &super::GRAPH_OPTIONS_RING_LINE
         };
 
         // Initialize CPU/Core structures
@@ -480,8 +520,9 @@ impl Cpu {
                 ),
                 MAX_SAMPLES,
             ),
-            graph_options: graph_opts.to_vec(),
+            graph_options,
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
             config: CpuConfig::default(),
         };
         cpu.set_colors(&ChartColors::default());
@@ -618,15 +659,16 @@ impl fmt::Display for Cpu {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let current_val = self.latest_sample();
 
-        if self.config.no_decimals {
-            write!(f, "{}%", current_val.round())
+        let value = if self.config.no_decimals {
+            crate::locale::format_number(current_val.round(), 0)
         } else if current_val < 10.0 {
-            write!(f, "{current_val:.2}%")
+            crate::locale::format_number(current_val, 2)
         } else if current_val < 100.0 {
-            write!(f, "{current_val:.1}%")
+            crate::locale::format_number(current_val, 1)
         } else {
-            write!(f, "{current_val}%")
-        }
+            crate::locale::format_number(current_val, 0)
+        };
+        write!(f, "{value}{}", crate::locale::unit_symbol("%"))
     }
 }
 
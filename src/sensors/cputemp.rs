@@ -1,9 +1,10 @@
 use crate::{
+    charts::{heat::HeatChart, line::LineChart, ring::RingChart},
     colorpicker::DemoGraph,
     config::{ChartColors, ChartKind, ColorVariant, CpuTempConfig, DeviceKind},
     fl,
     sensors::INVALID_IMG,
-    svg_graph::SvgColors,
+    svg_graph::{ChartSize, SvgColors},
 };
 use cosmic::{Element, iced::Alignment::Center, widget::Container};
 
@@ -31,7 +32,7 @@ use std::{
 use std::fs::read_dir;
 use std::io;
 
-use super::{CpuVariant, Sensor, TempUnit};
+use super::{CpuVariant, DisplayOptions, Sensor, TempUnit};
 
 const MAX_SAMPLES: usize = 21;
 
@@ -141,10 +142,11 @@ impl HwmonTemp {
 pub struct CpuTemp {
     hwmon_temp: Option<HwmonTemp>,
     pub samples: BoundedVecDeque<f64>,
-    graph_options: Vec<&'static str>,
+    graph_options: &'static super::GraphOptions,
     unit_options: Vec<&'static str>,
     /// colors cached so we don't need to convert to string every time
     svg_colors: SvgColors,
+    display: DisplayOptions,
     config: CpuTempConfig,
 }
 
@@ -160,11 +162,24 @@ impl DemoGraph for CpuTemp {
                 &std::collections::VecDeque::from(DEMO_SAMPLES),
                 100.0,
                 &self.svg_colors,
+                ChartSize::SQUARE,
             ),
             ChartKind::Heat => crate::svg_graph::heat(
                 &std::collections::VecDeque::from(DEMO_SAMPLES),
                 100,
                 &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            ChartKind::Gauge => {
+                crate::svg_graph::gauge(&[0.4], &self.svg_colors, ChartSize::SQUARE)
+            }
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                DEMO_SAMPLES.len(),
+                100.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
             ),
             ChartKind::StackedBars => {
                 log::error!("StackedBars not supported for CpuTemp");
@@ -184,9 +199,10 @@ impl DemoGraph for CpuTemp {
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
         match self.config.chart {
-            ChartKind::Line => (*super::COLOR_CHOICES_LINE).into(),
+            ChartKind::Line | ChartKind::Gauge => (*super::COLOR_CHOICES_LINE).into(),
             ChartKind::Ring => (*super::COLOR_CHOICES_RING).into(),
             ChartKind::Heat => (*super::COLOR_CHOICES_HEAT).into(),
+            ChartKind::Sparkline => (*super::COLOR_CHOICES_SPARKLINE).into(),
             ChartKind::StackedBars => panic!("StackedBars not supported for CpuTemp"),
         }
     }
@@ -201,9 +217,10 @@ impl DemoGraph for CpuTemp {
 }
 
 impl Sensor for CpuTemp {
-    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<CpuTempConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors.set_colors(cfg.colors());
         }
     }
@@ -213,7 +230,7 @@ impl Sensor for CpuTemp {
     }
 
     fn set_graph_kind(&mut self, kind: ChartKind) {
-        assert!(kind == ChartKind::Line || kind == ChartKind::Ring || kind == ChartKind::Heat);
+        assert!(self.graph_options.contains(kind));
         self.config.chart = kind;
     }
 
@@ -230,59 +247,21 @@ impl Sensor for CpuTemp {
 
     fn demo_graph(&self) -> Box<dyn DemoGraph> {
         let mut dmo = CpuTemp::default();
-        dmo.update_config(&self.config, 0);
+        dmo.update_config(&self.config, 0, self.display);
         Box::new(dmo)
     }
 
-    #[cfg(feature = "lyon_charts")]
-    fn chart(
-        &self,
-    ) -> cosmic::widget::Container<crate::app::Message, cosmic::Theme, cosmic::Renderer> {
-        let mut max: f64 = 100.0;
-        if let Some(hwmon) = &self.hwmon_temp {
-            max = hwmon.crit_temp;
-        }
-        match self.config.kind {
-            ChartKind::Ring => {
-                let latest = self.latest_sample();
-                let mut value = self.to_string();
-
-                // remove the °C/°F/°R/K unit if there's not enough space (assuming temp stays below 282°C = 1000°R)
-                while value.len() > 3 {
-                    let _ = value.pop();
-                }
-                chart_container!(crate::charts::ring::RingChart::new(
-                    latest as f32,
-                    &value,
-                    &self.config.colors,
-                ))
-            }
-            ChartKind::Line => chart_container!(crate::charts::line::LineChart::new(
-                MAX_SAMPLES,
-                &self.samples,
-                &VecDeque::new(),
-                Some(max),
-                &self.config.colors,
-            )),
-            ChartKind::Heat => chart_container!(crate::charts::heat::HeatChart::new(
-                MAX_SAMPLES,
-                &self.samples,
-                Some(max),
-                &self.config.colors,
-            )),
-        }
-    }
-
-    #[cfg(not(feature = "lyon_charts"))]
     fn chart(
         &'_ self,
-        _height_hint: u16,
-        _width_hint: u16,
+        height_hint: u16,
+        width_hint: u16,
     ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
         let mut max: f64 = 100.0;
         if let Some(hwmon) = &self.hwmon_temp {
             max = hwmon.crit_temp;
         }
+        let canvas = self.display.canvas;
         let svg = match self.config.chart {
             ChartKind::Ring => {
                 let latest = self.latest_sample();
@@ -298,25 +277,70 @@ impl Sensor for CpuTemp {
                     .round()
                     .clamp(0.0, max) as u8;
 
+                if canvas {
+                    return super::canvas_container(RingChart::new(
+                        &value,
+                        percentage,
+                        None,
+                        self.config.colors(),
+                    ));
+                }
                 crate::svg_graph::ring(&value, percentage, None, &self.svg_colors)
             }
             ChartKind::Line => {
-                if self.config.min_temp == 0.0 {
-                    crate::svg_graph::line(&self.samples, max, &self.svg_colors)
-                } else {
-                    let normalized =
-                        super::normalize_temps_dynamic(&self.samples, self.config.min_temp);
-                    crate::svg_graph::line(&normalized, max, &self.svg_colors)
+                let samples = super::temps_above_floor(&self.samples, self.config.min_temp);
+                let grid = self.config.grid_visible().then(|| {
+                    let top = super::temp_chart_top(&self.samples, self.config.min_temp, max);
+                    self.config.unit.label(top)
+                });
+                if canvas {
+                    return super::canvas_container(
+                        LineChart::new(&samples, max, self.config.colors()).grid(grid),
+                    );
                 }
+                crate::svg_graph::with_grid(
+                    crate::svg_graph::line(&samples, max, &self.svg_colors, size),
+                    max,
+                    false,
+                    grid.as_deref(),
+                    &self.svg_colors,
+                    size,
+                )
             }
             ChartKind::Heat => {
-                if self.config.min_temp == 0.0 {
-                    crate::svg_graph::heat(&self.samples, max as u64, &self.svg_colors)
-                } else {
-                    let normalized =
-                        super::normalize_temps_dynamic(&self.samples, self.config.min_temp);
-                    crate::svg_graph::heat(&normalized, max as u64, &self.svg_colors)
+                let samples = super::temps_above_floor(&self.samples, self.config.min_temp);
+                if canvas {
+                    return super::canvas_container(HeatChart::new(
+                        &samples,
+                        max as u64,
+                        self.config.colors(),
+                    ));
                 }
+                crate::svg_graph::heat(&samples, max as u64, &self.svg_colors, size)
+            }
+            ChartKind::Gauge => {
+                let fraction =
+                    (self.latest_sample() - self.config.min_temp) / (max - self.config.min_temp);
+                return super::gauge_container(
+                    &[fraction],
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    canvas,
+                );
+            }
+            ChartKind::Sparkline => {
+                let samples = super::temps_above_floor(&self.samples, self.config.min_temp);
+                return super::sparkline_container(
+                    &samples,
+                    None,
+                    MAX_SAMPLES,
+                    max,
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    canvas,
+                );
             }
             ChartKind::StackedBars => {
                 log::error!("StackedBars not supported for CpuTemp");
@@ -347,7 +371,8 @@ impl Sensor for CpuTemp {
             .align_x(Alignment::Center),
         ));
 
-        let selected_graph: Option<usize> = Some(self.graph_kind().into());
+        let selected_graph = self.graph_options.index(self.graph_kind());
+        let graph_options = self.graph_options;
         let selected_unit: Option<usize> = Some(self.config.unit.into());
 
         let config = &self.config;
@@ -396,10 +421,18 @@ impl Sensor for CpuTemp {
                         Message::SelectCpuTempUnit(m.into())
                     },)
                 ),
+                crate::template::settings_ui(&config.template, |t| {
+                    Message::TemplateChanged(DeviceKind::CpuTemp, t)
+                }),
+                settings::item(
+                    fl!("show-grid"),
+                    toggler(config.grid_visible())
+                        .on_toggle(|t| Message::ToggleGrid(DeviceKind::CpuTemp, t)),
+                ),
                 row!(
                     widget::text::body(fl!("chart-type")),
-                    widget::dropdown(&self.graph_options, selected_graph, |m| {
-                        Message::SelectGraphType(DeviceKind::CpuTemp, m.into())
+                    widget::dropdown(graph_options.names(), selected_graph, move |m| {
+                        Message::SelectGraphType(DeviceKind::CpuTemp, graph_options.kind(m))
                     },)
                     .width(70),
                     widget::space::horizontal(),
@@ -452,8 +485,9 @@ impl Default for CpuTemp {
         let mut cpu = CpuTemp {
             hwmon_temp: hwmon,
             samples: BoundedVecDeque::from_iter(std::iter::repeat_n(0.0, MAX_SAMPLES), MAX_SAMPLES),
-            graph_options: super::GRAPH_OPTIONS_RING_LINE_HEAT.to_vec(),
+            graph_options: &super::GRAPH_OPTIONS_RING_LINE_HEAT,
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
             unit_options: super::UNIT_OPTIONS.to_vec(),
             config: CpuTempConfig::default(),
         };
@@ -487,13 +521,7 @@ use std::fmt;
 
 impl fmt::Display for CpuTemp {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let current_val = self.latest_sample();
-        match self.config.unit {
-            TempUnit::Celsius => write!(f, "{}°C", current_val.trunc()),
-            TempUnit::Farenheit => write!(f, "{}°F", (current_val * 9.0 / 5.0 + 32.0).trunc()),
-            TempUnit::Kelvin => write!(f, "{}K", (current_val + 273.15).trunc()),
-            TempUnit::Rankine => write!(f, "{}°R", (current_val * 9.0 / 5.0 + 491.67).trunc()),
-        }
+        f.write_str(&self.config.unit.label(self.latest_sample()))
     }
 }
 
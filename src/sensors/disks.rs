@@ -3,10 +3,11 @@ use bounded_vec_deque::BoundedVecDeque;
 use sysinfo::{DiskRefreshKind, Disks as DisksInfo};
 
 use crate::{
+    charts::line::LineChart,
     colorpicker::DemoGraph,
     config::{ChartColors, ChartKind, ColorVariant, DeviceKind, DisksConfig},
     fl,
-    svg_graph::SvgColors,
+    svg_graph::{ChartSize, SvgColors},
 };
 
 use cosmic::{
@@ -28,13 +29,12 @@ use cosmic::{
 use crate::app::Message;
 use crate::config::DisksVariant;
 use std::any::Any;
+use std::collections::VecDeque;
 
-use super::Sensor;
+use super::{DisplayOptions, Sensor};
 
 const MAX_SAMPLES: usize = 30;
 const GRAPH_SAMPLES: usize = 21;
-const UNITS_SHORT: [&str; 5] = ["B", "K", "M", "G", "T"];
-const UNITS_LONG: [&str; 5] = ["B/s", "KB/s", "MB/s", "GB/s", "TB/s"];
 use std::sync::LazyLock;
 
 pub static COLOR_CHOICES_COMBINED: LazyLock<[(&'static str, ColorVariant); 4]> =
@@ -63,11 +63,8 @@ pub static COLOR_CHOICES_READ: LazyLock<[(&'static str, ColorVariant); 3]> = Laz
     ]
 });
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum UnitVariant {
-    Short,
-    Long,
-}
+use crate::units::Quantity;
+pub use crate::units::UnitVariant;
 
 #[derive(Debug)]
 pub struct Disks {
@@ -75,7 +72,11 @@ pub struct Disks {
     write: BoundedVecDeque<u64>,
     read: BoundedVecDeque<u64>,
     max_y: Option<u64>,
+    // Scale of the adaptive chart with a sticky peak
+    peak: u64,
     svg_colors: SvgColors,
+    display: DisplayOptions,
+    graph_options: &'static super::GraphOptions,
     config: DisksConfig,
     refresh_rate: u32,
 }
@@ -85,17 +86,39 @@ impl DemoGraph for Disks {
         let write = std::collections::VecDeque::from(DL_DEMO);
         let read = std::collections::VecDeque::from(UL_DEMO);
 
+        if matches!(self.config.chart, ChartKind::Gauge | ChartKind::Sparkline) {
+            return self.demo_rates(&write, &read);
+        }
+
         match self.config.variant {
-            DisksVariant::Combined => {
-                crate::svg_graph::double_line(&write, &read, GRAPH_SAMPLES, &self.svg_colors, None)
-            }
-            DisksVariant::Write => {
-                crate::svg_graph::line_adaptive(&write, GRAPH_SAMPLES, &self.svg_colors, None)
-            }
+            DisksVariant::Combined => crate::svg_graph::double_line(
+                &write,
+                &read,
+                GRAPH_SAMPLES,
+                &self.svg_colors,
+                None,
+                self.config.log_scale,
+                ChartSize::SQUARE,
+            ),
+            DisksVariant::Write => crate::svg_graph::line_adaptive(
+                &write,
+                GRAPH_SAMPLES,
+                &self.svg_colors,
+                None,
+                self.config.log_scale,
+                ChartSize::SQUARE,
+            ),
             DisksVariant::Read => {
                 let mut cols = self.svg_colors.clone();
                 cols.graph1 = cols.graph2.clone();
-                crate::svg_graph::line_adaptive(&read, GRAPH_SAMPLES, &cols, None)
+                crate::svg_graph::line_adaptive(
+                    &read,
+                    GRAPH_SAMPLES,
+                    &cols,
+                    None,
+                    self.config.log_scale,
+                    ChartSize::SQUARE,
+                )
             }
         }
     }
@@ -110,11 +133,18 @@ impl DemoGraph for Disks {
     }
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
-        match self.config.variant {
+        let mut choices: Vec<(&'static str, ColorVariant)> = match self.config.variant {
             DisksVariant::Combined => (*COLOR_CHOICES_COMBINED).into(),
             DisksVariant::Write => (*COLOR_CHOICES_WRITE).into(),
             DisksVariant::Read => (*COLOR_CHOICES_READ).into(),
+        };
+        // Sparklines have no background or frame
+        if self.config.chart == ChartKind::Sparkline {
+            choices.retain(|(_, variant)| {
+                matches!(variant, ColorVariant::Graph1 | ColorVariant::Graph2)
+            });
         }
+        choices
     }
 
     fn id(&self) -> Option<String> {
@@ -127,20 +157,42 @@ impl DemoGraph for Disks {
 }
 
 impl Sensor for Disks {
-    fn update_config(&mut self, config: &dyn Any, refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<DisksConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors.set_colors(cfg.colors());
             self.refresh_rate = refresh_rate;
+
+            if !cfg.sticky_peak {
+                self.peak = 0;
+            }
+
+            if cfg.adaptive {
+                self.max_y = None;
+            } else {
+                // Samples are in bytes, the bandwidth in the quantity the values are shown in
+                let unit = cfg.unit.unwrap_or(2).min(4); // ensure safe index
+                let bandwidth = cfg.bandwidth * self.display.units.multiplier(unit);
+                let bandwidth = if cfg.show_bits {
+                    bandwidth / 8
+                } else {
+                    bandwidth
+                };
+                let sec_per_tic = refresh_rate as f64 / 1000.0;
+                let new_y = bandwidth as f64 * sec_per_tic;
+                self.max_y = Some(new_y.round() as u64);
+            }
         }
     }
 
     fn graph_kind(&self) -> ChartKind {
-        ChartKind::Line
+        self.config.chart
     }
 
     fn set_graph_kind(&mut self, kind: ChartKind) {
-        assert!(kind == ChartKind::Line);
+        assert!(self.graph_options.contains(kind));
+        self.config.chart = kind;
     }
 
     /// Retrieve the amount of data transmitted since last update.
@@ -158,84 +210,114 @@ impl Sensor for Disks {
 
         self.write.push_back(wr);
         self.read.push_back(rd);
+
+        if self.config.sticky_peak {
+            let (samples, samples2) = self.shown_samples();
+            self.peak = super::decayed_peak(
+                self.peak,
+                samples,
+                samples2,
+                GRAPH_SAMPLES,
+                self.refresh_rate,
+            );
+        }
     }
 
     fn demo_graph(&self) -> Box<dyn DemoGraph> {
         let mut dmo = Disks::default();
-        dmo.update_config(&self.config, 0);
+        dmo.update_config(&self.config, 0, self.display);
         Box::new(dmo)
     }
 
-    #[cfg(feature = "lyon_charts")]
     fn chart(
-        &self,
-    ) -> cosmic::widget::Container<crate::app::Message, cosmic::Theme, cosmic::Renderer> {
-        //A bit awkward, but to maintain compatibility with the SVG charts
-        let mut colors = self.config.colors;
-        match self.config.variant {
-            DisksVariant::Combined => {
-                colors.color4 = self.config.colors.color2;
-                colors.color2 = self.config.colors.color4;
-                chart_container!(crate::charts::line::LineChart::new(
-                    GRAPH_SAMPLES,
-                    &self.write,
-                    &self.read,
-                    self.max_y,
-                    &colors,
-                ))
-            }
-            DisksVariant::Write => {
-                colors.color4 = self.config.colors.color2;
-                colors.color2 = self.config.colors.color4;
-                chart_container!(crate::charts::line::LineChart::new(
-                    GRAPH_SAMPLES,
-                    &self.write,
-                    &VecDeque::new(),
-                    self.max_y,
-                    &colors,
-                ))
-            }
-            DisksVariant::Read => {
-                //A bit awkward, but to maintain compatibility with the SVG charts
-                colors.color4 = self.config.colors.color3;
-                colors.color2 = self.config.colors.color4;
-                chart_container!(crate::charts::line::LineChart::new(
-                    GRAPH_SAMPLES,
-                    &self.read,
-                    &VecDeque::new(),
-                    self.max_y,
-                    &colors,
-                ))
+        &'_ self,
+        height_hint: u16,
+        width_hint: u16,
+    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
+        let max_y = self.scale_max();
+        let log_scale = self.config.log_scale;
+        if matches!(self.config.chart, ChartKind::Gauge | ChartKind::Sparkline) {
+            let mut colors = *self.config.colors();
+            let mut svg_colors = self.svg_colors.clone();
+            if self.config.variant == DisksVariant::Read {
+                colors.graph1 = colors.graph2;
+                svg_colors.graph1 = svg_colors.graph2.clone();
             }
+            let (samples, samples2) = self.shown_samples();
+            return super::rate_container(
+                self.config.chart,
+                samples,
+                samples2,
+                GRAPH_SAMPLES,
+                max_y,
+                &colors,
+                &svg_colors,
+                size,
+                self.display.canvas,
+            );
+        }
+        // Both renderers scale to the samples they show
+        let (samples, samples2) = self.shown_samples();
+        let top = max_y.unwrap_or_else(|| super::recent_max(samples, samples2, GRAPH_SAMPLES));
+
+        if self.display.canvas {
+            let colors = self.config.colors();
+            let chart = match self.config.variant {
+                DisksVariant::Combined => {
+                    LineChart::double(&self.write, &self.read, GRAPH_SAMPLES, colors, max_y)
+                }
+                DisksVariant::Write => {
+                    LineChart::adaptive(&self.write, GRAPH_SAMPLES, colors, max_y)
+                }
+                DisksVariant::Read => {
+                    let mut cols = *colors;
+                    cols.graph1 = cols.graph2;
+                    LineChart::adaptive(&self.read, GRAPH_SAMPLES, &cols, max_y)
+                }
+            };
+            return super::canvas_container(chart.log_scale(log_scale).grid(self.grid_label(top)));
         }
-    }
 
-    #[cfg(not(feature = "lyon_charts"))]
-    fn chart(
-        &'_ self,
-        _height_hint: u16,
-        _width_hint: u16,
-    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
         let svg = match self.config.variant {
             DisksVariant::Combined => crate::svg_graph::double_line(
                 &self.write,
                 &self.read,
                 GRAPH_SAMPLES,
                 &self.svg_colors,
-                self.max_y,
+                max_y,
+                log_scale,
+                size,
             ),
             DisksVariant::Write => crate::svg_graph::line_adaptive(
                 &self.write,
                 GRAPH_SAMPLES,
                 &self.svg_colors,
-                self.max_y,
+                max_y,
+                log_scale,
+                size,
             ),
             DisksVariant::Read => {
                 let mut cols = self.svg_colors.clone();
                 cols.graph1 = cols.graph2.clone();
-                crate::svg_graph::line_adaptive(&self.read, GRAPH_SAMPLES, &cols, self.max_y)
+                crate::svg_graph::line_adaptive(
+                    &self.read,
+                    GRAPH_SAMPLES,
+                    &cols,
+                    max_y,
+                    log_scale,
+                    size,
+                )
             }
         };
+        let svg = crate::svg_graph::with_grid(
+            svg,
+            top as f64,
+            log_scale,
+            self.grid_label(top).as_deref(),
+            &self.svg_colors,
+            size,
+        );
         super::svg_icon_container::<Message>(svg)
     }
 
@@ -251,6 +333,11 @@ impl Sensor for Disks {
         let rdrate = format!("R {}", &self.read_label(sample_rate_ms, UnitVariant::Long));
 
         let config = &self.config;
+        let unit_names = self.display.units.unit_names(if config.show_bits {
+            Quantity::Bits
+        } else {
+            Quantity::Bytes
+        });
         let k = self.config.variant;
 
         let mut rate = column!(
@@ -309,13 +396,90 @@ impl Sensor for Disks {
             )
             .into(),
         );
+        // The template covers the whole panel item, it is kept with the first config
+        if k != DisksVariant::Read {
+            disk_bandwidth_items.push(crate::template::settings_ui(&config.template, move |t| {
+                Message::TemplateChanged(DeviceKind::Disks(k), t)
+            }));
+        }
+        disk_bandwidth_items.push(
+            settings::item(
+                fl!("show-grid"),
+                widget::toggler(config.grid_visible())
+                    .on_toggle(move |t| Message::ToggleGrid(DeviceKind::Disks(k), t)),
+            )
+            .into(),
+        );
+        let graph_options = self.graph_options;
+        disk_bandwidth_items.push(
+            settings::item(
+                fl!("chart-type"),
+                widget::dropdown(
+                    graph_options.names(),
+                    graph_options.index(config.chart),
+                    move |m| Message::SelectGraphType(DeviceKind::Disks(k), graph_options.kind(m)),
+                )
+                .width(90),
+            )
+            .into(),
+        );
+        disk_bandwidth_items.push(
+            settings::item(
+                fl!("use-adaptive"),
+                row!(
+                    widget::checkbox(config.adaptive)
+                        .on_toggle(move |t| Message::ToggleAdaptiveDisks(k, t))
+                ),
+            )
+            .into(),
+        );
+
+        if config.adaptive {
+            disk_bandwidth_items.push(
+                settings::item(
+                    fl!("sticky-peak"),
+                    row!(
+                        widget::checkbox(config.sticky_peak)
+                            .on_toggle(move |t| Message::ToggleDisksStickyPeak(k, t))
+                    ),
+                )
+                .into(),
+            );
+        } else {
+            disk_bandwidth_items.push(
+                settings::item(
+                    fl!("disks-bandwidth"),
+                    row!(
+                        widget::text_input("", config.bandwidth.to_string())
+                            .width(100)
+                            .on_input(move |b| Message::TextInputDisksBandwidthChanged(k, b)),
+                        widget::dropdown(unit_names, config.unit, move |u| {
+                            Message::DisksSelectUnit(k, u)
+                        },)
+                        .width(50)
+                    ),
+                )
+                .into(),
+            );
+        }
+
+        disk_bandwidth_items.push(
+            settings::item(
+                fl!("log-scale"),
+                row!(
+                    widget::checkbox(config.log_scale)
+                        .on_toggle(move |t| Message::ToggleDisksLogScale(k, t))
+                ),
+            )
+            .into(),
+        );
 
         disk_bandwidth_items.push(
             row!(
                 widget::space::horizontal(),
                 widget::button::standard(fl!("change-colors")).on_press(Message::ColorPickerOpen(
                     DeviceKind::Disks(self.config.variant),
-                    ChartKind::Line,
+                    self.config.chart,
                     None
                 )),
                 widget::space::horizontal()
@@ -343,6 +507,67 @@ impl Sensor for Disks {
     }
 }
 
+impl Disks {
+    /// The rates the variant shows, the second one only when combined
+    fn shown_samples(&self) -> (&VecDeque<u64>, Option<&VecDeque<u64>>) {
+        match self.config.variant {
+            DisksVariant::Combined => (&*self.write, Some(&*self.read)),
+            DisksVariant::Write => (&*self.write, None),
+            DisksVariant::Read => (&*self.read, None),
+        }
+    }
+
+    /// Label for the top of a line chart scaled to `max`, as a rate per second
+    fn grid_label(&self, max: u64) -> Option<String> {
+        self.config.grid_visible().then(|| {
+            let per_second = max as f64 * 1000.0 / self.refresh_rate.max(1) as f64;
+            self.makestr(per_second as u64, UnitVariant::Short, self.config.show_bits)
+        })
+    }
+
+    /// The fixed bandwidth, or the sticky peak of the adaptive scale
+    fn scale_max(&self) -> Option<u64> {
+        // Until the first update there is no peak yet
+        let peak = Some(self.peak).filter(|&peak| self.config.sticky_peak && peak > 0);
+        self.max_y.or(peak)
+    }
+
+    /// Gauge and sparkline demos of the rates, in the colors of the variant
+    fn demo_rates(
+        &self,
+        write: &std::collections::VecDeque<u64>,
+        read: &std::collections::VecDeque<u64>,
+    ) -> String {
+        let mut cols = self.svg_colors.clone();
+        let (samples, samples2) = match self.config.variant {
+            DisksVariant::Combined => (write, Some(read)),
+            DisksVariant::Write => (write, None),
+            DisksVariant::Read => {
+                cols.graph1 = cols.graph2.clone();
+                (read, None)
+            }
+        };
+        let max = super::recent_max(samples, samples2, GRAPH_SAMPLES) as f64;
+        if self.config.chart == ChartKind::Gauge {
+            let latest =
+                |s: &std::collections::VecDeque<u64>| s.back().copied().unwrap_or(0) as f64 / max;
+            let mut fractions = vec![latest(samples)];
+            fractions.extend(samples2.map(latest));
+            return crate::svg_graph::gauge(&fractions, &cols, ChartSize::SQUARE);
+        }
+        let to_f64 = |s: &std::collections::VecDeque<u64>| s.iter().map(|&v| v as f64).collect();
+        let samples2: Option<std::collections::VecDeque<f64>> = samples2.map(to_f64);
+        crate::svg_graph::sparkline(
+            &to_f64(samples),
+            samples2.as_ref(),
+            GRAPH_SAMPLES,
+            max,
+            &cols,
+            ChartSize::SQUARE,
+        )
+    }
+}
+
 impl Default for Disks {
     fn default() -> Self {
         let disks = DisksInfo::new_with_refreshed_list();
@@ -351,7 +576,10 @@ impl Default for Disks {
             write: BoundedVecDeque::from_iter(std::iter::repeat_n(0, MAX_SAMPLES), MAX_SAMPLES),
             read: BoundedVecDeque::from_iter(std::iter::repeat_n(0, MAX_SAMPLES), MAX_SAMPLES),
             max_y: None,
+            peak: 0,
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
+            graph_options: &super::GRAPH_OPTIONS_LINE,
             config: DisksConfig::default(),
             refresh_rate: 1000,
         }
@@ -359,54 +587,15 @@ impl Default for Disks {
 }
 
 impl Disks {
-    fn makestr(val: u64, format: UnitVariant) -> String {
-        let mut formatted = String::with_capacity(20);
-
-        let mut value = val as f64;
-        let mut unit_index = 0;
-        let units = if format == UnitVariant::Short {
-            UNITS_SHORT
+    fn makestr(&self, val: u64, format: UnitVariant, show_bits: bool) -> String {
+        // Samples are in bytes
+        if show_bits {
+            self.display
+                .units
+                .rate(val.saturating_mul(8), Quantity::Bits, format)
         } else {
-            UNITS_LONG
-        };
-
-        // Find the appropriate unit
-        while value >= 999.0 && unit_index < units.len() - 1 {
-            value /= 1000.0;
-            unit_index += 1;
+            self.display.units.rate(val, Quantity::Bytes, format)
         }
-
-        // Format the number with varying precision, prevent the formatter from rounding up
-        let mut value_str = if value < 10.0 {
-            format!("{:.2}", (value * 100.0).trunc() / 100.0)
-        } else if value < 100.0 {
-            format!("{:.1}", (value * 10.0).trunc() / 10.0)
-        } else {
-            format!("{:.0}", value.trunc())
-        };
-
-        // This happens when value is something like 9.9543456789908765453456 and it's rounded up to 10.
-        if value_str.len() == 5 {
-            log::info!("Value: {value}. formatted: {value:.2}. string: {value_str}");
-            value_str.pop();
-        }
-
-        formatted.push_str(&value_str);
-
-        if format == UnitVariant::Long {
-            formatted.push(' ');
-        }
-
-        formatted.push_str(units[unit_index]);
-
-        if format == UnitVariant::Long {
-            let padding = 9usize.saturating_sub(formatted.len());
-            if padding > 0 {
-                formatted = " ".repeat(padding) + &formatted;
-            }
-        }
-
-        formatted
     }
 
     // If the sample rate doesn't match exactly one second (more or less),
@@ -434,13 +623,13 @@ impl Disks {
     // Get bytes per second
     pub fn write_label(&self, sample_interval_ms: u32, format: UnitVariant) -> String {
         let val = Disks::last_second_rate(&self.write, sample_interval_ms);
-        Disks::makestr(val, format)
+        self.makestr(val, format, self.config.show_bits)
     }
 
     // Get bytes per second
     pub fn read_label(&self, sample_interval_ms: u32, format: UnitVariant) -> String {
         let val = Disks::last_second_rate(&self.read, sample_interval_ms);
-        Disks::makestr(val, format)
+        self.makestr(val, format, self.config.show_bits)
     }
 }
 
@@ -6,44 +6,125 @@ use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::sync::LazyLock;
 
-use crate::sensors::gpu::GpuType;
+use crate::config::GpuTempSensor;
+use crate::sensors::gpu::amd_metrics::{self, GpuMetrics};
+use crate::sensors::gpu::fdinfo::ProcessSampler;
+use crate::sensors::gpu::pci_ids;
+use crate::sensors::gpu::{FanSpeed, GpuClocks, GpuProcess, GpuType};
 use crate::sensors::gpus::Gpu;
 //use log::{debug, warn};
 
+/// hwmon temperature inputs, found by their labels
+#[derive(Debug, Default)]
+struct TempInputs {
+    edge: Option<String>,
+    junction: Option<String>,
+    mem: Option<String>,
+}
+
 pub struct AmdGpu {
     name: String,
     id: String,
+    card: String,
     usage_path: String,
     vram_used_path: String,
+    gtt_used_path: String,
     power_status_path: String,
-    temp_input_path: Option<String>,
+    temp_inputs: TempInputs,
+    power_path: Option<String>,
+    metrics_path: String,
+    // Snapshot of gpu_metrics taken in update(), None if unavailable or powered down
+    metrics: Option<GpuMetrics>,
     vram_total: u64,
+    // Only set for APUs, see is_apu()
+    gtt_total: u64,
+    pci_slot: Option<String>,
+    // None if the PCI slot is unknown, fdinfo is keyed by it
+    processes: Option<ProcessSampler>,
     paused: bool,
 }
 
 impl AmdGpu {
     pub fn new(name: &str, card: &str, id: &str, vram_total: u64) -> Self {
         let base = format!("/sys/class/drm/{card}/device");
-        let temp_input_path = AmdGpu::find_temp_input_path(card);
+        let temp_inputs = AmdGpu::find_temp_inputs(card);
+        let power_path = AmdGpu::find_power_path(card);
+        let pci_slot = AmdGpu::get_pci_slot(card);
+        let gtt_total = if AmdGpu::is_apu(card, vram_total) {
+            Self::parse_u64_file(&format!("{base}/mem_info_gtt_total")).unwrap_or(0)
+        } else {
+            0
+        };
+        info!("AMD {card}: VRAM {vram_total}, APU GTT {gtt_total}");
         Self {
             name: name.to_string(),
             id: id.to_string(),
+            card: card.to_string(),
             usage_path: format!("{base}/gpu_busy_percent"),
             vram_used_path: format!("{base}/mem_info_vram_used"),
+            gtt_used_path: format!("{base}/mem_info_gtt_used"),
             power_status_path: format!("{base}/power/runtime_status"),
-            temp_input_path,
+            temp_inputs,
+            power_path,
+            metrics_path: format!("{base}/gpu_metrics"),
+            metrics: None,
             vram_total,
+            gtt_total,
+            processes: pci_slot
+                .as_ref()
+                .map(|slot| ProcessSampler::new(Path::new("/proc"), slot)),
+            pci_slot,
             paused: false,
         }
     }
 
+    // APUs report gpu_metrics v2 or later. Older kernels lack the table, there the carve-out
+    // of an APU is all CPU visible while a dedicated card has VRAM beyond its BAR. With a
+    // resizable BAR both are equal on a dedicated card too, so that alone isn't an APU.
+    fn is_apu(card: &str, vram_total: u64) -> bool {
+        let base = format!("/sys/class/drm/{card}/device");
+        if let Ok(data) = fs::read(format!("{base}/gpu_metrics"))
+            && let Ok(metrics) = amd_metrics::parse(&data)
+        {
+            return metrics.format_revision >= 2;
+        }
+        let visible = Self::parse_u64_file(&format!("{base}/mem_info_vis_vram_total"));
+        let apu = visible == Some(vram_total) && !Self::has_board_memory(&base);
+        debug!("AmdGpu::is_apu({card}): no gpu_metrics, visible VRAM {visible:?}, APU {apu}");
+        apu
+    }
+
+    // Dedicated cards report the vendor of their memory chips, APUs use system memory
+    fn has_board_memory(base: &str) -> bool {
+        Self::read_file_to_string(&format!("{base}/mem_info_vram_vendor"))
+            .is_ok_and(|vendor| !vendor.is_empty())
+    }
+
     fn powered_on(&self) -> bool {
         Self::read_file_to_string(&self.power_status_path).map_or(true, |s| s != "suspended")
     }
 
+    fn read_metrics(&self) -> Option<GpuMetrics> {
+        let data = fs::read(&self.metrics_path).ok()?;
+        match amd_metrics::parse(&data) {
+            Ok(metrics) => Some(metrics),
+            Err(e) => {
+                debug!("AmdGpu::read_metrics({}): {e}", self.name);
+                None
+            }
+        }
+    }
+
+    /// Value from the last gpu_metrics snapshot
+    fn metric(&self, field: impl FnOnce(&GpuMetrics) -> Option<u32>, what: &str) -> Result<u32> {
+        self.metrics
+            .as_ref()
+            .and_then(field)
+            .with_context(|| format!("{what} not in gpu_metrics"))
+    }
+
     fn parse_u32_file(path: &str) -> Option<u32> {
         Self::read_file_to_string(path).ok()?.parse().ok()
     }
@@ -85,20 +166,75 @@ impl AmdGpu {
         cards
     }
 
-    fn find_temp_input_path(card: &str) -> Option<String> {
-        log::info!("AMD find_temp_input_path({card})");
+    // Usually temp1 is "edge", temp2 "junction" and temp3 "mem", but go by the labels
+    fn find_temp_inputs(card: &str) -> TempInputs {
+        log::info!("AMD find_temp_inputs({card})");
+        let mut inputs = TempInputs::default();
+        let hwmon_base = format!("/sys/class/drm/{card}/device/hwmon");
+        let Ok(entries) = fs::read_dir(hwmon_base) else {
+            log::info!("    No hwmon directory.");
+            return inputs;
+        };
+
+        for entry in entries.flatten() {
+            for index in 1..=8 {
+                let input = entry.path().join(format!("temp{index}_input"));
+                if !input.exists() {
+                    continue;
+                }
+                let label =
+                    Self::read_file_to_string(entry.path().join(format!("temp{index}_label")))
+                        .unwrap_or_default();
+                let slot = match label.as_str() {
+                    "junction" => &mut inputs.junction,
+                    "mem" => &mut inputs.mem,
+                    // Unlabeled temp1 on older kernels is the edge sensor
+                    "edge" => &mut inputs.edge,
+                    "" if index == 1 => &mut inputs.edge,
+                    _ => continue,
+                };
+                if slot.is_none() {
+                    log::info!("    Found temperature file {input:?} ({label})");
+                    *slot = Some(input.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        if inputs.edge.is_none() {
+            log::info!("    Couldn't find an edge temperature input.");
+        }
+        inputs
+    }
+
+    fn read_temperature(path: &str) -> Result<u32> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read temperature from {path}"))?;
+
+        let temp_millidegrees: u32 = contents
+            .trim()
+            .parse()
+            .context("Failed to parse temperature value")?;
+
+        Ok(temp_millidegrees)
+    }
+
+    // power1_average on most cards, newer kernels only expose power1_input on some
+    fn find_power_path(card: &str) -> Option<String> {
+        log::info!("AMD find_power_path({card})");
         let hwmon_base = format!("/sys/class/drm/{card}/device/hwmon");
         let entries = fs::read_dir(hwmon_base).ok()?;
 
         for entry in entries.flatten() {
-            let path = entry.path().join("temp1_input");
-            if path.exists() {
-                log::info!("    Found temperature file {path:?}");
-                return Some(path.to_string_lossy().to_string());
+            for file in ["power1_average", "power1_input"] {
+                let path = entry.path().join(file);
+                if path.exists() {
+                    log::info!("    Found power file {path:?}");
+                    return Some(path.to_string_lossy().to_string());
+                }
             }
         }
 
-        log::info!("    Couldn't find temp1_input.");
+        log::info!("    Couldn't find power1_average.");
         None
     }
 
@@ -118,48 +254,7 @@ impl AmdGpu {
             })
     }
 
-    fn get_lspci_gpu_names() -> Vec<(String, String)> {
-        fn clean_gpu_name(model: &str) -> String {
-            let (_, truncated) = model.split_once("]:").unwrap_or((model, model));
-            let truncated = truncated.split("[1002:").next().unwrap_or(model);
-            truncated
-                .replace("Corporation", "")
-                .replace("[AMD/ATI]", "")
-                .replace("compatible controller", "")
-                .replace("controller", "")
-                .replace("VGA", "")
-                .replace("3D", "")
-                .replace("Display", "")
-                .replace(':', "")
-                .replace("  ", " ")
-                .replace('[', "(")
-                .replace(']', ")")
-                .trim()
-                .to_string()
-        }
-
-        let mut map = Vec::new();
-        let output = Command::new("lspci").arg("-nn").output();
-        let Ok(output) = output else {
-            return map;
-        };
-        let Ok(stdout) = String::from_utf8(output.stdout) else {
-            return map;
-        };
-
-        for line in stdout.lines() {
-            if (line.contains("VGA") || line.contains("Display") || line.contains("3D"))
-                && let Some((slot, rest)) = line.split_once(' ')
-            {
-                let model = rest.trim();
-                let name = clean_gpu_name(model);
-                map.push((slot.to_lowercase().to_string(), name));
-            }
-        }
-        map
-    }
-
-    fn get_gpu_name(card: &str, lspci_map: &Vec<(String, String)>) -> String {
+    fn get_gpu_name(card: &str) -> String {
         info!("Resolving GPU name for card: {card}");
 
         // Use static lookup table first, with nice names
@@ -175,16 +270,15 @@ impl AmdGpu {
             debug!("Failed to read device ID from path: {device_id_path}");
         }
 
-        // Fallback: Get PCI slot and look for it in the lspci list
-        if let Some(slot) = &AmdGpu::get_pci_slot(card) {
-            info!("Resolved PCI slot for card {card}: {slot:?}");
-            for (p, n) in lspci_map {
-                if slot.contains(p) {
-                    info!("Found name in lspci_map: {n}");
-                    return n.clone();
-                }
+        // Fallback: the system PCI id database
+        let device_path = PathBuf::from(format!("/sys/class/drm/{card}/device"));
+        if let Some(name) = pci_ids::lookup(&device_path).and_then(|n| n.gpu_name()) {
+            info!("Found name in pci.ids: {name}");
+            // Match the names in the static table
+            if name.starts_with("AMD") {
+                return name;
             }
-            debug!("No entry in lspci_map for slot: {slot}");
+            return format!("AMD {name}");
         }
 
         debug!("Falling back to unknown GPU name");
@@ -212,30 +306,27 @@ impl AmdGpu {
 
         let mut gpus = Vec::new();
 
-        let lspci_map = AmdGpu::get_lspci_gpu_names();
-        debug!("Available lspci_map entries:");
-        for (k, v) in &lspci_map {
-            debug!("  {k} -> {v}");
-        }
-
         let cards = AmdGpu::get_amd_cards();
 
         for card in cards {
             debug!("                    Found card {card}");
-            if let Some(vram_total) = AmdGpu::get_vram_total(&card) {
-                debug!("                    total vram {vram_total}");
-                if let Some(id) = AmdGpu::generate_gpu_id(&card) {
-                    debug!("                    id {id}");
-                    let name = AmdGpu::get_gpu_name(&card, &lspci_map);
-                    debug!("                    name {name}");
-                    gpus.push(Gpu::new(Box::new(AmdGpu::new(
-                        &name, &card, &id, vram_total,
-                    ))));
-                }
-            }
+            gpus.extend(AmdGpu::get_gpu(&card));
         }
         gpus
     }
+
+    /// Detect the GPU of a single card, such as one named in a hotplug event
+    pub fn get_gpu(card: &str) -> Option<Gpu> {
+        let vram_total = AmdGpu::get_vram_total(card)?;
+        debug!("                    total vram {vram_total}");
+        let id = AmdGpu::generate_gpu_id(card)?;
+        debug!("                    id {id}");
+        let name = AmdGpu::get_gpu_name(card);
+        debug!("                    name {name}");
+        Some(Gpu::new(Box::new(AmdGpu::new(
+            &name, card, &id, vram_total,
+        ))))
+    }
 }
 
 impl super::GpuIf for AmdGpu {
@@ -243,6 +334,15 @@ impl super::GpuIf for AmdGpu {
         GpuType::Amd
     }
 
+    fn update(&mut self) {
+        // Reading gpu_metrics would wake a suspended GPU
+        self.metrics = if self.is_active() && self.powered_on() {
+            self.read_metrics()
+        } else {
+            None
+        };
+    }
+
     fn restart(&mut self) {
         debug!("AmdGpu::restart({}).", self.name);
         self.paused = false;
@@ -257,6 +357,19 @@ impl super::GpuIf for AmdGpu {
         !self.paused
     }
 
+    fn is_suspended(&self) -> bool {
+        !self.powered_on()
+    }
+
+    // Card numbers are reused, so check it is still the same device
+    fn is_present(&self) -> bool {
+        AmdGpu::generate_gpu_id(&self.card).is_some_and(|id| id == self.id)
+    }
+
+    fn pdev(&self) -> Option<String> {
+        self.pci_slot.clone()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -275,40 +388,136 @@ impl super::GpuIf for AmdGpu {
             return Err(anyhow!("AMD device paused"));
         }
         if !self.powered_on() {
-            return Ok(0);
+            return Err(anyhow!("AMD device suspended"));
         }
-        Ok(Self::parse_u32_file(&self.usage_path).unwrap_or(0))
+        // Some APUs lack gpu_busy_percent, gpu_metrics still has the activity
+        Ok(Self::parse_u32_file(&self.usage_path)
+            .or_else(|| self.metrics.and_then(|m| m.gfx_activity))
+            .unwrap_or(0))
     }
 
     fn temperature(&self) -> Result<u32> {
         if !self.powered_on() {
-            return Ok(0);
+            return Err(anyhow!("AMD device suspended"));
         }
 
-        let path = self
-            .temp_input_path
-            .as_ref()
-            .context("Temperature path not found")?;
+        match &self.temp_inputs.edge {
+            Some(path) => Self::read_temperature(path),
+            None => self.metric(|m| m.temperature_edge, "Temperature"),
+        }
+    }
 
-        let contents = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read temperature from {path}"))?;
+    fn vram_used(&self) -> Result<u64> {
+        if !self.is_active() {
+            return Err(anyhow!("AMD device paused"));
+        }
+        if !self.powered_on() {
+            return Err(anyhow!("AMD device suspended"));
+        }
+        Ok(Self::parse_u64_file(&self.vram_used_path).unwrap_or(0))
+    }
 
-        let temp_millidegrees: u32 = contents
-            .trim()
-            .parse()
-            .context("Failed to parse temperature value")?;
+    fn gtt_total(&self) -> u64 {
+        self.gtt_total
+    }
 
-        Ok(temp_millidegrees)
+    fn gtt_used(&self) -> Result<u64> {
+        if !self.is_active() {
+            return Err(anyhow!("AMD device paused"));
+        }
+        if self.gtt_total == 0 {
+            return Err(anyhow!("GTT only reported for APUs"));
+        }
+        Self::parse_u64_file(&self.gtt_used_path).context("Failed to read GTT usage")
     }
 
-    fn vram_used(&self) -> Result<u64> {
+    fn power(&self) -> Result<u32> {
         if !self.is_active() {
             return Err(anyhow!("AMD device paused"));
         }
         if !self.powered_on() {
-            return Ok(0);
+            return Err(anyhow!("AMD device suspended"));
         }
-        Ok(Self::parse_u64_file(&self.vram_used_path).unwrap_or(0))
+        let Some(path) = self.power_path.as_ref() else {
+            return self.metric(|m| m.socket_power, "Socket power");
+        };
+
+        // Reported in microwatts
+        let micro_watts = Self::parse_u64_file(path)
+            .with_context(|| format!("Failed to read power from {path}"))?;
+        Ok((micro_watts / 1000) as u32)
+    }
+
+    fn memory_controller_usage(&self) -> Result<u32> {
+        self.metric(|m| m.umc_activity, "Memory controller activity")
+    }
+
+    fn media_usage(&self) -> Result<u32> {
+        self.metric(|m| m.mm_activity, "Media engine activity")
+    }
+
+    fn hotspot_temperature(&self) -> Result<u32> {
+        if !self.powered_on() {
+            return Err(anyhow!("AMD device suspended"));
+        }
+        match &self.temp_inputs.junction {
+            Some(path) => Self::read_temperature(path),
+            None => self.metric(|m| m.temperature_hotspot, "Hotspot temperature"),
+        }
+    }
+
+    fn memory_temperature(&self) -> Result<u32> {
+        if !self.powered_on() {
+            return Err(anyhow!("AMD device suspended"));
+        }
+        match &self.temp_inputs.mem {
+            Some(path) => Self::read_temperature(path),
+            None => self.metric(|m| m.temperature_mem, "Memory temperature"),
+        }
+    }
+
+    fn temperature_sensors(&self) -> Vec<GpuTempSensor> {
+        let metrics = self.read_metrics();
+        let mut sensors = vec![GpuTempSensor::Edge];
+        if self.temp_inputs.junction.is_some()
+            || metrics.is_some_and(|m| m.temperature_hotspot.is_some())
+        {
+            sensors.push(GpuTempSensor::Junction);
+        }
+        if self.temp_inputs.mem.is_some() || metrics.is_some_and(|m| m.temperature_mem.is_some()) {
+            sensors.push(GpuTempSensor::Memory);
+        }
+        if sensors.len() > 1 {
+            sensors.push(GpuTempSensor::Max);
+        }
+        sensors
+    }
+
+    fn clocks(&self) -> Result<GpuClocks> {
+        Ok(GpuClocks {
+            core: self.metric(|m| m.gfxclk, "Graphics clock")?,
+            memory: self.metric(|m| m.uclk, "Memory clock").unwrap_or(0),
+        })
+    }
+
+    fn fan_speed(&self) -> Result<FanSpeed> {
+        self.metric(|m| m.fan_rpm, "Fan speed")
+            .map(FanSpeed::Rpm)
+            .or_else(|_| {
+                self.metric(|m| m.fan_pwm, "Fan speed")
+                    .map(FanSpeed::Percent)
+            })
+    }
+
+    // amdgpu publishes per client usage in DRM fdinfo
+    fn processes(&mut self) -> Result<Vec<GpuProcess>> {
+        if self.paused {
+            return Err(anyhow!("AMD device paused"));
+        }
+        self.processes
+            .as_mut()
+            .map(ProcessSampler::sample)
+            .ok_or_else(|| anyhow!("No PCI slot for {}", self.name))
     }
 }
 
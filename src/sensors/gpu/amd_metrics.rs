@@ -0,0 +1,378 @@
+//! Parser for the binary `gpu_metrics` table of the amdgpu driver.
+//!
+//! The layouts follow `struct gpu_metrics_v*` in the kernel's kgd_pp_interface.h.
+//! Version 1 tables come from discrete GPUs, version 2 and 3 from APUs.
+
+use anyhow::{Result, anyhow, bail};
+
+const HEADER_SIZE: usize = 4;
+
+// Fields the SMU doesn't fill in are set to all ones
+const U16_NOT_AVAILABLE: u16 = 0xFFFF;
+const U32_NOT_AVAILABLE: u32 = 0xFFFF_FFFF;
+
+/// The values minimon uses from a `gpu_metrics` table, None if the layout lacks them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuMetrics {
+    pub format_revision: u8,
+    pub content_revision: u8,
+    /// Temperatures in millidegrees Celsius
+    pub temperature_edge: Option<u32>,
+    pub temperature_hotspot: Option<u32>,
+    pub temperature_mem: Option<u32>,
+    /// Activity in percent
+    pub gfx_activity: Option<u32>,
+    pub umc_activity: Option<u32>,
+    pub mm_activity: Option<u32>,
+    /// Socket power in milliwatts
+    pub socket_power: Option<u32>,
+    /// Clocks in MHz
+    pub gfxclk: Option<u32>,
+    pub uclk: Option<u32>,
+    pub throttle_status: Option<u32>,
+    pub fan_rpm: Option<u32>,
+    /// Fan duty cycle in percent, APUs report this instead of rpm
+    pub fan_pwm: Option<u32>,
+}
+
+/// Little endian field access that treats the "not available" patterns as None
+struct Table<'a>(&'a [u8]);
+
+impl Table<'_> {
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes = self.0.get(offset..offset + 2)?;
+        let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+        (value != U16_NOT_AVAILABLE).then_some(value)
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes = self.0.get(offset..offset + 4)?;
+        let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        (value != U32_NOT_AVAILABLE).then_some(value)
+    }
+
+    fn u16_as_u32(&self, offset: usize) -> Option<u32> {
+        self.u16(offset).map(u32::from)
+    }
+
+    fn percent(&self, offset: usize) -> Option<u32> {
+        self.u16(offset).map(|p| u32::from(p).min(100))
+    }
+
+    /// Whole degrees to millidegrees
+    fn degrees(&self, offset: usize) -> Option<u32> {
+        self.u16(offset).map(|t| u32::from(t) * 1000)
+    }
+
+    /// Centidegrees to millidegrees
+    fn centidegrees(&self, offset: usize) -> Option<u32> {
+        self.u16(offset).map(|t| u32::from(t) * 10)
+    }
+}
+
+/// Parse a `gpu_metrics` table as read from sysfs
+pub fn parse(data: &[u8]) -> Result<GpuMetrics> {
+    if data.len() < HEADER_SIZE {
+        bail!("gpu_metrics too short: {} bytes", data.len());
+    }
+
+    let structure_size = usize::from(u16::from_le_bytes([data[0], data[1]]));
+    let format_revision = data[2];
+    let content_revision = data[3];
+
+    if structure_size > data.len() {
+        bail!(
+            "gpu_metrics v{format_revision}.{content_revision} truncated: header says {structure_size} bytes, got {}",
+            data.len()
+        );
+    }
+
+    let table = Table(&data[..structure_size]);
+    let metrics = GpuMetrics {
+        format_revision,
+        content_revision,
+        ..Default::default()
+    };
+
+    match (format_revision, content_revision) {
+        (1, 0) => Ok(parse_v1_0(&table, metrics)),
+        (1, 1..=3) => Ok(parse_v1_1(&table, metrics)),
+        (2, 0) => Ok(parse_v2_0(&table, metrics)),
+        (2, _) => Ok(parse_v2_1(&table, metrics)),
+        (3, _) => Ok(parse_v3_0(&table, metrics)),
+        _ => Err(anyhow!(
+            "Unsupported gpu_metrics version {format_revision}.{content_revision}"
+        )),
+    }
+}
+
+// The first version has the timestamp before the temperatures
+fn parse_v1_0(table: &Table, metrics: GpuMetrics) -> GpuMetrics {
+    GpuMetrics {
+        temperature_edge: table.degrees(16),
+        temperature_hotspot: table.degrees(18),
+        temperature_mem: table.degrees(20),
+        gfx_activity: table.percent(28),
+        umc_activity: table.percent(30),
+        mm_activity: table.percent(32),
+        socket_power: table.u16(34).map(|w| u32::from(w) * 1000),
+        gfxclk: table.u16_as_u32(40),
+        uclk: table.u16_as_u32(44),
+        throttle_status: table.u32(68),
+        fan_rpm: table.u16_as_u32(72),
+        ..metrics
+    }
+}
+
+// v1.1 to v1.3 share the start of the table, v1.2 and v1.3 only append fields.
+// v1.4 and later (MI300) have another layout and aren't supported.
+fn parse_v1_1(table: &Table, metrics: GpuMetrics) -> GpuMetrics {
+    GpuMetrics {
+        temperature_edge: table.degrees(4),
+        temperature_hotspot: table.degrees(6),
+        temperature_mem: table.degrees(8),
+        gfx_activity: table.percent(16),
+        umc_activity: table.percent(18),
+        mm_activity: table.percent(20),
+        socket_power: table.u16(22).map(|w| u32::from(w) * 1000),
+        gfxclk: table.u16_as_u32(40),
+        uclk: table.u16_as_u32(44),
+        throttle_status: table.u32(68),
+        fan_rpm: table.u16_as_u32(72),
+        ..metrics
+    }
+}
+
+fn parse_v2_0(table: &Table, metrics: GpuMetrics) -> GpuMetrics {
+    GpuMetrics {
+        temperature_edge: table.centidegrees(16),
+        gfx_activity: table.percent(40),
+        mm_activity: table.percent(42),
+        socket_power: table.u16_as_u32(44),
+        gfxclk: table.u16_as_u32(68),
+        uclk: table.u16_as_u32(72),
+        throttle_status: table.u32(112),
+        fan_pwm: table.percent(116),
+        ..metrics
+    }
+}
+
+// v2.1 to v2.4 moved the timestamp behind the activity fields
+fn parse_v2_1(table: &Table, metrics: GpuMetrics) -> GpuMetrics {
+    GpuMetrics {
+        temperature_edge: table.centidegrees(4),
+        gfx_activity: table.percent(28),
+        mm_activity: table.percent(30),
+        socket_power: table.u16_as_u32(40),
+        gfxclk: table.u16_as_u32(64),
+        uclk: table.u16_as_u32(68),
+        throttle_status: table.u32(108),
+        fan_pwm: table.percent(112),
+        ..metrics
+    }
+}
+
+// The u64 timestamp is aligned to 104 and the u32 powers after average_ipu_power to 120.
+// average_sys_power and the two STAPM limits sit at 168-172, right before the clocks
+fn parse_v3_0(table: &Table, metrics: GpuMetrics) -> GpuMetrics {
+    GpuMetrics {
+        temperature_edge: table.centidegrees(4),
+        gfx_activity: table.percent(42),
+        mm_activity: table.percent(44),
+        socket_power: table.u32(112),
+        gfxclk: table.u16_as_u32(174),
+        uclk: table.u16_as_u32(186),
+        ..metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A table of `size` bytes with only the header filled in
+    fn table(size: u16, format_revision: u8, content_revision: u8) -> Vec<u8> {
+        let mut data = vec![0; usize::from(size)];
+        data[0..2].copy_from_slice(&size.to_le_bytes());
+        data[2] = format_revision;
+        data[3] = content_revision;
+        data
+    }
+
+    fn put_u16(data: &mut [u8], offset: usize, value: u16) {
+        data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    // Offsets are those of struct gpu_metrics_v1_0 in kgd_pp_interface.h
+    #[test]
+    fn v1_0() {
+        let mut data = table(80, 1, 0);
+        put_u16(&mut data, 16, 45); // temperature_edge
+        put_u16(&mut data, 18, 60); // temperature_hotspot
+        put_u16(&mut data, 20, 70); // temperature_mem
+        put_u16(&mut data, 28, 35); // average_gfx_activity
+        put_u16(&mut data, 30, 12); // average_umc_activity
+        put_u16(&mut data, 32, 5); // average_mm_activity
+        put_u16(&mut data, 34, 150); // average_socket_power
+        put_u16(&mut data, 40, 2100); // average_gfxclk_frequency
+        put_u16(&mut data, 44, 1000); // average_uclk_frequency
+        put_u32(&mut data, 68, 0x10); // throttle_status
+        put_u16(&mut data, 72, 1500); // current_fan_speed
+
+        let metrics = parse(&data).unwrap();
+        assert_eq!(metrics.format_revision, 1);
+        assert_eq!(metrics.content_revision, 0);
+        assert_eq!(metrics.temperature_edge, Some(45_000));
+        assert_eq!(metrics.temperature_hotspot, Some(60_000));
+        assert_eq!(metrics.temperature_mem, Some(70_000));
+        assert_eq!(metrics.gfx_activity, Some(35));
+        assert_eq!(metrics.umc_activity, Some(12));
+        assert_eq!(metrics.mm_activity, Some(5));
+        assert_eq!(metrics.socket_power, Some(150_000));
+        assert_eq!(metrics.gfxclk, Some(2100));
+        assert_eq!(metrics.uclk, Some(1000));
+        assert_eq!(metrics.throttle_status, Some(0x10));
+        assert_eq!(metrics.fan_rpm, Some(1500));
+        assert_eq!(metrics.fan_pwm, None);
+    }
+
+    // struct gpu_metrics_v1_1, shared by v1.2 and v1.3
+    #[test]
+    fn v1_1() {
+        for content_revision in 1..=3 {
+            let mut data = table(96, 1, content_revision);
+            put_u16(&mut data, 4, 50); // temperature_edge
+            put_u16(&mut data, 6, 65); // temperature_hotspot
+            put_u16(&mut data, 8, 72); // temperature_mem
+            put_u16(&mut data, 16, 99); // average_gfx_activity
+            put_u16(&mut data, 18, 40); // average_umc_activity
+            put_u16(&mut data, 20, 3); // average_mm_activity
+            put_u16(&mut data, 22, 220); // average_socket_power
+            put_u16(&mut data, 40, 2500); // average_gfxclk_frequency
+            put_u16(&mut data, 44, 1250); // average_uclk_frequency
+            put_u32(&mut data, 68, 0); // throttle_status
+            put_u16(&mut data, 72, 900); // current_fan_speed
+
+            let metrics = parse(&data).unwrap();
+            assert_eq!(metrics.temperature_edge, Some(50_000));
+            assert_eq!(metrics.temperature_hotspot, Some(65_000));
+            assert_eq!(metrics.temperature_mem, Some(72_000));
+            assert_eq!(metrics.gfx_activity, Some(99));
+            assert_eq!(metrics.umc_activity, Some(40));
+            assert_eq!(metrics.mm_activity, Some(3));
+            assert_eq!(metrics.socket_power, Some(220_000));
+            assert_eq!(metrics.gfxclk, Some(2500));
+            assert_eq!(metrics.uclk, Some(1250));
+            assert_eq!(metrics.throttle_status, Some(0));
+            assert_eq!(metrics.fan_rpm, Some(900));
+        }
+    }
+
+    // struct gpu_metrics_v2_0, temperatures in centidegrees and power in milliwatts
+    #[test]
+    fn v2_0() {
+        let mut data = table(120, 2, 0);
+        put_u16(&mut data, 16, 4525); // temperature_gfx
+        put_u16(&mut data, 40, 20); // average_gfx_activity
+        put_u16(&mut data, 42, 7); // average_mm_activity
+        put_u16(&mut data, 44, 8500); // average_socket_power
+        put_u16(&mut data, 68, 1800); // average_gfxclk_frequency
+        put_u16(&mut data, 72, 800); // average_uclk_frequency
+        put_u32(&mut data, 112, 0x4); // throttle_status
+        put_u16(&mut data, 116, 30); // fan_pwm
+
+        let metrics = parse(&data).unwrap();
+        assert_eq!(metrics.temperature_edge, Some(45_250));
+        assert_eq!(metrics.gfx_activity, Some(20));
+        assert_eq!(metrics.mm_activity, Some(7));
+        assert_eq!(metrics.socket_power, Some(8500));
+        assert_eq!(metrics.gfxclk, Some(1800));
+        assert_eq!(metrics.uclk, Some(800));
+        assert_eq!(metrics.throttle_status, Some(0x4));
+        assert_eq!(metrics.fan_pwm, Some(30));
+        assert_eq!(metrics.fan_rpm, None);
+    }
+
+    // struct gpu_metrics_v2_1, the timestamp moved behind the activity fields
+    #[test]
+    fn v2_1() {
+        let mut data = table(120, 2, 1);
+        put_u16(&mut data, 4, 5100); // temperature_gfx
+        put_u16(&mut data, 28, 64); // average_gfx_activity
+        put_u16(&mut data, 30, 2); // average_mm_activity
+        put_u16(&mut data, 40, 12000); // average_socket_power
+        put_u16(&mut data, 64, 2200); // average_gfxclk_frequency
+        put_u16(&mut data, 68, 1600); // average_uclk_frequency
+        put_u32(&mut data, 108, 0x1); // throttle_status
+        put_u16(&mut data, 112, 100); // fan_pwm
+
+        let metrics = parse(&data).unwrap();
+        assert_eq!(metrics.temperature_edge, Some(51_000));
+        assert_eq!(metrics.gfx_activity, Some(64));
+        assert_eq!(metrics.mm_activity, Some(2));
+        assert_eq!(metrics.socket_power, Some(12000));
+        assert_eq!(metrics.gfxclk, Some(2200));
+        assert_eq!(metrics.uclk, Some(1600));
+        assert_eq!(metrics.throttle_status, Some(0x1));
+        assert_eq!(metrics.fan_pwm, Some(100));
+    }
+
+    // struct gpu_metrics_v3_0, pins the offsets that depend on alignment padding
+    #[test]
+    fn v3_0() {
+        let mut data = table(264, 3, 0);
+        put_u16(&mut data, 4, 4800); // temperature_gfx
+        put_u16(&mut data, 42, 15); // average_gfx_activity
+        put_u16(&mut data, 44, 9); // average_vcn_activity
+        put_u32(&mut data, 112, 23_456); // average_socket_power
+        put_u16(&mut data, 116, 11); // average_ipu_power, before the padding
+        put_u16(&mut data, 168, 31); // average_sys_power
+        put_u16(&mut data, 170, 54); // stapm_power_limit
+        put_u16(&mut data, 172, 28); // current_stapm_power_limit
+        put_u16(&mut data, 174, 2900); // average_gfxclk_frequency
+        put_u16(&mut data, 176, 1400); // average_socclk_frequency
+        put_u16(&mut data, 184, 1200); // average_vclk_frequency
+        put_u16(&mut data, 186, 2800); // average_uclk_frequency
+        put_u16(&mut data, 188, 1); // average_mpipu_frequency
+
+        let metrics = parse(&data).unwrap();
+        assert_eq!(metrics.temperature_edge, Some(48_000));
+        assert_eq!(metrics.gfx_activity, Some(15));
+        assert_eq!(metrics.mm_activity, Some(9));
+        assert_eq!(metrics.socket_power, Some(23_456));
+        assert_eq!(metrics.gfxclk, Some(2900));
+        assert_eq!(metrics.uclk, Some(2800));
+        assert_eq!(metrics.throttle_status, None);
+    }
+
+    #[test]
+    fn not_available_fields() {
+        let mut data = table(96, 1, 1);
+        put_u16(&mut data, 4, U16_NOT_AVAILABLE);
+        put_u16(&mut data, 16, 250);
+        put_u32(&mut data, 68, U32_NOT_AVAILABLE);
+
+        let metrics = parse(&data).unwrap();
+        assert_eq!(metrics.temperature_edge, None);
+        assert_eq!(metrics.gfx_activity, Some(100));
+        assert_eq!(metrics.throttle_status, None);
+    }
+
+    #[test]
+    fn unsupported_and_truncated() {
+        for (format_revision, content_revision) in [(1, 4), (1, 5), (1, 6), (4, 0)] {
+            let data = table(96, format_revision, content_revision);
+            let error = parse(&data).unwrap_err().to_string();
+            assert!(error.starts_with("Unsupported"), "{error}");
+        }
+
+        let mut data = table(96, 1, 1);
+        data.truncate(50);
+        assert!(parse(&data).is_err());
+        assert!(parse(&[0, 0]).is_err());
+    }
+}
@@ -0,0 +1,781 @@
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::sensors::gpu::{GpuProcess, GpuType, pci_ids, process_name};
+use crate::sensors::gpus::Gpu;
+
+// Drivers with a dedicated backend, or without usable fdinfo
+const SKIP_DRIVERS: [&str; 2] = ["amdgpu", "nvidia"];
+
+// Several GPUs update in the same tick, share one walk of /proc between them
+const SCAN_MAX_AGE: Duration = Duration::from_millis(250);
+
+/// What one DRM client reports in its fdinfo
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrmClient {
+    /// Set by scan(), the fdinfo text doesn't name its process
+    pub pid: u32,
+    pub driver: String,
+    /// Busy time per engine in nanoseconds
+    pub engines: HashMap<String, u64>,
+    /// Busy and total GPU cycles per engine, xe counts these instead of busy time
+    pub cycles: HashMap<String, Cycles>,
+    /// Number of engines of a class, when the driver reports more than one
+    pub capacity: HashMap<String, u32>,
+    /// Resident memory in bytes, summed over regions
+    pub memory: u64,
+}
+
+/// `drm-cycles-<engine>` and `drm-total-cycles-<engine>`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cycles {
+    pub busy: u64,
+    pub total: u64,
+}
+
+/// Clients per device, keyed by drm-pdev and drm-client-id
+pub type DrmScan = HashMap<String, HashMap<u64, DrmClient>>;
+
+// Busy time and cycles of one client, kept between samples
+#[derive(Debug, Clone, Default)]
+struct Counters {
+    engines: HashMap<String, u64>,
+    cycles: HashMap<String, Cycles>,
+}
+
+type EngineTimes = HashMap<u64, Counters>;
+
+// What the clients of a device did since the previous sample, per engine
+#[derive(Debug, Default)]
+struct Busy {
+    ns: HashMap<String, u64>,
+    cycles: HashMap<String, Cycles>,
+}
+
+/// Parse the text of one `/proc/<pid>/fdinfo/<fd>` file.
+///
+/// Returns the device, client id and client data, or None if it isn't a DRM client.
+pub fn parse_fdinfo(text: &str) -> Option<(String, u64, DrmClient)> {
+    let mut pdev = None;
+    let mut client_id = None;
+    let mut client = DrmClient::default();
+    let mut resident = 0;
+    let mut legacy_memory = 0;
+
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        if key == "drm-pdev" {
+            pdev = Some(value.to_string());
+        } else if key == "drm-client-id" {
+            client_id = value.parse::<u64>().ok();
+        } else if key == "drm-driver" {
+            client.driver = value.to_string();
+        } else if let Some(engine) = key.strip_prefix("drm-engine-capacity-") {
+            if let Ok(capacity) = value.parse::<u32>() {
+                client.capacity.insert(engine.to_string(), capacity);
+            }
+        } else if let Some(engine) = key.strip_prefix("drm-engine-") {
+            if let Some(ns) = value.strip_suffix("ns").and_then(|v| v.trim().parse().ok()) {
+                client.engines.insert(engine.to_string(), ns);
+            }
+        } else if let Some(engine) = key.strip_prefix("drm-cycles-") {
+            if let Ok(busy) = value.parse() {
+                client.cycles.entry(engine.to_string()).or_default().busy = busy;
+            }
+        } else if let Some(engine) = key.strip_prefix("drm-total-cycles-") {
+            if let Ok(total) = value.parse() {
+                client.cycles.entry(engine.to_string()).or_default().total = total;
+            }
+        } else if key.starts_with("drm-resident-") {
+            resident += parse_memory(value).unwrap_or(0);
+        } else if key.starts_with("drm-memory-") {
+            // Older name for resident memory
+            legacy_memory += parse_memory(value).unwrap_or(0);
+        }
+    }
+
+    client.memory = if resident > 0 {
+        resident
+    } else {
+        legacy_memory
+    };
+    Some((pdev?, client_id?, client))
+}
+
+/// "1234 KiB" and the like to bytes
+fn parse_memory(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let amount: u64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next() {
+        None => 1,
+        Some("KiB") => 1024,
+        Some("MiB") => 1024 * 1024,
+        Some("GiB") => 1024 * 1024 * 1024,
+        Some(_) => return None,
+    };
+    Some(amount * multiplier)
+}
+
+/// Collect the DRM clients of all processes we may read below `proc_root`
+pub fn scan(proc_root: &Path) -> DrmScan {
+    let mut devices = DrmScan::new();
+
+    let Ok(processes) = fs::read_dir(proc_root) else {
+        return devices;
+    };
+
+    for process in processes.flatten() {
+        let Ok(pid) = process.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+
+        for fd in fds.flatten() {
+            // Only read fdinfo of open DRM device nodes, reading every fd is slow
+            let is_drm =
+                fs::read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/dri/"));
+            if !is_drm {
+                continue;
+            }
+
+            let fdinfo = process.path().join("fdinfo").join(fd.file_name());
+            if let Ok(text) = fs::read_to_string(fdinfo)
+                && let Some((pdev, client_id, mut client)) = parse_fdinfo(&text)
+            {
+                client.pid = pid;
+                // Duplicated fds share the client, so the id keeps it from counting twice
+                devices.entry(pdev).or_default().insert(client_id, client);
+            }
+        }
+    }
+
+    devices
+}
+
+type ScanCache = Option<(PathBuf, Instant, Arc<DrmScan>)>;
+
+static SCAN_CACHE: LazyLock<Mutex<ScanCache>> = LazyLock::new(|| Mutex::new(None));
+
+fn cached_scan(proc_root: &Path) -> Arc<DrmScan> {
+    let mut cache = SCAN_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((root, taken, scan)) = cache.as_ref()
+        && root == proc_root
+        && taken.elapsed() < SCAN_MAX_AGE
+    {
+        return scan.clone();
+    }
+
+    let result = Arc::new(scan(proc_root));
+    *cache = Some((proc_root.to_path_buf(), Instant::now(), result.clone()));
+    result
+}
+
+fn engine_times(clients: Option<&HashMap<u64, DrmClient>>) -> EngineTimes {
+    clients
+        .map(|c| {
+            c.iter()
+                .map(|(id, client)| {
+                    let counters = Counters {
+                        engines: client.engines.clone(),
+                        cycles: client.cycles.clone(),
+                    };
+                    (*id, counters)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Add the busy time of a client since `last`, clients that are new since then count from now on
+fn add_busy(id: u64, client: &DrmClient, last: &EngineTimes, busy: &mut Busy) {
+    let previous = last.get(&id);
+    for (engine, ns) in &client.engines {
+        let before = previous
+            .and_then(|p| p.engines.get(engine))
+            .copied()
+            .unwrap_or(*ns);
+        *busy.ns.entry(engine.clone()).or_default() += ns.saturating_sub(before);
+    }
+
+    // The total cycles are the GPU clock, the same for every client of the device
+    for (engine, cycles) in &client.cycles {
+        let before = previous
+            .and_then(|p| p.cycles.get(engine))
+            .copied()
+            .unwrap_or(*cycles);
+        let sum = busy.cycles.entry(engine.clone()).or_default();
+        sum.busy += cycles.busy.saturating_sub(before.busy);
+        sum.total = sum.total.max(cycles.total.saturating_sub(before.total));
+    }
+}
+
+// The busiest engine decides the usage, like intel_gpu_top does
+fn busiest_percent(busy: &Busy, clients: &HashMap<u64, DrmClient>, elapsed: Duration) -> u32 {
+    let capacity = |engine: &str| {
+        let capacity = clients
+            .values()
+            .find_map(|client| client.capacity.get(engine))
+            .copied()
+            .unwrap_or(1)
+            .max(1);
+        f64::from(capacity)
+    };
+
+    let elapsed = elapsed.as_nanos() as f64;
+    let by_time = busy
+        .ns
+        .iter()
+        .map(|(engine, ns)| *ns as f64 / elapsed / capacity(engine));
+    let by_cycles = busy
+        .cycles
+        .iter()
+        .filter(|(_, cycles)| cycles.total > 0)
+        .map(|(engine, cycles)| cycles.busy as f64 / cycles.total as f64 / capacity(engine));
+
+    by_time
+        .chain(by_cycles)
+        .map(|busy| (busy * 100.0).round() as u32)
+        .max()
+        .unwrap_or(0)
+        .min(100)
+}
+
+/// Per process usage of one device, measured between calls of `sample()`
+pub struct ProcessSampler {
+    proc_root: PathBuf,
+    pdev: String,
+    last: Option<(Instant, EngineTimes)>,
+}
+
+impl ProcessSampler {
+    pub fn new(proc_root: &Path, pdev: &str) -> Self {
+        ProcessSampler {
+            proc_root: proc_root.to_path_buf(),
+            pdev: pdev.to_string(),
+            last: None,
+        }
+    }
+
+    /// The processes with the device open, usage is None on the first call
+    pub fn sample(&mut self) -> Vec<GpuProcess> {
+        let scan = cached_scan(&self.proc_root);
+        let now = Instant::now();
+        let clients = scan.get(&self.pdev);
+
+        let mut processes: Vec<GpuProcess> = Vec::new();
+        if let Some(clients) = clients {
+            // A process can hold several clients, sum them per pid
+            let mut per_pid: HashMap<u32, (u64, Busy)> = HashMap::new();
+            for (id, client) in clients {
+                let (memory, busy) = per_pid.entry(client.pid).or_default();
+                *memory += client.memory;
+                if let Some((_, last)) = &self.last {
+                    add_busy(*id, client, last, busy);
+                }
+            }
+
+            processes = per_pid
+                .into_iter()
+                .map(|(pid, (memory, busy))| GpuProcess {
+                    pid,
+                    name: process_name(pid),
+                    usage: self.last.as_ref().map(|(last_time, _)| {
+                        busiest_percent(&busy, clients, now.duration_since(*last_time))
+                    }),
+                    memory: Some(memory),
+                })
+                .collect();
+        }
+
+        self.last = Some((now, engine_times(clients)));
+        processes
+    }
+}
+
+/// GPU backend for any driver that publishes DRM fdinfo, such as i915, xe, nouveau,
+/// panfrost, msm and v3d. Only processes readable by the user are counted.
+pub struct DrmGpu {
+    name: String,
+    pdev: String,
+    driver: String,
+    proc_root: PathBuf,
+    device: PathBuf,
+    temp_input_path: Option<PathBuf>,
+    memory_total: u64,
+    // Busy time per client and engine at the previous update
+    last: Option<(Instant, EngineTimes)>,
+    processes: ProcessSampler,
+    usage: u32,
+    memory: u64,
+    paused: bool,
+}
+
+impl DrmGpu {
+    pub fn new(name: &str, pdev: &str, driver: &str, proc_root: &Path, device: &Path) -> Self {
+        DrmGpu {
+            name: name.to_string(),
+            pdev: pdev.to_string(),
+            driver: driver.to_string(),
+            proc_root: proc_root.to_path_buf(),
+            device: device.to_path_buf(),
+            temp_input_path: Self::find_temp_input_path(device),
+            memory_total: Self::memory_total(proc_root),
+            last: None,
+            processes: ProcessSampler::new(proc_root, pdev),
+            usage: 0,
+            memory: 0,
+            paused: false,
+        }
+    }
+
+    pub fn get_gpus() -> Vec<Gpu> {
+        Self::get_gpus_in(Path::new("/sys/class/drm"), Path::new("/proc"))
+    }
+
+    /// Detect GPUs below `drm_root`, reading clients from `proc_root`
+    pub fn get_gpus_in(drm_root: &Path, proc_root: &Path) -> Vec<Gpu> {
+        debug!("DrmGpu::get_gpus_in({drm_root:?}).");
+        let mut gpus = Vec::new();
+
+        let Ok(entries) = fs::read_dir(drm_root) else {
+            return gpus;
+        };
+
+        let mut cards: Vec<_> = entries
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                // card0, not the connectors like card0-DP-1
+                name.starts_with("card") && !name.contains('-')
+            })
+            .collect();
+        cards.sort_by_key(|e| e.file_name());
+
+        for card in cards {
+            gpus.extend(Self::detect(&card.path().join("device"), proc_root));
+        }
+
+        gpus
+    }
+
+    /// Detect the GPU of a single card, such as one named in a hotplug event
+    pub fn get_gpu(card: &str) -> Option<Gpu> {
+        let device = Path::new("/sys/class/drm").join(card).join("device");
+        Self::detect(&device, Path::new("/proc"))
+    }
+
+    fn detect(device: &Path, proc_root: &Path) -> Option<Gpu> {
+        let driver = Self::driver(device)?;
+        if SKIP_DRIVERS.contains(&driver.as_str()) {
+            debug!("    {driver} has its own backend, skipping.");
+            return None;
+        }
+
+        let pdev = Self::read_pdev(device)?;
+        let name = Self::gpu_name(device, &driver);
+        info!("DRM fdinfo GPU {name} at {pdev}");
+        Some(Gpu::new(Box::new(DrmGpu::new(
+            &name, &pdev, &driver, proc_root, device,
+        ))))
+    }
+
+    /// Name of the kernel driver bound to a DRM card's device
+    pub fn driver(device: &Path) -> Option<String> {
+        fs::read_link(device.join("driver"))
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+    }
+
+    // drm-pdev is the PCI slot for PCI devices and the device name for platform devices
+    pub fn read_pdev(device: &Path) -> Option<String> {
+        if let Ok(uevent) = fs::read_to_string(device.join("uevent"))
+            && let Some(slot) = uevent
+                .lines()
+                .find_map(|line| line.strip_prefix("PCI_SLOT_NAME="))
+        {
+            return Some(slot.trim().to_lowercase());
+        }
+
+        fs::canonicalize(device)
+            .ok()?
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+    }
+
+    fn gpu_name(device: &Path, driver: &str) -> String {
+        // Platform devices have no PCI ids
+        if let Some(name) = pci_ids::lookup(device).and_then(|n| n.gpu_name()) {
+            return name;
+        }
+
+        let vendor = fs::read_to_string(device.join("vendor")).unwrap_or_default();
+        match vendor.trim() {
+            "0x8086" => format!("Intel Graphics ({driver})"),
+            "0x10de" => format!("NVIDIA ({driver})"),
+            _ => format!("GPU ({driver})"),
+        }
+    }
+
+    // Most of these GPUs allocate from system memory, so that is the best total we have
+    fn memory_total(proc_root: &Path) -> u64 {
+        fs::read_to_string(proc_root.join("meminfo"))
+            .ok()
+            .and_then(|meminfo| {
+                meminfo.lines().find_map(|line| {
+                    line.strip_prefix("MemTotal:")
+                        .and_then(|value| parse_memory(&value.trim().replace("kB", "KiB")))
+                })
+            })
+            .unwrap_or(0)
+    }
+
+    fn find_temp_input_path(device: &Path) -> Option<PathBuf> {
+        fs::read_dir(device.join("hwmon"))
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join("temp1_input"))
+            .find(|path| path.exists())
+    }
+}
+
+impl super::GpuIf for DrmGpu {
+    fn gpu_type(&self) -> GpuType {
+        GpuType::Drm
+    }
+
+    fn update(&mut self) {
+        if self.paused {
+            return;
+        }
+
+        let scan = cached_scan(&self.proc_root);
+        let now = Instant::now();
+        let clients = scan.get(&self.pdev);
+
+        self.memory = clients.map_or(0, |c| c.values().map(|client| client.memory).sum());
+
+        if let Some((last_time, last)) = &self.last
+            && let Some(clients) = clients
+        {
+            let mut busy = Busy::default();
+            for (id, client) in clients {
+                add_busy(*id, client, last, &mut busy);
+            }
+            self.usage = busiest_percent(&busy, clients, now.duration_since(*last_time));
+        } else {
+            self.usage = 0;
+        }
+
+        self.last = Some((now, engine_times(clients)));
+    }
+
+    fn restart(&mut self) {
+        debug!("DrmGpu::restart({}).", self.name);
+        self.paused = false;
+        self.last = None;
+    }
+
+    fn stop(&mut self) {
+        debug!("DrmGpu::stop({}).", self.name);
+        self.paused = true;
+        self.usage = 0;
+    }
+
+    fn is_active(&self) -> bool {
+        !self.paused
+    }
+
+    fn is_present(&self) -> bool {
+        Self::read_pdev(&self.device).is_some_and(|pdev| pdev == self.pdev)
+    }
+
+    fn pdev(&self) -> Option<String> {
+        Some(self.pdev.clone())
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn id(&self) -> String {
+        format!("drm-{}-{}", self.driver, self.pdev)
+    }
+
+    fn usage(&self) -> Result<u32> {
+        if self.paused {
+            return Err(anyhow!("DRM device paused"));
+        }
+        Ok(self.usage)
+    }
+
+    fn temperature(&self) -> Result<u32> {
+        let path = self
+            .temp_input_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("No temperature input for {}", self.name))?;
+        Ok(fs::read_to_string(path)?.trim().parse()?)
+    }
+
+    fn vram_total(&self) -> u64 {
+        self.memory_total
+    }
+
+    fn processes(&mut self) -> Result<Vec<GpuProcess>> {
+        if self.paused {
+            return Err(anyhow!("DRM device paused"));
+        }
+        Ok(self.processes.sample())
+    }
+
+    fn vram_used(&self) -> Result<u64> {
+        if self.paused {
+            return Err(anyhow!("DRM device paused"));
+        }
+        Ok(self.memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    const AMDGPU: &str = "pos:\t0
+flags:\t02100002
+mnt_id:\t24
+drm-driver:\tamdgpu
+drm-client-id:\t14
+drm-pdev:\t0000:03:00.0
+pasid:\t32771
+drm-memory-vram:\t15248 KiB
+drm-memory-gtt:\t 2048 KiB
+drm-memory-cpu:\t0 KiB
+drm-engine-gfx:\t1190851834 ns
+drm-engine-compute:\t0 ns
+drm-engine-dec:\t0 ns
+drm-engine-enc:\t0 ns
+";
+
+    const I915: &str = "pos:\t0
+flags:\t02100002
+drm-driver:\ti915
+drm-client-id:\t7
+drm-pdev:\t0000:00:02.0
+drm-total-system0:\t14016 KiB
+drm-resident-system0:\t14016 KiB
+drm-engine-render:\t25662044495 ns
+drm-engine-copy:\t0 ns
+drm-engine-video:\t512 ns
+drm-engine-capacity-video:\t2
+drm-engine-video-enhance:\t0 ns
+";
+
+    const XE: &str = "drm-driver:\txe
+drm-client-id:\t42
+drm-pdev:\t0000:00:02.0
+drm-total-system:\t4096 KiB
+drm-resident-system:\t4096 KiB
+drm-resident-vram0:\t1 MiB
+drm-cycles-rcs:\t28257900
+drm-total-cycles-rcs:\t7655183225
+drm-engine-capacity-vcs:\t2
+";
+
+    // A /proc with processes, their fds and fdinfo, removed when dropped
+    struct FakeProc(PathBuf);
+
+    impl FakeProc {
+        fn new(name: &str) -> Self {
+            let root =
+                std::env::temp_dir().join(format!("minimon-fdinfo-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            FakeProc(root)
+        }
+
+        fn add_fd(&self, pid: u32, fd: u32, target: &str, fdinfo: Option<&str>) {
+            let process = self.0.join(pid.to_string());
+            fs::create_dir_all(process.join("fd")).unwrap();
+            fs::create_dir_all(process.join("fdinfo")).unwrap();
+            symlink(target, process.join("fd").join(fd.to_string())).unwrap();
+            if let Some(fdinfo) = fdinfo {
+                fs::write(process.join("fdinfo").join(fd.to_string()), fdinfo).unwrap();
+            }
+        }
+    }
+
+    impl Drop for FakeProc {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn parse_amdgpu() {
+        let (pdev, id, client) = parse_fdinfo(AMDGPU).unwrap();
+        assert_eq!(pdev, "0000:03:00.0");
+        assert_eq!(id, 14);
+        assert_eq!(client.driver, "amdgpu");
+        assert_eq!(client.engines.get("gfx"), Some(&1_190_851_834));
+        assert_eq!(client.engines.get("compute"), Some(&0));
+        assert_eq!(client.engines.len(), 4);
+        // Only the older drm-memory- keys, summed over regions
+        assert_eq!(client.memory, (15248 + 2048) * 1024);
+    }
+
+    #[test]
+    fn parse_i915() {
+        let (pdev, id, client) = parse_fdinfo(I915).unwrap();
+        assert_eq!(pdev, "0000:00:02.0");
+        assert_eq!(id, 7);
+        assert_eq!(client.engines.get("render"), Some(&25_662_044_495));
+        assert_eq!(client.engines.get("video"), Some(&512));
+        assert_eq!(client.engines.get("video-enhance"), Some(&0));
+        assert_eq!(client.capacity.get("video"), Some(&2));
+        assert!(!client.engines.contains_key("capacity-video"));
+        // drm-total- is not resident
+        assert_eq!(client.memory, 14016 * 1024);
+    }
+
+    #[test]
+    fn parse_xe() {
+        let (pdev, id, client) = parse_fdinfo(XE).unwrap();
+        assert_eq!(pdev, "0000:00:02.0");
+        assert_eq!(id, 42);
+        assert_eq!(client.driver, "xe");
+        // xe reports cycles instead of busy time
+        assert!(client.engines.is_empty());
+        assert_eq!(
+            client.cycles.get("rcs"),
+            Some(&Cycles {
+                busy: 28_257_900,
+                total: 7_655_183_225,
+            })
+        );
+        assert_eq!(client.cycles.len(), 1);
+        assert_eq!(client.capacity.get("vcs"), Some(&2));
+        assert_eq!(client.memory, 4096 * 1024 + 1024 * 1024);
+    }
+
+    #[test]
+    fn parse_not_drm() {
+        assert_eq!(parse_fdinfo("pos:\t0\nflags:\t02\nmnt_id:\t24\n"), None);
+        // A client without a device can't be attributed
+        assert_eq!(parse_fdinfo("drm-driver:\ti915\ndrm-client-id:\t3\n"), None);
+    }
+
+    #[test]
+    fn scan_fake_proc() {
+        let proc = FakeProc::new("scan");
+        proc.add_fd(100, 3, "/dev/dri/renderD128", Some(AMDGPU));
+        // The same client through a duplicated fd
+        proc.add_fd(100, 7, "/dev/dri/renderD128", Some(AMDGPU));
+        proc.add_fd(100, 1, "/dev/null", Some("pos:\t0\n"));
+        proc.add_fd(200, 4, "/dev/dri/renderD129", Some(I915));
+        proc.add_fd(300, 5, "/dev/dri/card1", Some(XE));
+        // fdinfo can't be read, the process may have exited in between
+        proc.add_fd(400, 6, "/dev/dri/renderD128", None);
+        // fd can't be listed, as for processes of other users
+        fs::create_dir_all(proc.0.join("500")).unwrap();
+        fs::write(proc.0.join("500").join("fd"), "").unwrap();
+        fs::create_dir_all(proc.0.join("self")).unwrap();
+
+        let devices = scan(&proc.0);
+        assert_eq!(devices.len(), 2);
+
+        let amd = &devices["0000:03:00.0"];
+        assert_eq!(amd.len(), 1);
+        assert_eq!(amd[&14].pid, 100);
+
+        let intel = &devices["0000:00:02.0"];
+        assert_eq!(intel.len(), 2);
+        assert_eq!(intel[&7].pid, 200);
+        assert_eq!(intel[&42].pid, 300);
+    }
+
+    #[test]
+    fn scan_missing_root() {
+        assert!(scan(Path::new("/nonexistent/minimon/proc")).is_empty());
+    }
+
+    #[test]
+    fn cached_scan_reuses_recent_walk() {
+        let proc = FakeProc::new("cache");
+        proc.add_fd(100, 3, "/dev/dri/renderD128", Some(AMDGPU));
+
+        let first = cached_scan(&proc.0);
+        proc.add_fd(200, 4, "/dev/dri/renderD129", Some(I915));
+        let second = cached_scan(&proc.0);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(second.len(), 1);
+
+        std::thread::sleep(SCAN_MAX_AGE + Duration::from_millis(50));
+        let third = cached_scan(&proc.0);
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(third.len(), 2);
+    }
+
+    fn xe_client(rcs: (u64, u64), vcs: (u64, u64)) -> String {
+        format!(
+            "drm-driver:\txe
+drm-client-id:\t42
+drm-pdev:\t0000:00:02.0
+drm-cycles-rcs:\t{}
+drm-total-cycles-rcs:\t{}
+drm-cycles-vcs:\t{}
+drm-total-cycles-vcs:\t{}
+drm-engine-capacity-vcs:\t2
+",
+            rcs.0, rcs.1, vcs.0, vcs.1
+        )
+    }
+
+    #[test]
+    fn xe_usage_from_cycles() {
+        let sample = |rcs, vcs| {
+            let (_, id, client) = parse_fdinfo(&xe_client(rcs, vcs)).unwrap();
+            HashMap::from([(id, client)])
+        };
+        let first = sample((1_000, 50_000), (0, 50_000));
+        let second = sample((4_000, 60_000), (9_000, 60_000));
+
+        let last = engine_times(Some(&first));
+        let mut busy = Busy::default();
+        for (id, client) in &second {
+            add_busy(*id, client, &last, &mut busy);
+        }
+
+        // rcs busy 3000 of 10000 cycles, vcs 9000 of 10000 over its two engines
+        assert_eq!(
+            busy.cycles["rcs"],
+            Cycles {
+                busy: 3_000,
+                total: 10_000
+            }
+        );
+        assert_eq!(busy.cycles["vcs"].busy, 9_000);
+        // The wall clock doesn't matter for cycles
+        assert_eq!(busiest_percent(&busy, &second, Duration::from_secs(5)), 45);
+    }
+
+    #[test]
+    fn xe_client_counts_from_its_first_sample() {
+        let (_, id, client) = parse_fdinfo(&xe_client((5_000, 9_000), (0, 9_000))).unwrap();
+        let mut busy = Busy::default();
+        add_busy(id, &client, &EngineTimes::new(), &mut busy);
+        let clients = HashMap::from([(id, client)]);
+        assert_eq!(busiest_percent(&busy, &clients, Duration::from_secs(1)), 0);
+    }
+}
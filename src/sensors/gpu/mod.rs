@@ -1,19 +1,128 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+
+use crate::{config::GpuTempSensor, fl};
 
 pub mod amd;
+pub mod amd_metrics;
+pub mod fdinfo;
 pub mod intel;
 pub mod nvidia;
+pub mod pci_ids;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GpuType {
     Nvidia,
     Intel,
     Amd,
+    /// Any other driver, through DRM fdinfo
+    Drm,
+}
+
+/// Whether the GPU can be sampled, and if not why
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GpuPowerState {
+    #[default]
+    Active,
+    /// Runtime suspended by the kernel
+    Suspended,
+    /// Polling stopped, on battery or by the power profile
+    Paused,
+    /// Reading the GPU failed
+    Error,
+}
+
+impl std::fmt::Display for GpuPowerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            GpuPowerState::Active => fl!("gpu-state-active"),
+            GpuPowerState::Suspended => fl!("gpu-state-suspended"),
+            GpuPowerState::Paused => fl!("gpu-state-paused"),
+            GpuPowerState::Error => fl!("gpu-state-error"),
+        };
+        write!(f, "{state}")
+    }
+}
+
+/// Why the GPU runs below its maximum clocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleReason {
+    Idle,
+    ApplicationClocks,
+    PowerCap,
+    Thermal,
+    HardwareSlowdown,
+    SyncBoost,
+    DisplayClock,
+}
+
+impl std::fmt::Display for ThrottleReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            ThrottleReason::Idle => fl!("throttle-idle"),
+            ThrottleReason::ApplicationClocks => fl!("throttle-app-clocks"),
+            ThrottleReason::PowerCap => fl!("throttle-power-cap"),
+            ThrottleReason::Thermal => fl!("throttle-thermal"),
+            ThrottleReason::HardwareSlowdown => fl!("throttle-hw-slowdown"),
+            ThrottleReason::SyncBoost => fl!("throttle-sync-boost"),
+            ThrottleReason::DisplayClock => fl!("throttle-display-clock"),
+        };
+        write!(f, "{reason}")
+    }
+}
+
+/// Current clocks in MHz
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuClocks {
+    pub core: u32,
+    pub memory: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FanSpeed {
+    Rpm(u32),
+    Percent(u32),
+}
+
+impl std::fmt::Display for FanSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FanSpeed::Rpm(rpm) => write!(f, "{rpm} RPM"),
+            FanSpeed::Percent(percent) => write!(f, "{percent}%"),
+        }
+    }
+}
+
+/// PCIe traffic in bytes per second
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PcieThroughput {
+    pub tx: u64,
+    pub rx: u64,
+}
+
+/// A process with the GPU open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    /// Busiest engine in percent
+    pub usage: Option<u32>,
+    /// Memory in bytes
+    pub memory: Option<u64>,
+}
+
+/// The command name of a process, or its pid if it has already exited
+pub fn process_name(pid: u32) -> String {
+    std::fs::read_to_string(format!("/proc/{pid}/comm"))
+        .map(|comm| comm.trim().to_string())
+        .unwrap_or_else(|_| pid.to_string())
 }
 
 pub trait GpuIf {
     fn gpu_type(&self) -> GpuType;
 
+    // Called once per refresh before the values are read, for backends that cache a snapshot
+    fn update(&mut self) {}
+
     fn name(&self) -> String;
     fn id(&self) -> String;
     fn usage(&self) -> Result<u32>;
@@ -23,10 +132,104 @@ pub trait GpuIf {
     fn vram_total(&self) -> u64;
     fn vram_used(&self) -> Result<u64>;
 
+    // GTT, system memory mapped by the GPU. Only reported for APUs where it holds most graphics memory
+    fn gtt_total(&self) -> u64 {
+        0
+    }
+    fn gtt_used(&self) -> Result<u64> {
+        Err(anyhow!("GTT not supported"))
+    }
+
+    // Board power draw in milliwatts, not all backends can report it
+    fn power(&self) -> Result<u32> {
+        Err(anyhow!("Power draw not supported"))
+    }
+
+    // Extended metrics, optional for backends. Percentages are 0-100.
+    fn memory_controller_usage(&self) -> Result<u32> {
+        Err(anyhow!("Memory controller usage not supported"))
+    }
+    fn encoder_usage(&self) -> Result<u32> {
+        Err(anyhow!("Encoder usage not supported"))
+    }
+    fn decoder_usage(&self) -> Result<u32> {
+        Err(anyhow!("Decoder usage not supported"))
+    }
+    // Combined activity of the media engines, for GPUs that don't split encode and decode
+    fn media_usage(&self) -> Result<u32> {
+        Err(anyhow!("Media engine usage not supported"))
+    }
+    // Temps in millidegrees Celsius
+    fn hotspot_temperature(&self) -> Result<u32> {
+        Err(anyhow!("Hotspot temperature not supported"))
+    }
+    fn memory_temperature(&self) -> Result<u32> {
+        Err(anyhow!("Memory temperature not supported"))
+    }
+    // Sensors the backend can read, offered as choices in the settings
+    fn temperature_sensors(&self) -> Vec<GpuTempSensor> {
+        vec![GpuTempSensor::Edge]
+    }
+    fn sensor_temperature(&self, sensor: GpuTempSensor) -> Result<u32> {
+        match sensor {
+            GpuTempSensor::Edge => self.temperature(),
+            GpuTempSensor::Junction => self.hotspot_temperature(),
+            GpuTempSensor::Memory => self.memory_temperature(),
+            GpuTempSensor::Max => [
+                self.temperature(),
+                self.hotspot_temperature(),
+                self.memory_temperature(),
+            ]
+            .into_iter()
+            .flatten()
+            .max()
+            .ok_or_else(|| anyhow!("No temperature available")),
+        }
+    }
+    fn fan_speed(&self) -> Result<FanSpeed> {
+        Err(anyhow!("Fan speed not supported"))
+    }
+    fn pcie_throughput(&self) -> Result<PcieThroughput> {
+        Err(anyhow!("PCIe throughput not supported"))
+    }
+    fn clocks(&self) -> Result<GpuClocks> {
+        Err(anyhow!("Clocks not supported"))
+    }
+    fn throttle_reasons(&self) -> Result<Vec<ThrottleReason>> {
+        Err(anyhow!("Throttle reasons not supported"))
+    }
+
+    // Per process usage, takes &mut self since usage is measured between calls
+    fn processes(&mut self) -> Result<Vec<GpuProcess>> {
+        Err(anyhow!("Process list not supported"))
+    }
+
     // Stop polling, to allow it to sleep
     fn stop(&mut self);
     // Resume active polling
     fn restart(&mut self);
     // Stopped or active for polling?
     fn is_active(&self) -> bool;
+    // Runtime suspended by the kernel, sampling would wake it
+    fn is_suspended(&self) -> bool {
+        false
+    }
+    // Errors are only known after a read, so the default never reports them
+    fn power_state(&self) -> GpuPowerState {
+        if !self.is_active() {
+            GpuPowerState::Paused
+        } else if self.is_suspended() {
+            GpuPowerState::Suspended
+        } else {
+            GpuPowerState::Active
+        }
+    }
+    // Still in the system, checked after hotplug events
+    fn is_present(&self) -> bool {
+        true
+    }
+    // PCI slot, or device name for platform devices, the same key as drm-pdev in fdinfo
+    fn pdev(&self) -> Option<String> {
+        None
+    }
 }
@@ -1,10 +1,22 @@
 use anyhow::{Context, Result, anyhow};
 use log::{debug, info, warn};
-use nvml_wrapper::{Device, Nvml, error::NvmlError};
+use nvml_wrapper::{
+    Device, Nvml,
+    bitmasks::device::ThrottleReasons,
+    enum_wrappers::device::{Clock, PcieUtilCounter},
+    enums::device::UsedGpuMemory,
+    error::NvmlError,
+};
 
-use std::sync::OnceLock;
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
-use crate::sensors::{gpu::GpuType, gpus::Gpu};
+use crate::sensors::{
+    gpu::{GpuClocks, GpuProcess, GpuType, PcieThroughput, ThrottleReason, process_name},
+    gpus::Gpu,
+};
 
 static NVML: OnceLock<Nvml> = OnceLock::new();
 
@@ -36,18 +48,28 @@ pub struct NvidiaGpu<'a> {
     pub uuid: String,
     vram_total: u64,
     device: Option<Device<'a>>,
+    // Timestamp of the newest process utilization sample seen, in µs
+    last_process_sample: u64,
+    // sysfs directory of the PCI device, None if the bus id is unknown
+    sysfs_path: Option<PathBuf>,
 }
 
 impl NvidiaGpu<'_> {
     pub fn new(index: u32, name: String, uuid: String) -> Self {
         let mut device = None;
         let mut vram = 0;
+        let mut sysfs_path = None;
 
         if let Ok(nvml) = nvml()
             && let Ok(dev) = nvml.device_by_index(index)
             && let Ok(mem) = dev.memory_info()
         {
             vram = mem.total;
+            sysfs_path = dev
+                .pci_info()
+                .ok()
+                .and_then(|pci| Self::sysfs_slot(&pci.bus_id))
+                .map(|slot| Path::new("/sys/bus/pci/devices").join(slot));
             device = Some(dev);
         }
 
@@ -57,8 +79,17 @@ impl NvidiaGpu<'_> {
             uuid,
             vram_total: vram,
             device,
+            last_process_sample: 0,
+            sysfs_path,
         }
     }
+
+    // NVML has an 8 digit domain, "00000000:01:00.0", sysfs uses 4, "0000:01:00.0"
+    fn sysfs_slot(bus_id: &str) -> Option<String> {
+        let (domain, rest) = bus_id.split_once(':')?;
+        let domain = u32::from_str_radix(domain, 16).ok()?;
+        Some(format!("{domain:04x}:{}", rest.to_lowercase()))
+    }
 }
 
 impl super::GpuIf for NvidiaGpu<'_> {
@@ -86,6 +117,26 @@ impl super::GpuIf for NvidiaGpu<'_> {
         self.device.is_some()
     }
 
+    fn is_suspended(&self) -> bool {
+        self.runtime_suspended()
+    }
+
+    // Called on every DRM hotplug event, so avoid NVML when the PCI slot is known,
+    // device_by_uuid() wakes a runtime suspended GPU
+    fn is_present(&self) -> bool {
+        match &self.sysfs_path {
+            Some(path) => path.exists(),
+            None => nvml().is_ok_and(|nvml| nvml.device_by_uuid(self.uuid.as_str()).is_ok()),
+        }
+    }
+
+    fn pdev(&self) -> Option<String> {
+        self.sysfs_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|slot| slot.to_string_lossy().to_string())
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -120,6 +171,121 @@ impl super::GpuIf for NvidiaGpu<'_> {
             Ok(mem.used)
         })
     }
+
+    fn power(&self) -> Result<u32> {
+        self.with_device(|device_ref| Ok(device_ref.power_usage()?))
+    }
+
+    fn memory_controller_usage(&self) -> Result<u32> {
+        self.with_device(|device_ref| Ok(device_ref.utilization_rates()?.memory))
+    }
+
+    fn encoder_usage(&self) -> Result<u32> {
+        self.with_device(|device_ref| Ok(device_ref.encoder_utilization()?.utilization))
+    }
+
+    fn decoder_usage(&self) -> Result<u32> {
+        self.with_device(|device_ref| Ok(device_ref.decoder_utilization()?.utilization))
+    }
+
+    fn pcie_throughput(&self) -> Result<PcieThroughput> {
+        // NVML reports KB/s
+        self.with_device(|device_ref| {
+            Ok(PcieThroughput {
+                tx: u64::from(device_ref.pcie_throughput(PcieUtilCounter::Send)?) * 1024,
+                rx: u64::from(device_ref.pcie_throughput(PcieUtilCounter::Receive)?) * 1024,
+            })
+        })
+    }
+
+    fn clocks(&self) -> Result<GpuClocks> {
+        self.with_device(|device_ref| {
+            Ok(GpuClocks {
+                core: device_ref.clock_info(Clock::SM)?,
+                memory: device_ref.clock_info(Clock::Memory)?,
+            })
+        })
+    }
+
+    fn throttle_reasons(&self) -> Result<Vec<ThrottleReason>> {
+        self.with_device(|device_ref| {
+            let reasons = device_ref.current_throttle_reasons()?;
+            Ok([
+                (ThrottleReasons::GPU_IDLE, ThrottleReason::Idle),
+                (
+                    ThrottleReasons::APPLICATIONS_CLOCKS_SETTING,
+                    ThrottleReason::ApplicationClocks,
+                ),
+                (ThrottleReasons::SW_POWER_CAP, ThrottleReason::PowerCap),
+                (
+                    ThrottleReasons::HW_SLOWDOWN,
+                    ThrottleReason::HardwareSlowdown,
+                ),
+                (ThrottleReasons::SYNC_BOOST, ThrottleReason::SyncBoost),
+                (
+                    ThrottleReasons::SW_THERMAL_SLOWDOWN,
+                    ThrottleReason::Thermal,
+                ),
+                (
+                    ThrottleReasons::HW_THERMAL_SLOWDOWN,
+                    ThrottleReason::Thermal,
+                ),
+                (
+                    ThrottleReasons::HW_POWER_BRAKE_SLOWDOWN,
+                    ThrottleReason::HardwareSlowdown,
+                ),
+                (
+                    ThrottleReasons::DISPLAY_CLOCK_SETTING,
+                    ThrottleReason::DisplayClock,
+                ),
+            ]
+            .into_iter()
+            .filter(|(flag, _)| reasons.contains(*flag))
+            .fold(Vec::new(), |mut list, (_, reason)| {
+                if !list.contains(&reason) {
+                    list.push(reason);
+                }
+                list
+            }))
+        })
+    }
+
+    fn processes(&mut self) -> Result<Vec<GpuProcess>> {
+        let last_seen = self.last_process_sample;
+        let (processes, newest) = self.with_device(|device_ref| {
+            let running = device_ref.running_graphics_processes()?;
+            // Only samples newer than last_seen, fails when there are none
+            let samples = device_ref
+                .process_utilization_stats(last_seen)
+                .unwrap_or_default();
+            let newest = samples
+                .iter()
+                .map(|sample| sample.timestamp)
+                .max()
+                .unwrap_or(last_seen);
+
+            let processes = running
+                .into_iter()
+                .map(|process| GpuProcess {
+                    pid: process.pid,
+                    name: process_name(process.pid),
+                    usage: samples
+                        .iter()
+                        .filter(|sample| sample.pid == process.pid)
+                        .map(|sample| sample.sm_util)
+                        .max(),
+                    memory: match process.used_gpu_memory {
+                        UsedGpuMemory::Used(bytes) => Some(bytes),
+                        UsedGpuMemory::Unavailable => None,
+                    },
+                })
+                .collect();
+            Ok((processes, newest))
+        })?;
+
+        self.last_process_sample = newest;
+        Ok(processes)
+    }
 }
 
 impl NvidiaGpu<'_> {
@@ -145,6 +311,16 @@ impl NvidiaGpu<'_> {
         v
     }
 
+    /// Detect the GPU in one PCI slot, leaving any other NVIDIA GPU alone
+    pub fn get_gpu_at(slot: &str) -> Option<Gpu> {
+        let device = nvml().ok()?.device_by_pci_bus_id(slot).ok()?;
+        let index = device.index().ok()?;
+        let name = device.name().ok()?;
+        let uuid = device.uuid().ok()?;
+
+        Some(Gpu::new(Box::new(NvidiaGpu::new(index, name, uuid))))
+    }
+
     pub fn uuid(idx: u32) -> Result<String> {
         nvml()
             .context("unable to establish NVML connection")
@@ -169,10 +345,22 @@ impl NvidiaGpu<'_> {
             .and_then(|nvml| nvml.device_count().context("failed to get GPU count"))
     }
 
+    // Reading sysfs doesn't wake the GPU, unlike NVML
+    fn runtime_suspended(&self) -> bool {
+        self.sysfs_path.as_ref().is_some_and(|path| {
+            std::fs::read_to_string(path.join("power/runtime_status"))
+                .is_ok_and(|status| status.trim() == "suspended")
+        })
+    }
+
     fn with_device<F, T>(&self, f: F) -> Result<T>
     where
         F: FnOnce(&Device) -> Result<T>,
     {
+        // Any NVML call wakes a runtime suspended GPU
+        if self.runtime_suspended() {
+            return Err(anyhow!("nvml device runtime suspended"));
+        }
         match self.device.as_ref() {
             Some(device_ref) => f(device_ref),
             None => Err(anyhow!("nvml device not loaded")),
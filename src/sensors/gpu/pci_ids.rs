@@ -0,0 +1,385 @@
+//! Parser for the `pci.ids` database shipped by hwdata / pciutils.
+//!
+//! Vendors start at column 0, their devices are indented by one tab and the
+//! subsystems of a device by two. The device classes after the first `C` line are ignored.
+//!
+//! The database is over a megabyte, so it isn't kept around. It is read on each lookup,
+//! which only happens while detecting GPUs, and only for display controllers.
+
+use log::{debug, info};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const PCI_IDS_PATHS: [&str; 2] = ["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids"];
+
+// PCI base class of display controllers, the top byte of the sysfs class file
+const DISPLAY_CLASS: u32 = 0x03;
+
+#[derive(Debug, Default)]
+struct Device {
+    name: String,
+    // Keyed by subsystem vendor and device
+    subsystems: HashMap<(u16, u16), String>,
+}
+
+#[derive(Debug, Default)]
+struct Vendor {
+    name: String,
+    devices: HashMap<u16, Device>,
+}
+
+#[derive(Debug, Default)]
+pub struct PciIds {
+    vendors: HashMap<u16, Vendor>,
+}
+
+impl PciIds {
+    /// Read and parse the system database, None if there is none
+    pub fn load() -> Option<Self> {
+        PCI_IDS_PATHS.iter().find_map(|path| {
+            let text = fs::read_to_string(path).ok()?;
+            info!("Loaded PCI ids from {path}");
+            Some(PciIds::parse(&text))
+        })
+    }
+
+    /// Parse the database, up to the device class list
+    pub fn parse(text: &str) -> Self {
+        let mut ids = PciIds::default();
+        let mut vendor = None;
+        let mut device = None;
+
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with("C ") {
+                break;
+            }
+
+            if let Some(line) = line.strip_prefix("\t\t") {
+                let (Some(vendor), Some(device)) = (vendor, device) else {
+                    continue;
+                };
+                if let Some((sub_vendor, rest)) = split_id(line)
+                    && let Some((sub_device, name)) = split_id(rest)
+                    && let Some(device) = ids
+                        .vendors
+                        .get_mut(&vendor)
+                        .and_then(|v| v.devices.get_mut(&device))
+                {
+                    device
+                        .subsystems
+                        .insert((sub_vendor, sub_device), name.to_string());
+                }
+            } else if let Some(line) = line.strip_prefix('\t') {
+                let Some(vendor) = vendor else {
+                    continue;
+                };
+                device = None;
+                if let Some((id, name)) = split_id(line)
+                    && let Some(vendor) = ids.vendors.get_mut(&vendor)
+                {
+                    vendor.devices.insert(
+                        id,
+                        Device {
+                            name: name.to_string(),
+                            ..Default::default()
+                        },
+                    );
+                    device = Some(id);
+                }
+            } else {
+                device = None;
+                vendor = split_id(line).map(|(id, name)| {
+                    ids.vendors.insert(
+                        id,
+                        Vendor {
+                            name: name.to_string(),
+                            ..Default::default()
+                        },
+                    );
+                    id
+                });
+            }
+        }
+
+        ids
+    }
+
+    pub fn vendor_name(&self, vendor: u16) -> Option<&str> {
+        self.vendors.get(&vendor).map(|v| v.name.as_str())
+    }
+
+    pub fn device_name(&self, vendor: u16, device: u16) -> Option<&str> {
+        self.device(vendor, device).map(|d| d.name.as_str())
+    }
+
+    pub fn subsystem_name(&self, vendor: u16, device: u16, subsystem: (u16, u16)) -> Option<&str> {
+        self.device(vendor, device)?
+            .subsystems
+            .get(&subsystem)
+            .map(String::as_str)
+    }
+
+    fn device(&self, vendor: u16, device: u16) -> Option<&Device> {
+        self.vendors.get(&vendor)?.devices.get(&device)
+    }
+}
+
+/// Names of a PCI device as far as the database knows them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PciName {
+    pub vendor: Option<String>,
+    pub device: Option<String>,
+    /// The board, for integrated GPUs this is often the laptop model
+    pub subsystem: Option<String>,
+}
+
+impl PciName {
+    /// The marketing name of the device, else the vendor
+    pub fn gpu_name(&self) -> Option<String> {
+        match (&self.device, &self.vendor) {
+            (Some(device), _) => Some(marketing_name(device).to_string()),
+            (None, Some(vendor)) => Some(format!("{vendor} GPU")),
+            (None, None) => None,
+        }
+    }
+}
+
+// "1002  Advanced Micro Devices" to the id and the name
+fn split_id(line: &str) -> Option<(u16, &str)> {
+    let (id, name) = line.split_once(char::is_whitespace)?;
+    Some((u16::from_str_radix(id, 16).ok()?, name.trim()))
+}
+
+/// "Navi 31 [Radeon RX 7900 XT/7900 XTX]" to "Radeon RX 7900 XT/7900 XTX", the part in
+/// brackets is the marketing name
+pub fn marketing_name(name: &str) -> &str {
+    name.rsplit_once('[')
+        .and_then(|(_, rest)| rest.strip_suffix(']'))
+        .filter(|n| !n.is_empty())
+        .unwrap_or(name)
+}
+
+fn read_id(path: &Path) -> Option<u16> {
+    let text = fs::read_to_string(path).ok()?;
+    u16::from_str_radix(text.trim().trim_start_matches("0x"), 16).ok()
+}
+
+// Any GPU, whatever its vendor, has a class of 0x03xxxx, VGA, 3D or other display
+fn is_display(device: &Path) -> bool {
+    fs::read_to_string(device.join("class"))
+        .ok()
+        .and_then(|text| u32::from_str_radix(text.trim().trim_start_matches("0x"), 16).ok())
+        .is_some_and(|class| class >> 16 == DISPLAY_CLASS)
+}
+
+/// Names of the PCI device at `device`, such as /sys/class/drm/card0/device
+///
+/// Loads the database for this one lookup and drops it again.
+pub fn lookup(device: &Path) -> Option<PciName> {
+    // Not worth reading the database for anything but a display controller
+    if !is_display(device) {
+        return None;
+    }
+    lookup_in(&PciIds::load()?, device)
+}
+
+pub fn lookup_in(ids: &PciIds, device: &Path) -> Option<PciName> {
+    let vendor = read_id(&device.join("vendor"))?;
+    let device_id = read_id(&device.join("device"))?;
+    let subsystem =
+        read_id(&device.join("subsystem_vendor")).zip(read_id(&device.join("subsystem_device")));
+
+    let name = PciName {
+        vendor: ids.vendor_name(vendor).map(str::to_string),
+        device: ids.device_name(vendor, device_id).map(str::to_string),
+        subsystem: subsystem
+            .and_then(|s| ids.subsystem_name(vendor, device_id, s))
+            .map(str::to_string),
+    };
+    debug!(
+        "pci_ids::lookup({vendor:04x}:{device_id:04x}) = {:?} / {:?} / {:?}",
+        name.vendor, name.device, name.subsystem
+    );
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // Cut from pci.ids, with its comments and tab indentation
+    const PCI_IDS: &str = "\
+#
+#\tList of PCI ID's
+#
+# Version: 2024.02.02
+#
+
+# Vendors, devices and subsystems. Please keep sorted.
+
+0e11  Compaq Computer Corporation
+\t0001  PCI to EISA Bridge
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t15bf  Phoenix1
+\t\t1002 15bf  Phoenix1
+\t\tf111 0006  Framework Laptop 16 (AMD Ryzen 7040 Series)
+\t744c  Navi 31 [Radeon RX 7900 XT/7900 XTX/7900 GRE/7900M]
+\t\t1002 0e3b  RX 7900 GRE
+# Broken lines are skipped
+\tzzzz  Not an id
+\t\tzzzz 0001  Not an id either
+10de  NVIDIA Corporation
+\t2684  AD102 [GeForce RTX 4090]
+\t28e0  AD107M [GeForce RTX 4060 Max-Q / Mobile]
+8086  Intel Corporation
+\t7d55  Meteor Lake-P [Intel Arc Graphics]
+\t\t17aa 2234  ThinkPad X1 Carbon Gen 12
+\t9a49  TigerLake-LP GT2 [Iris Xe Graphics]
+
+# List of known device classes, subclasses and programming interfaces
+
+C 00  Unclassified device
+\t00  Non-VGA unclassified device
+";
+
+    #[test]
+    fn parse_gpu_vendors() {
+        let ids = PciIds::parse(PCI_IDS);
+
+        assert_eq!(
+            ids.vendor_name(0x1002),
+            Some("Advanced Micro Devices, Inc. [AMD/ATI]")
+        );
+        assert_eq!(ids.vendor_name(0x10de), Some("NVIDIA Corporation"));
+        assert_eq!(ids.vendor_name(0x8086), Some("Intel Corporation"));
+
+        assert_eq!(
+            ids.device_name(0x1002, 0x744c),
+            Some("Navi 31 [Radeon RX 7900 XT/7900 XTX/7900 GRE/7900M]")
+        );
+        assert_eq!(
+            ids.device_name(0x10de, 0x28e0),
+            Some("AD107M [GeForce RTX 4060 Max-Q / Mobile]")
+        );
+        assert_eq!(
+            ids.device_name(0x8086, 0x9a49),
+            Some("TigerLake-LP GT2 [Iris Xe Graphics]")
+        );
+        assert_eq!(ids.device_name(0x1002, 0x0001), None);
+
+        assert_eq!(
+            ids.subsystem_name(0x1002, 0x15bf, (0xf111, 0x0006)),
+            Some("Framework Laptop 16 (AMD Ryzen 7040 Series)")
+        );
+        assert_eq!(
+            ids.subsystem_name(0x8086, 0x7d55, (0x17aa, 0x2234)),
+            Some("ThinkPad X1 Carbon Gen 12")
+        );
+        // Subsystems belong to their own device only
+        assert_eq!(ids.subsystem_name(0x1002, 0x744c, (0xf111, 0x0006)), None);
+    }
+
+    #[test]
+    fn parse_skips_classes() {
+        let ids = PciIds::parse(PCI_IDS);
+
+        assert_eq!(ids.vendors.len(), 4);
+        assert_eq!(ids.vendor_name(0x0e11), Some("Compaq Computer Corporation"));
+        assert_eq!(ids.device_name(0x0e11, 0x0001), Some("PCI to EISA Bridge"));
+        // The class list would otherwise read as vendor 0 with device 0
+        assert_eq!(ids.device_name(0x0000, 0x0000), None);
+        assert_eq!(ids.vendors[&0x1002].devices.len(), 2);
+    }
+
+    // A sysfs PCI device directory, removed when dropped
+    struct FakeDevice(PathBuf);
+
+    impl FakeDevice {
+        fn new(name: &str, ids: &[(&str, &str)]) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("minimon-pci-ids-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            for (file, value) in ids {
+                fs::write(dir.join(file), format!("{value}\n")).unwrap();
+            }
+            FakeDevice(dir)
+        }
+    }
+
+    impl Drop for FakeDevice {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn lookup_with_subsystem() {
+        let ids = PciIds::parse(PCI_IDS);
+        let device = FakeDevice::new(
+            "framework",
+            &[
+                ("vendor", "0x1002"),
+                ("device", "0x15bf"),
+                ("subsystem_vendor", "0xf111"),
+                ("subsystem_device", "0x0006"),
+            ],
+        );
+
+        let name = lookup_in(&ids, &device.0).unwrap();
+        assert_eq!(
+            name.vendor.as_deref(),
+            Some("Advanced Micro Devices, Inc. [AMD/ATI]")
+        );
+        assert_eq!(name.device.as_deref(), Some("Phoenix1"));
+        assert_eq!(
+            name.subsystem.as_deref(),
+            Some("Framework Laptop 16 (AMD Ryzen 7040 Series)")
+        );
+        assert_eq!(name.gpu_name().as_deref(), Some("Phoenix1"));
+    }
+
+    #[test]
+    fn lookup_unknown_device() {
+        let ids = PciIds::parse(PCI_IDS);
+        let device = FakeDevice::new("unknown", &[("vendor", "0x10de"), ("device", "0xffff")]);
+
+        let name = lookup_in(&ids, &device.0).unwrap();
+        assert_eq!(name.device, None);
+        assert_eq!(name.subsystem, None);
+        assert_eq!(name.gpu_name().as_deref(), Some("NVIDIA Corporation GPU"));
+
+        // Platform devices have no ids at all
+        let platform = FakeDevice::new("platform", &[]);
+        assert_eq!(lookup_in(&ids, &platform.0), None);
+    }
+
+    #[test]
+    fn only_display_controllers_looked_up() {
+        let vga = FakeDevice::new("vga", &[("class", "0x030000")]);
+        let display_3d = FakeDevice::new("3d", &[("class", "0x030200")]);
+        let audio = FakeDevice::new("audio", &[("class", "0x040300")]);
+        let platform = FakeDevice::new("no-class", &[]);
+
+        assert!(is_display(&vga.0));
+        assert!(is_display(&display_3d.0));
+        assert!(!is_display(&audio.0));
+        assert!(!is_display(&platform.0));
+        assert_eq!(lookup(&audio.0), None);
+    }
+
+    #[test]
+    fn marketing_names() {
+        assert_eq!(
+            marketing_name("AD102 [GeForce RTX 4090]"),
+            "GeForce RTX 4090"
+        );
+        assert_eq!(marketing_name("Phoenix1"), "Phoenix1");
+        assert_eq!(marketing_name("Odd []"), "Odd []");
+    }
+}
@@ -1,10 +1,14 @@
 use bounded_vec_deque::BoundedVecDeque;
+use cosmic::cosmic_theme::palette::Srgba;
 use cosmic::iced::Alignment::Center;
 use cosmic::{Element, Renderer, Theme};
 use log::info;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt::Write;
+use std::path::Path;
+use std::sync::LazyLock;
 
+use crate::charts::{heat::HeatChart, line::LineChart, ring::RingChart};
 use crate::sensors::gpu::GpuType;
 use crate::sensors::{GpuConfig, INVALID_IMG};
 use cosmic::widget::{self, Column, Container};
@@ -17,31 +21,46 @@ use cosmic::{
     widget::Row,
 };
 
-use super::TempUnit;
+use super::{DisplayOptions, TempUnit};
 use crate::app::Message;
 use crate::colorpicker::DemoGraph;
-use crate::config::DeviceKind;
+use crate::config::{DeviceKind, UnitSystem};
+use crate::hotplug::DrmEvent;
+use crate::units::{Quantity, UnitVariant};
 use crate::{
-    config::{ChartColors, ChartKind, ColorVariant, GpuTempConfig, GpuUsageConfig, GpuVramConfig},
+    config::{
+        ChartColors, ChartKind, ColorVariant, GpuTempConfig, GpuTempSensor, GpuUsageConfig,
+        GpuVramConfig,
+    },
     fl,
-    svg_graph::SvgColors,
+    svg_graph::{ChartSize, SvgColors},
 };
 use std::any::Any;
 
 use super::gpu::amd::AmdGpu;
+use super::gpu::fdinfo::DrmGpu;
 use super::gpu::intel::IntelGpu;
-use super::gpu::{GpuIf, nvidia::NvidiaGpu};
+use super::gpu::{
+    FanSpeed, GpuClocks, GpuIf, GpuPowerState, GpuProcess, ThrottleReason, nvidia::NvidiaGpu,
+};
 
 const MAX_SAMPLES: usize = 21;
 
-#[cfg(feature = "lyon_charts")]
-use std::sync::LazyLock;
-#[cfg(feature = "lyon_charts")]
+// Detection order, the DRM fdinfo backend skips drivers with their own backend
+const GPU_TYPES: [GpuType; 4] = [GpuType::Intel, GpuType::Nvidia, GpuType::Amd, GpuType::Drm];
+
+// Rows shown in the process list of the settings page
+const MAX_PROCESSES: usize = 10;
+
+// Greyed out colors of disabled GPUs
 static DISABLED_COLORS: LazyLock<ChartColors> = LazyLock::new(|| ChartColors {
-    color1: cosmic::cosmic_theme::palette::Srgba::from_components((0xFF, 0xFF, 0xFF, 0x20)),
-    color2: cosmic::cosmic_theme::palette::Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
-    color3: cosmic::cosmic_theme::palette::Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
-    color4: cosmic::cosmic_theme::palette::Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
+    background: Srgba::from_components((0xFF, 0xFF, 0xFF, 0x20)),
+    frame: Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
+    text: Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
+    graph1: Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
+    graph2: Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
+    graph3: Srgba::from_components((0x72, 0x72, 0x72, 0xFF)),
+    ..Default::default()
 });
 
 pub struct Gpus {
@@ -56,9 +75,9 @@ impl Gpus {
             //nvidia_redetect_attempts: 0,
         };
 
-        gpus.redetect(GpuType::Intel, is_laptop);
-        gpus.redetect(GpuType::Nvidia, is_laptop);
-        gpus.redetect(GpuType::Amd, is_laptop);
+        for gpu_type in GPU_TYPES {
+            gpus.redetect(gpu_type, is_laptop);
+        }
 
         gpus
     }
@@ -75,6 +94,46 @@ impl Gpus {
         self.gpus.values().any(|gpu| gpu.gpu_type() == gpu_type)
     }
 
+    /// Drop the GPU that went away or pick up the new one, called on DRM hotplug events.
+    ///
+    /// Only the card named in the event is probed, the other GPUs may be runtime
+    /// suspended and NVML or gpu_metrics would wake them.
+    pub fn hotplug(&mut self, event: &DrmEvent, is_laptop: bool) {
+        match event {
+            DrmEvent::Removed(card) => {
+                info!("DRM {card} removed");
+                self.gpus.retain(|id, gpu| {
+                    let present = gpu.is_present();
+                    if !present {
+                        info!("GPU {id} removed");
+                    }
+                    present
+                });
+            }
+            DrmEvent::Added(card) => self.detect_card(card, is_laptop),
+        }
+    }
+
+    fn detect_card(&mut self, card: &str, is_laptop: bool) {
+        let device = Path::new("/sys/class/drm").join(card).join("device");
+        let pdev = DrmGpu::read_pdev(&device);
+
+        // Decided from sysfs alone, so a known GPU is never touched
+        if pdev.is_some() && self.gpus.values().any(|gpu| gpu.pdev() == pdev) {
+            info!("DRM {card} at {pdev:?} already detected, skipping.");
+            return;
+        }
+
+        let detected = match DrmGpu::driver(&device).as_deref() {
+            Some("amdgpu") => AmdGpu::get_gpu(card),
+            Some("nvidia") => pdev.as_deref().and_then(NvidiaGpu::get_gpu_at),
+            Some(_) => DrmGpu::get_gpu(card),
+            None => None,
+        };
+
+        self.insert(detected.into_iter().collect(), is_laptop);
+    }
+
     pub fn redetect(&mut self, gpu_type: GpuType, is_laptop: bool) {
         //Test code
         //if gpu_type == GpuType::Nvidia && self.nvidia_redetect_attempts < 5 {
@@ -86,8 +145,13 @@ impl Gpus {
             GpuType::Intel => IntelGpu::get_gpus(),
             GpuType::Nvidia => NvidiaGpu::get_gpus(),
             GpuType::Amd => AmdGpu::get_gpus(),
+            GpuType::Drm => DrmGpu::get_gpus(),
         };
 
+        self.insert(detected, is_laptop);
+    }
+
+    fn insert(&mut self, detected: Vec<Gpu>, is_laptop: bool) {
         for mut gpu in detected {
             let id = gpu.id();
 
@@ -130,14 +194,25 @@ impl Gpus {
     pub fn is_empty(&self) -> bool {
         self.gpus.is_empty()
     }
+
+    /// Summed board power of all GPUs that report it, in watts
+    pub fn power_draw(&self) -> Option<f64> {
+        self.gpus
+            .values()
+            .filter_map(Gpu::power)
+            .reduce(|a, b| a + b)
+    }
 }
 
 pub struct GpuGraph {
     id: String,
     samples: BoundedVecDeque<f64>,
-    graph_options: Vec<&'static str>,
+    graph_options: &'static super::GraphOptions,
     svg_colors: SvgColors,
+    display: DisplayOptions,
     disabled: bool,
+    // Runtime suspended, drawn in the sleeping style
+    sleeping: bool,
     disabled_colors: SvgColors,
     config: GpuUsageConfig,
 }
@@ -153,24 +228,20 @@ impl GpuGraph {
         GpuGraph {
             id: id.to_owned(),
             samples: BoundedVecDeque::from_iter(std::iter::repeat_n(0.0, MAX_SAMPLES), MAX_SAMPLES),
-            graph_options: super::GRAPH_OPTIONS_RING_LINE.to_vec(),
+            graph_options: &super::GRAPH_OPTIONS_RING_LINE,
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
             disabled: false,
-            disabled_colors: SvgColors {
-                background: String::from("#FFFFFF20"),
-                frame: String::from("#727272FF"),
-                text: String::from("#727272FF"),
-                graph1: String::from("#727272FF"),
-                graph2: String::from("#727272FF"),
-                graph3: String::from("#727272FF"),
-            },
+            sleeping: false,
+            disabled_colors: SvgColors::new(&DISABLED_COLORS),
             config: GpuUsageConfig::default(),
         }
     }
 
-    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<GpuUsageConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors = SvgColors::new(cfg.colors());
         }
     }
@@ -181,48 +252,61 @@ impl GpuGraph {
         }
     }
 
-    #[cfg(feature = "lyon_charts")]
-    pub fn chart<'a>(&self) -> cosmic::widget::Container<crate::app::Message, Theme, Renderer> {
-        if self.config.kind == ChartKind::Ring {
-            let mut latest = self.latest_sample();
-            let mut text = String::with_capacity(10);
-            let mut percentage = String::with_capacity(10);
-            if latest > 100.0 {
-                latest = 100.0;
-            }
-            if self.disabled {
-                _ = write!(percentage, "0");
-                _ = write!(text, "-");
-            } else {
-                if latest < 10.0 {
-                    write!(text, "{latest:.2}").unwrap();
-                } else if latest < 100.0 {
-                    write!(text, "{latest:.1}").unwrap();
-                } else {
-                    write!(text, "{latest}").unwrap();
-                }
-                write!(percentage, "{latest}").unwrap();
-            }
-            chart_container!(crate::charts::ring::RingChart::new(
-                latest as f32,
-                &text,
-                &self.config.colors,
-            ))
+    // The configured colors, or the greyed out ones while disabled
+    fn chart_colors(&self) -> &ChartColors {
+        if self.disabled {
+            &DISABLED_COLORS
         } else {
-            chart_container!(crate::charts::line::LineChart::new(
-                MAX_SAMPLES,
-                &self.samples,
-                &VecDeque::new(),
-                Some(100.0),
-                &self.config.colors,
-            ))
+            self.config.colors()
         }
     }
 
-    #[cfg(not(feature = "lyon_charts"))]
     pub fn chart(
         &'_ self,
+        height_hint: u16,
+        width_hint: u16,
     ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
+        let colors = if self.disabled {
+            &self.disabled_colors
+        } else {
+            &self.svg_colors
+        };
+        match self.config.chart {
+            ChartKind::Gauge if !self.sleeping => {
+                return super::gauge_container(
+                    &[self.latest_sample() / 100.0],
+                    self.chart_colors(),
+                    colors,
+                    size,
+                    self.display.canvas,
+                );
+            }
+            ChartKind::Sparkline if !self.sleeping => {
+                return super::sparkline_container(
+                    &self.samples,
+                    None,
+                    MAX_SAMPLES,
+                    100.0,
+                    self.chart_colors(),
+                    colors,
+                    size,
+                    self.display.canvas,
+                );
+            }
+            _ => (),
+        }
+
+        if self.sleeping {
+            return super::sleeping_container(
+                self.config.chart == ChartKind::Ring,
+                &DISABLED_COLORS,
+                &self.disabled_colors,
+                size,
+                self.display.canvas,
+            );
+        }
+
         let svg = if self.config.chart == ChartKind::Ring {
             let latest = self.latest_sample();
             let mut value = String::with_capacity(10);
@@ -241,25 +325,29 @@ impl GpuGraph {
                 percentage = latest.round().clamp(0.0, 100.0) as u8;
             }
 
-            crate::svg_graph::ring(
-                &value,
-                percentage,
-                None,
-                if self.disabled {
-                    &self.disabled_colors
-                } else {
-                    &self.svg_colors
-                },
-            )
+            if self.display.canvas {
+                return super::canvas_container(RingChart::new(
+                    &value,
+                    percentage,
+                    None,
+                    self.chart_colors(),
+                ));
+            }
+            crate::svg_graph::ring(&value, percentage, None, colors)
         } else {
-            crate::svg_graph::line(
-                &self.samples,
+            let grid = self.config.grid_visible().then(|| "100%".to_owned());
+            if self.display.canvas {
+                return super::canvas_container(
+                    LineChart::new(&self.samples, 100.0, self.chart_colors()).grid(grid),
+                );
+            }
+            crate::svg_graph::with_grid(
+                crate::svg_graph::line(&self.samples, 100.0, colors, size),
                 100.0,
-                if self.disabled {
-                    &self.disabled_colors
-                } else {
-                    &self.svg_colors
-                },
+                false,
+                grid.as_deref(),
+                colors,
+                size,
             )
         };
         super::svg_icon_container::<Message>(svg)
@@ -288,13 +376,14 @@ impl fmt::Display for GpuGraph {
             write!(f, "---%")
         } else {
             let current_val = self.latest_sample();
-            if current_val < 10.0 {
-                write!(f, "{:.2}%", (current_val * 100.0).trunc() / 100.0)
+            let value = if current_val < 10.0 {
+                crate::locale::format_number((current_val * 100.0).trunc() / 100.0, 2)
             } else if current_val < 100.0 {
-                write!(f, "{:.1}%", (current_val * 10.0).trunc() / 10.0)
+                crate::locale::format_number((current_val * 10.0).trunc() / 10.0, 1)
             } else {
-                write!(f, "{current_val}%")
-            }
+                crate::locale::format_number(current_val, 0)
+            };
+            write!(f, "{value}{}", crate::locale::unit_symbol("%"))
         }
     }
 }
@@ -312,6 +401,18 @@ impl DemoGraph for GpuGraph {
                 &std::collections::VecDeque::from(DEMO_SAMPLES),
                 100.0,
                 &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            ChartKind::Gauge => {
+                crate::svg_graph::gauge(&[0.4], &self.svg_colors, ChartSize::SQUARE)
+            }
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                DEMO_SAMPLES.len(),
+                100.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
             ),
             _ => {
                 log::error!("GPUGraph type not supported {:?}", self.config.chart);
@@ -330,7 +431,9 @@ impl DemoGraph for GpuGraph {
     }
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
-        if self.config.chart == ChartKind::Line {
+        if self.config.chart == ChartKind::Sparkline {
+            (*super::COLOR_CHOICES_SPARKLINE).into()
+        } else if matches!(self.config.chart, ChartKind::Line | ChartKind::Gauge) {
             (*super::COLOR_CHOICES_LINE).into()
         } else {
             (*super::COLOR_CHOICES_RING).into()
@@ -349,10 +452,18 @@ impl DemoGraph for GpuGraph {
 pub struct VramGraph {
     id: String,
     samples: BoundedVecDeque<f64>,
-    graph_options: Vec<&'static str>,
+    // GTT is system memory the GPU maps, on APUs most graphics memory lives there
+    gtt_samples: BoundedVecDeque<f64>,
+    graph_options: &'static super::GraphOptions,
     total: f64,
+    gtt_total: f64,
+    // Show VRAM and GTT separately rather than combined
+    stacked: bool,
     svg_colors: SvgColors,
+    display: DisplayOptions,
     disabled: bool,
+    // Runtime suspended, drawn in the sleeping style
+    sleeping: bool,
     disabled_colors: SvgColors,
     config: GpuVramConfig,
 }
@@ -363,25 +474,27 @@ impl VramGraph {
         VramGraph {
             id: id.to_owned(),
             samples: BoundedVecDeque::from_iter(std::iter::repeat_n(0.0, MAX_SAMPLES), MAX_SAMPLES),
-            graph_options: super::GRAPH_OPTIONS_RING_LINE.to_vec(),
+            gtt_samples: BoundedVecDeque::from_iter(
+                std::iter::repeat_n(0.0, MAX_SAMPLES),
+                MAX_SAMPLES,
+            ),
+            graph_options: &super::GRAPH_OPTIONS_RING_LINE,
             total,
+            gtt_total: 0.0,
+            stacked: false,
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
             disabled: false,
-            disabled_colors: SvgColors {
-                background: String::from("#FFFFFF20"),
-                frame: String::from("#727272FF"),
-                text: String::from("#727272FF"),
-                graph1: String::from("#727272FF"),
-                graph2: String::from("#727272FF"),
-                graph3: String::from("#727272FF"),
-            },
+            sleeping: false,
+            disabled_colors: SvgColors::new(&DISABLED_COLORS),
             config: GpuVramConfig::default(),
         }
     }
 
-    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<GpuVramConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors = SvgColors::new(cfg.colors());
         }
     }
@@ -390,58 +503,85 @@ impl VramGraph {
         for sample in &mut self.samples {
             *sample = 0.0;
         }
+        for sample in &mut self.gtt_samples {
+            *sample = 0.0;
+        }
     }
 
-    #[cfg(feature = "lyon_charts")]
-    pub fn chart<'a>(
-        &self,
-    ) -> cosmic::widget::Container<crate::app::Message, cosmic::Theme, cosmic::Renderer> {
-        if self.config.kind == ChartKind::Ring {
-            let latest = self.latest_sample();
-            let mut text = String::with_capacity(10);
-            let mut percentage = String::with_capacity(10);
-
-            let mut pct: f32 = 0.0;
-            if self.disabled {
-                _ = write!(percentage, "0");
-                _ = write!(text, "-");
-            } else {
-                pct = ((latest / self.total) * 100.0) as f32;
-                if pct > 100.0 {
-                    pct = 100.0;
-                }
+    // The configured colors, or the greyed out ones while disabled
+    fn chart_colors(&self) -> &ChartColors {
+        if self.disabled {
+            &DISABLED_COLORS
+        } else {
+            self.config.colors()
+        }
+    }
 
-                if latest < 10.0 {
-                    write!(text, "{latest:.2}").unwrap();
-                } else if latest < 100.0 {
-                    write!(text, "{latest:.1}").unwrap();
+    pub fn chart(
+        &'_ self,
+        height_hint: u16,
+        width_hint: u16,
+    ) -> cosmic::widget::Container<'_, crate::app::Message, Theme, Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
+        let colors = if self.disabled {
+            &self.disabled_colors
+        } else {
+            &self.svg_colors
+        };
+        let total = self.total + self.gtt_total;
+        match self.config.chart {
+            ChartKind::Gauge if !self.sleeping => {
+                return super::gauge_container(
+                    &[self.latest_sample() / total],
+                    self.chart_colors(),
+                    colors,
+                    size,
+                    self.display.canvas,
+                );
+            }
+            ChartKind::Sparkline if !self.sleeping => {
+                let samples = if self.is_shared() {
+                    self.combined_samples()
                 } else {
-                    write!(text, "{latest}").unwrap();
-                }
+                    self.samples.iter().copied().collect()
+                };
+                return super::sparkline_container(
+                    &samples,
+                    None,
+                    MAX_SAMPLES,
+                    total,
+                    self.chart_colors(),
+                    colors,
+                    size,
+                    self.display.canvas,
+                );
             }
-
-            chart_container!(crate::charts::ring::RingChart::new(
-                pct,
-                &text,
-                &self.config.colors,
-            ))
-        } else {
-            chart_container!(crate::charts::line::LineChart::new(
-                MAX_SAMPLES,
-                &self.samples,
-                &VecDeque::new(),
-                Some(self.total),
-                &self.config.colors,
-            ))
+            _ => (),
+        }
+        let canvas = self.display.canvas;
+        // Only the line charts have a grid
+        let grid =
+            (self.config.grid_visible() && !self.sleeping && self.config.chart != ChartKind::Ring)
+                .then(|| {
+                    let units = self.display.units;
+                    format!("{}{}", units.from_gib(total).round(), units.giga())
+                });
+
+        if self.sleeping {
+            return super::sleeping_container(
+                self.config.chart == ChartKind::Ring,
+                self.chart_colors(),
+                colors,
+                size,
+                canvas,
+            );
         }
-    }
 
-    #[cfg(not(feature = "lyon_charts"))]
-    pub fn chart(&'_ self) -> cosmic::widget::Container<'_, crate::app::Message, Theme, Renderer> {
         let svg = if self.config.chart == ChartKind::Ring {
             let latest = self.latest_sample();
             let mut value = String::with_capacity(10);
             let mut percentage: u8 = 0;
+            let mut vram_percentage = None;
 
             if self.disabled {
                 value.push('-');
@@ -453,34 +593,94 @@ impl VramGraph {
                 } else {
                     let _ = write!(value, "{}", latest.round());
                 }
-                percentage = ((latest / self.total) * 100.0).round().clamp(0.0, 100.0) as u8;
+                percentage = ((latest / total) * 100.0).round().clamp(0.0, 100.0) as u8;
+                if self.is_stacked() {
+                    let vram = self.samples.back().copied().unwrap_or(0.0);
+                    vram_percentage =
+                        Some(((vram / total) * 100.0).round().clamp(0.0, 100.0) as u8);
+                }
             }
-            crate::svg_graph::ring(
-                &value,
-                percentage,
-                None,
-                if self.disabled {
-                    &self.disabled_colors
-                } else {
-                    &self.svg_colors
-                },
-            )
-        } else {
-            crate::svg_graph::line(
+            if canvas {
+                return super::canvas_container(RingChart::new(
+                    &value,
+                    percentage,
+                    vram_percentage,
+                    self.chart_colors(),
+                ));
+            }
+            crate::svg_graph::ring(&value, percentage, vram_percentage, colors)
+        } else if self.is_stacked() {
+            if canvas {
+                return super::canvas_container(
+                    LineChart::stacked(
+                        &self.samples,
+                        &self.combined_samples(),
+                        total,
+                        self.chart_colors(),
+                    )
+                    .grid(grid),
+                );
+            }
+            crate::svg_graph::line_stacked(
                 &self.samples,
-                self.total,
-                if self.disabled {
-                    &self.disabled_colors
-                } else {
-                    &self.svg_colors
-                },
+                &self.combined_samples(),
+                total,
+                colors,
+                size,
             )
+        } else if self.is_shared() {
+            if canvas {
+                return super::canvas_container(
+                    LineChart::new(&self.combined_samples(), total, self.chart_colors()).grid(grid),
+                );
+            }
+            crate::svg_graph::line(&self.combined_samples(), total, colors, size)
+        } else {
+            if canvas {
+                return super::canvas_container(
+                    LineChart::new(&self.samples, total, self.chart_colors()).grid(grid),
+                );
+            }
+            crate::svg_graph::line(&self.samples, total, colors, size)
         };
+        let svg = crate::svg_graph::with_grid(svg, total, false, grid.as_deref(), colors, size);
         super::svg_icon_container::<Message>(svg)
     }
 
+    /// VRAM plus GTT used, in GB
+    fn combined_samples(&self) -> VecDeque<f64> {
+        self.samples
+            .iter()
+            .zip(self.gtt_samples.iter())
+            .map(|(vram, gtt)| vram + gtt)
+            .collect()
+    }
+
+    /// Memory in use in GB, including GTT on APUs
     pub fn latest_sample(&self) -> f64 {
-        *self.samples.back().unwrap_or(&0f64)
+        let vram = *self.samples.back().unwrap_or(&0f64);
+        if self.is_shared() {
+            vram + *self.gtt_samples.back().unwrap_or(&0f64)
+        } else {
+            vram
+        }
+    }
+
+    /// The GPU shares system memory through GTT, as APUs do
+    pub fn is_shared(&self) -> bool {
+        self.gtt_total > 0.0
+    }
+
+    fn is_stacked(&self) -> bool {
+        self.stacked && self.is_shared()
+    }
+
+    pub fn set_gtt_total(&mut self, gtt_total: f64) {
+        self.gtt_total = gtt_total;
+    }
+
+    pub fn set_stacked(&mut self, stacked: bool) {
+        self.stacked = stacked;
     }
 
     pub fn graph_kind(&self) -> crate::config::ChartKind {
@@ -492,38 +692,76 @@ impl VramGraph {
     }
 
     pub fn string(&self, vertical_panel: bool) -> String {
-        let current_val = self.latest_sample();
-        let unit: &str = if vertical_panel { "GB" } else { " GB" };
+        let units = self.display.units;
+        let space = if vertical_panel { "" } else { " " };
+        let unit = format!("{space}{}", units.giga());
 
         if self.disabled {
             format!("---{unit}")
-        } else if current_val < 10.0 {
-            format!("{:.2}{unit}", (current_val * 100.0).trunc() / 100.0)
-        } else if current_val < 100.0 {
-            format!("{:.1}{unit}", (current_val * 10.0).trunc() / 10.0)
+        } else if self.is_stacked() {
+            let vram = self.samples.back().copied().unwrap_or(0.0);
+            let gtt = self.gtt_samples.back().copied().unwrap_or(0.0);
+            format!(
+                "{}+{}{unit}",
+                Self::format_gb(units.from_gib(vram)),
+                Self::format_gb(units.from_gib(gtt))
+            )
+        } else {
+            format!(
+                "{}{unit}",
+                Self::format_gb(units.from_gib(self.latest_sample()))
+            )
+        }
+    }
+
+    fn format_gb(value: f64) -> String {
+        if value < 10.0 {
+            crate::locale::format_number((value * 100.0).trunc() / 100.0, 2)
+        } else if value < 100.0 {
+            crate::locale::format_number((value * 10.0).trunc() / 10.0, 1)
         } else {
-            format!("{}{unit}", current_val.round())
+            crate::locale::format_number(value.round(), 0)
         }
     }
 
+    /// Total memory in GB, including GTT on APUs
     pub fn total(&self) -> f64 {
+        self.total + self.gtt_total
+    }
+
+    pub fn vram_total(&self) -> f64 {
         self.total
     }
 
+    pub fn gtt_total(&self) -> f64 {
+        self.gtt_total
+    }
+
     pub fn update(&mut self, sample: u64) {
         let new_val: f64 = sample as f64 / 1_073_741_824.0;
         self.samples.push_back(new_val);
     }
+
+    pub fn update_gtt(&mut self, sample: u64) {
+        let new_val: f64 = sample as f64 / 1_073_741_824.0;
+        self.gtt_samples.push_back(new_val);
+    }
 }
 
 pub struct TempGraph {
     id: String,
     samples: BoundedVecDeque<f64>,
     unit_options: Vec<&'static str>,
-    graph_options: Vec<&'static str>,
+    // Sensors the GPU offers, and their names for the dropdown
+    sensors: Vec<GpuTempSensor>,
+    sensor_options: Vec<String>,
+    graph_options: &'static super::GraphOptions,
     max_temp: f64,
     svg_colors: SvgColors,
+    display: DisplayOptions,
     disabled: bool,
+    // Runtime suspended, drawn in the sleeping style
+    sleeping: bool,
     disabled_colors: SvgColors,
     config: GpuTempConfig,
 }
@@ -535,98 +773,93 @@ impl TempGraph {
             id: id.to_owned(),
             samples: BoundedVecDeque::from_iter(std::iter::repeat_n(0.0, MAX_SAMPLES), MAX_SAMPLES),
             unit_options: super::UNIT_OPTIONS.to_vec(),
-            graph_options: super::GRAPH_OPTIONS_RING_LINE_HEAT.to_vec(),
+            sensors: vec![GpuTempSensor::Edge],
+            sensor_options: Vec::new(),
+            graph_options: &super::GRAPH_OPTIONS_RING_LINE_HEAT,
             max_temp: 100.0,
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
             disabled: false,
-            disabled_colors: SvgColors {
-                background: String::from("#FFFFFF20"),
-                frame: String::from("#727272FF"),
-                text: String::from("#727272FF"),
-                graph1: String::from("#727272FF"),
-                graph2: String::from("#727272FF"),
-                graph3: String::from("#727272FF"),
-            },
+            sleeping: false,
+            disabled_colors: SvgColors::new(&DISABLED_COLORS),
             config: GpuTempConfig::default(),
         }
     }
 
-    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<GpuTempConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors = SvgColors::new(cfg.colors());
         }
     }
 
+    fn set_sensors(&mut self, sensors: Vec<GpuTempSensor>) {
+        self.sensor_options = sensors.iter().map(ToString::to_string).collect();
+        self.sensors = sensors;
+    }
+
     pub fn clear(&mut self) {
         for sample in &mut self.samples {
             *sample = 0.0;
         }
     }
 
-    #[cfg(feature = "lyon_charts")]
-    pub fn chart(
-        &self,
-    ) -> cosmic::widget::Container<crate::app::Message, cosmic::Theme, cosmic::Renderer> {
-        match self.config.kind {
-            ChartKind::Ring => {
-                let mut latest = self.latest_sample();
-                let mut text = self.to_string();
-
-                // remove the °C/°F/°R/K unit if there's not enough space (assuming temp stays below 282°C = 1000°R)
-                while text.len() > 3 {
-                    let _ = text.pop();
-                }
-                let mut percentage = String::with_capacity(10);
-
-                write!(percentage, "{latest}").unwrap();
-
-                if latest > 100.0 {
-                    latest = 100.0;
-                }
-
-                chart_container!(crate::charts::ring::RingChart::new(
-                    latest as f32,
-                    &text,
-                    if self.disabled {
-                        &*DISABLED_COLORS
-                    } else {
-                        &self.config.colors
-                    },
-                ))
-            }
-            ChartKind::Line => chart_container!(crate::charts::line::LineChart::new(
-                MAX_SAMPLES,
-                &self.samples,
-                &VecDeque::new(),
-                Some(self.max_temp),
-                if self.disabled {
-                    &*DISABLED_COLORS
-                } else {
-                    &self.config.colors
-                },
-            )),
-            ChartKind::Heat => chart_container!(crate::charts::heat::HeatChart::new(
-                MAX_SAMPLES,
-                &self.samples,
-                Some(self.max_temp),
-                if self.disabled {
-                    &*DISABLED_COLORS
-                } else {
-                    &self.config.colors
-                },
-            )),
+    // The configured colors, or the greyed out ones while disabled
+    fn chart_colors(&self) -> &ChartColors {
+        if self.disabled {
+            &DISABLED_COLORS
+        } else {
+            self.config.colors()
         }
     }
 
-    #[cfg(not(feature = "lyon_charts"))]
-    pub fn chart(&'_ self) -> cosmic::widget::Container<'_, crate::app::Message, Theme, Renderer> {
+    pub fn chart(
+        &'_ self,
+        height_hint: u16,
+        width_hint: u16,
+    ) -> cosmic::widget::Container<'_, crate::app::Message, Theme, Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
         let colors = if self.disabled {
             &self.disabled_colors
         } else {
             &self.svg_colors
         };
+        if self.sleeping {
+            return super::sleeping_container(
+                self.config.chart == ChartKind::Ring,
+                self.chart_colors(),
+                colors,
+                size,
+                self.display.canvas,
+            );
+        }
+        let canvas = self.display.canvas;
         let svg = match self.config.chart {
+            ChartKind::Gauge => {
+                let fraction =
+                    (self.latest_sample() - self.config.min_temp) / (100.0 - self.config.min_temp);
+                return super::gauge_container(
+                    &[fraction],
+                    self.chart_colors(),
+                    colors,
+                    size,
+                    canvas,
+                );
+            }
+            ChartKind::Sparkline => {
+                let samples = super::temps_above_floor(&self.samples, self.config.min_temp);
+                return super::sparkline_container(
+                    &samples,
+                    None,
+                    MAX_SAMPLES,
+                    self.max_temp,
+                    self.chart_colors(),
+                    colors,
+                    size,
+                    canvas,
+                );
+            }
             ChartKind::Ring => {
                 let latest = self.latest_sample();
                 let mut value = self.to_string_raw();
@@ -642,25 +875,47 @@ impl TempGraph {
                     .round()
                     .clamp(0.0, max) as u8;
 
+                if canvas {
+                    return super::canvas_container(RingChart::new(
+                        &value,
+                        percentage,
+                        None,
+                        self.chart_colors(),
+                    ));
+                }
                 crate::svg_graph::ring(&value, percentage, None, colors)
             }
             ChartKind::Line => {
-                if self.config.min_temp == 0.0 {
-                    crate::svg_graph::line(&self.samples, self.max_temp, colors)
-                } else {
-                    let normalized =
-                        super::normalize_temps_dynamic(&self.samples, self.config.min_temp);
-                    crate::svg_graph::line(&normalized, self.max_temp, colors)
+                let samples = super::temps_above_floor(&self.samples, self.config.min_temp);
+                let grid = self.config.grid_visible().then(|| {
+                    let top =
+                        super::temp_chart_top(&self.samples, self.config.min_temp, self.max_temp);
+                    self.config.unit.label(top)
+                });
+                if canvas {
+                    return super::canvas_container(
+                        LineChart::new(&samples, self.max_temp, self.chart_colors()).grid(grid),
+                    );
                 }
+                crate::svg_graph::with_grid(
+                    crate::svg_graph::line(&samples, self.max_temp, colors, size),
+                    self.max_temp,
+                    false,
+                    grid.as_deref(),
+                    colors,
+                    size,
+                )
             }
             ChartKind::Heat => {
-                if self.config.min_temp == 0.0 {
-                    crate::svg_graph::heat(&self.samples, self.max_temp as u64, colors)
-                } else {
-                    let normalized =
-                        super::normalize_temps_dynamic(&self.samples, self.config.min_temp);
-                    crate::svg_graph::heat(&normalized, self.max_temp as u64, colors)
+                let samples = super::temps_above_floor(&self.samples, self.config.min_temp);
+                if canvas {
+                    return super::canvas_container(HeatChart::new(
+                        &samples,
+                        self.max_temp as u64,
+                        self.chart_colors(),
+                    ));
                 }
+                crate::svg_graph::heat(&samples, self.max_temp as u64, colors, size)
             }
             ChartKind::StackedBars => {
                 log::error!("StackedBars not supported for GpuTemp");
@@ -711,11 +966,24 @@ impl DemoGraph for TempGraph {
                 &std::collections::VecDeque::from(DEMO_SAMPLES),
                 100.0,
                 &self.svg_colors,
+                ChartSize::SQUARE,
             ),
             ChartKind::Heat => crate::svg_graph::heat(
                 &std::collections::VecDeque::from(HEAT_DEMO_SAMPLES),
                 100,
                 &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            ChartKind::Gauge => {
+                crate::svg_graph::gauge(&[0.4], &self.svg_colors, ChartSize::SQUARE)
+            }
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                DEMO_SAMPLES.len(),
+                100.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
             ),
             ChartKind::StackedBars => {
                 log::error!("StackedBars not supported for GpuTemp");
@@ -735,9 +1003,10 @@ impl DemoGraph for TempGraph {
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
         match self.config.chart {
-            ChartKind::Line => (*super::COLOR_CHOICES_LINE).into(),
+            ChartKind::Line | ChartKind::Gauge => (*super::COLOR_CHOICES_LINE).into(),
             ChartKind::Ring => (*super::COLOR_CHOICES_RING).into(),
             ChartKind::Heat => (*super::COLOR_CHOICES_HEAT).into(),
+            ChartKind::Sparkline => (*super::COLOR_CHOICES_SPARKLINE).into(),
             ChartKind::StackedBars => panic!("StackedBars not supported for GpuTemp"),
         }
     }
@@ -764,12 +1033,7 @@ impl fmt::Display for TempGraph {
                 TempUnit::Rankine => write!(f, "---°R"),
             }
         } else {
-            match self.config.unit {
-                TempUnit::Celsius => write!(f, "{}°C", current_val.trunc()),
-                TempUnit::Farenheit => write!(f, "{}°F", (current_val * 9.0 / 5.0 + 32.0).trunc()),
-                TempUnit::Kelvin => write!(f, "{}K", (current_val + 273.15).trunc()),
-                TempUnit::Rankine => write!(f, "{}°R", (current_val * 9.0 / 5.0 + 491.67).trunc()),
-            }
+            f.write_str(&self.config.unit.label(current_val))
         }
     }
 }
@@ -787,6 +1051,18 @@ impl DemoGraph for VramGraph {
                 &std::collections::VecDeque::from(DEMO_SAMPLES),
                 32.0,
                 &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            ChartKind::Gauge => {
+                crate::svg_graph::gauge(&[0.4], &self.svg_colors, ChartSize::SQUARE)
+            }
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                DEMO_SAMPLES.len(),
+                32.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
             ),
             _ => {
                 log::error!("VRAM Graph type not supported {:?}", self.config.chart);
@@ -805,7 +1081,9 @@ impl DemoGraph for VramGraph {
     }
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
-        if self.config.chart == ChartKind::Line {
+        if self.config.chart == ChartKind::Sparkline {
+            (*super::COLOR_CHOICES_SPARKLINE).into()
+        } else if matches!(self.config.chart, ChartKind::Line | ChartKind::Gauge) {
             (*super::COLOR_CHOICES_LINE).into()
         } else {
             (*super::COLOR_CHOICES_RING).into()
@@ -821,36 +1099,296 @@ impl DemoGraph for VramGraph {
     }
 }
 
+/// History of a percentage metric the backend may not support
+struct PercentHistory {
+    samples: BoundedVecDeque<f64>,
+    supported: bool,
+}
+
+impl PercentHistory {
+    fn new() -> Self {
+        PercentHistory {
+            samples: BoundedVecDeque::from_iter(std::iter::repeat_n(0.0, MAX_SAMPLES), MAX_SAMPLES),
+            supported: false,
+        }
+    }
+
+    fn update(&mut self, sample: anyhow::Result<u32>) {
+        if let Ok(sample) = sample {
+            self.supported = true;
+            self.samples.push_back(f64::from(sample));
+        }
+    }
+
+    fn latest_sample(&self) -> f64 {
+        self.samples.back().copied().unwrap_or(0.0)
+    }
+}
+
+/// Optional metrics shown on the settings page when enabled
+pub struct ExtendedGraphs {
+    memory_controller: PercentHistory,
+    encoder: PercentHistory,
+    decoder: PercentHistory,
+    media: PercentHistory,
+    pcie_tx: BoundedVecDeque<u64>,
+    pcie_rx: BoundedVecDeque<u64>,
+    pcie_supported: bool,
+    clocks: Option<GpuClocks>,
+    throttle_reasons: Option<Vec<ThrottleReason>>,
+    hotspot_temperature: Option<u32>,
+    memory_temperature: Option<u32>,
+    fan_speed: Option<FanSpeed>,
+    percent_colors: SvgColors,
+    pcie_colors: SvgColors,
+}
+
+impl ExtendedGraphs {
+    fn new() -> Self {
+        ExtendedGraphs {
+            memory_controller: PercentHistory::new(),
+            encoder: PercentHistory::new(),
+            decoder: PercentHistory::new(),
+            media: PercentHistory::new(),
+            pcie_tx: BoundedVecDeque::from_iter(std::iter::repeat_n(0, MAX_SAMPLES), MAX_SAMPLES),
+            pcie_rx: BoundedVecDeque::from_iter(std::iter::repeat_n(0, MAX_SAMPLES), MAX_SAMPLES),
+            pcie_supported: false,
+            clocks: None,
+            throttle_reasons: None,
+            hotspot_temperature: None,
+            memory_temperature: None,
+            fan_speed: None,
+            percent_colors: SvgColors::new(&ChartColors::new(DeviceKind::Gpu, ChartKind::Line)),
+            pcie_colors: SvgColors::new(&ChartColors::new(
+                DeviceKind::Network(crate::config::NetworkVariant::Combined),
+                ChartKind::Line,
+            )),
+        }
+    }
+
+    fn update(&mut self, gpu_if: &dyn GpuIf) {
+        self.memory_controller
+            .update(gpu_if.memory_controller_usage());
+        self.encoder.update(gpu_if.encoder_usage());
+        self.decoder.update(gpu_if.decoder_usage());
+        self.media.update(gpu_if.media_usage());
+        if let Ok(pcie) = gpu_if.pcie_throughput() {
+            self.pcie_supported = true;
+            self.pcie_tx.push_back(pcie.tx);
+            self.pcie_rx.push_back(pcie.rx);
+        }
+        self.clocks = gpu_if.clocks().ok();
+        self.throttle_reasons = gpu_if.throttle_reasons().ok();
+        self.hotspot_temperature = gpu_if.hotspot_temperature().ok();
+        self.memory_temperature = gpu_if.memory_temperature().ok();
+        self.fan_speed = gpu_if.fan_speed().ok();
+    }
+
+    fn percent_ui<'a>(&self, title: String, history: &PercentHistory) -> Element<'a, Message> {
+        let svg = crate::svg_graph::line(
+            &history.samples,
+            100.0,
+            &self.percent_colors,
+            ChartSize::SQUARE,
+        );
+        column!(
+            Container::new(
+                super::svg_icon_container::<Message>(svg)
+                    .width(60)
+                    .height(60)
+            )
+            .width(90)
+            .align_x(Alignment::Center),
+            widget::text::body(format!("{:.0}%", history.latest_sample()))
+                .width(90)
+                .align_x(Alignment::Center),
+            widget::text::caption(title)
+                .width(90)
+                .align_x(Alignment::Center)
+        )
+        .padding(cosmic::theme::spacing().space_xs)
+        .align_x(Alignment::Center)
+        .into()
+    }
+
+    fn pcie_ui<'a>(&self, units: UnitSystem) -> Element<'a, Message> {
+        let svg = crate::svg_graph::double_line(
+            &self.pcie_rx,
+            &self.pcie_tx,
+            MAX_SAMPLES,
+            &self.pcie_colors,
+            None,
+            false,
+            ChartSize::SQUARE,
+        );
+        let rate = |bytes: Option<&u64>| {
+            units
+                .rate(
+                    bytes.copied().unwrap_or(0),
+                    Quantity::Bytes,
+                    UnitVariant::Long,
+                )
+                .trim_start()
+                .to_string()
+        };
+        column!(
+            Container::new(
+                super::svg_icon_container::<Message>(svg)
+                    .width(60)
+                    .height(60)
+            )
+            .width(90)
+            .align_x(Alignment::Center),
+            widget::text::body(format!(
+                "↓ {} ↑ {}",
+                rate(self.pcie_rx.back()),
+                rate(self.pcie_tx.back())
+            ))
+            .width(130)
+            .align_x(Alignment::Center),
+            widget::text::caption(fl!("gpu-pcie"))
+                .width(90)
+                .align_x(Alignment::Center)
+        )
+        .padding(cosmic::theme::spacing().space_xs)
+        .align_x(Alignment::Center)
+        .into()
+    }
+
+    fn settings_ui<'a>(&self, units: UnitSystem) -> Element<'a, Message> {
+        let mut charts: Vec<Element<'a, Message>> = Vec::new();
+        if self.memory_controller.supported {
+            charts.push(self.percent_ui(fl!("gpu-memory-controller"), &self.memory_controller));
+        }
+        if self.encoder.supported {
+            charts.push(self.percent_ui(fl!("gpu-encoder"), &self.encoder));
+        }
+        if self.decoder.supported {
+            charts.push(self.percent_ui(fl!("gpu-decoder"), &self.decoder));
+        }
+        if self.media.supported {
+            charts.push(self.percent_ui(fl!("gpu-media"), &self.media));
+        }
+        if self.pcie_supported {
+            charts.push(self.pcie_ui(units));
+        }
+
+        let mut content = Column::new().push(
+            Row::with_children(charts)
+                .align_y(Alignment::Center)
+                .spacing(cosmic::theme::spacing().space_xs),
+        );
+
+        if let Some(clocks) = self.clocks {
+            content = content.push(settings::item(
+                fl!("gpu-clocks"),
+                widget::text::body(format!("{} / {} MHz", clocks.core, clocks.memory)),
+            ));
+        }
+
+        let temperatures = [
+            (fl!("gpu-hotspot-temperature"), self.hotspot_temperature),
+            (fl!("gpu-memory-temperature"), self.memory_temperature),
+        ];
+        for (label, temperature) in temperatures {
+            if let Some(millidegrees) = temperature {
+                content = content.push(settings::item(
+                    label,
+                    widget::text::body(format!("{} °C", millidegrees / 1000)),
+                ));
+            }
+        }
+
+        if let Some(fan_speed) = self.fan_speed {
+            content = content.push(settings::item(
+                fl!("gpu-fan"),
+                widget::text::body(fan_speed.to_string()),
+            ));
+        }
+
+        if let Some(reasons) = &self.throttle_reasons {
+            let reasons = if reasons.is_empty() {
+                fl!("throttle-none")
+            } else {
+                reasons
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            content = content.push(settings::item(
+                fl!("gpu-throttle-reasons"),
+                widget::text::body(reasons),
+            ));
+        }
+
+        if !self.memory_controller.supported
+            && !self.encoder.supported
+            && !self.decoder.supported
+            && !self.media.supported
+            && !self.pcie_supported
+            && self.clocks.is_none()
+            && self.throttle_reasons.is_none()
+            && self.hotspot_temperature.is_none()
+            && self.memory_temperature.is_none()
+            && self.fan_speed.is_none()
+        {
+            content = content.push(widget::text::body(fl!("gpu-extended-unsupported")));
+        }
+
+        content.spacing(cosmic::theme::spacing().space_xs).into()
+    }
+}
+
 pub struct Gpu {
     gpu_if: Box<dyn GpuIf>,
+    detected_name: String,
     pub gpu: GpuGraph,
     pub vram: VramGraph,
     pub temp: TempGraph,
+    pub extended: ExtendedGraphs,
+    processes: Vec<GpuProcess>,
+    processes_supported: bool,
+    state: GpuPowerState,
     is_laptop: bool,
     config: GpuConfig,
+    display: DisplayOptions,
 }
 
 impl Gpu {
     pub fn new(gpu_if: Box<dyn GpuIf>) -> Self {
         let total = gpu_if.vram_total();
+        let gtt_total = gpu_if.gtt_total();
         let id = gpu_if.id();
+        let mut vram = VramGraph::new(&id, total as f64 / 1_073_741_824.0);
+        vram.set_gtt_total(gtt_total as f64 / 1_073_741_824.0);
+        let mut temp = TempGraph::new(&id);
+        temp.set_sensors(gpu_if.temperature_sensors());
 
         Gpu {
+            detected_name: gpu_if.name(),
             gpu_if,
             gpu: GpuGraph::new(&id),
-            vram: VramGraph::new(&id, total as f64 / 1_073_741_824.0),
-            temp: TempGraph::new(&id),
+            vram,
+            temp,
+            extended: ExtendedGraphs::new(),
+            processes: Vec::new(),
+            processes_supported: true,
+            state: GpuPowerState::Active,
             is_laptop: false,
             config: GpuConfig::default(),
+            display: DisplayOptions::default(),
         }
     }
 
-    pub fn update_config(&mut self, config: &dyn Any, refresh_rate: u32) {
+    pub fn update_config(&mut self, config: &dyn Any, refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<GpuConfig>() {
             self.config = cfg.clone();
-            self.gpu.update_config(&cfg.usage, refresh_rate);
-            self.vram.update_config(&cfg.vram, refresh_rate);
-            self.temp.update_config(&cfg.temp, refresh_rate);
+            self.display = display;
+            self.gpu.update_config(&cfg.usage, refresh_rate, display);
+            self.vram.update_config(&cfg.vram, refresh_rate, display);
+            self.vram.set_stacked(cfg.stack_values);
+            self.temp.update_config(&cfg.temp, refresh_rate, display);
         }
     }
 
@@ -858,6 +1396,16 @@ impl Gpu {
         self.gpu_if.as_ref().name().clone()
     }
 
+    /// The name chosen by the user, else the detected one
+    pub fn display_name<'a>(&'a self, config: &'a GpuConfig) -> &'a str {
+        let name = config.name.trim();
+        if name.is_empty() {
+            &self.detected_name
+        } else {
+            name
+        }
+    }
+
     pub fn id(&self) -> String {
         self.gpu_if.as_ref().id().clone()
     }
@@ -870,17 +1418,17 @@ impl Gpu {
         match device {
             DeviceKind::Gpu => {
                 let mut dmo = GpuGraph::new(&self.id());
-                dmo.update_config(&self.gpu.config, 0);
+                dmo.update_config(&self.gpu.config, 0, self.display);
                 Box::new(dmo)
             }
             DeviceKind::Vram => {
-                let mut dmo = VramGraph::new(&self.id(), self.vram.total);
-                dmo.update_config(&self.vram.config, 0);
+                let mut dmo = VramGraph::new(&self.id(), self.vram.total());
+                dmo.update_config(&self.vram.config, 0, self.display);
                 Box::new(dmo)
             }
             DeviceKind::GpuTemp => {
                 let mut dmo = TempGraph::new(&self.id());
-                dmo.update_config(&self.temp.config, 0);
+                dmo.update_config(&self.temp.config, 0, self.display);
                 Box::new(dmo)
             }
             _ => {
@@ -892,24 +1440,136 @@ impl Gpu {
 
     pub fn update(&mut self) {
         if self.gpu_if.is_active() {
-            if let Ok(sample) = self.gpu_if.usage() {
+            // Sampling a suspended GPU would wake it
+            let state = self.gpu_if.power_state();
+            if state != GpuPowerState::Active {
+                self.set_state(state);
+                return;
+            }
+
+            self.gpu_if.update();
+            let usage = self.gpu_if.usage();
+            if let Ok(sample) = usage {
                 self.gpu.update(sample);
             }
             if let Ok(sample) = self.gpu_if.vram_used() {
                 self.vram.update(sample);
             }
-            if let Ok(sample) = self.gpu_if.temperature() {
+            if self.vram.is_shared()
+                && let Ok(sample) = self.gpu_if.gtt_used()
+            {
+                self.vram.update_gtt(sample);
+            }
+            // Fall back to edge if the chosen sensor went away
+            if let Ok(sample) = self
+                .gpu_if
+                .sensor_temperature(self.config.temp.sensor)
+                .or_else(|_| self.gpu_if.temperature())
+            {
                 self.temp.update(sample);
             }
+
+            self.set_state(if usage.is_ok() {
+                GpuPowerState::Active
+            } else {
+                GpuPowerState::Error
+            });
+        }
+    }
+
+    /// Grey out the charts unless active, suspended GPUs get the sleeping style
+    fn set_state(&mut self, state: GpuPowerState) {
+        self.state = state;
+        let disabled = state != GpuPowerState::Active;
+        let sleeping = state == GpuPowerState::Suspended;
+        self.gpu.disabled = disabled;
+        self.vram.disabled = disabled;
+        self.temp.disabled = disabled;
+        self.gpu.sleeping = sleeping;
+        self.vram.sleeping = sleeping;
+        self.temp.sleeping = sleeping;
+    }
+
+    pub fn power_state(&self) -> GpuPowerState {
+        self.state
+    }
+
+    /// Sample the extended metrics, only done while they are shown
+    pub fn update_extended(&mut self) {
+        if self.gpu_if.is_active() {
+            self.extended.update(self.gpu_if.as_ref());
+        }
+    }
+
+    /// Sample the processes using the GPU, only done while its settings page is open
+    pub fn update_processes(&mut self) {
+        if self.gpu_if.is_active() {
+            match self.gpu_if.processes() {
+                Ok(mut processes) => {
+                    processes.sort_by(|a, b| {
+                        b.usage
+                            .cmp(&a.usage)
+                            .then(b.memory.cmp(&a.memory))
+                            .then(a.pid.cmp(&b.pid))
+                    });
+                    self.processes = processes;
+                    self.processes_supported = true;
+                }
+                Err(e) => {
+                    log::debug!("{}: no process list: {e}", self.name());
+                    self.processes.clear();
+                    self.processes_supported = false;
+                }
+            }
+        }
+    }
+
+    pub fn clear_processes(&mut self) {
+        self.processes.clear();
+    }
+
+    /// The processes using the GPU, `suspended` if the power profile stops sampling them
+    pub fn processes_ui(&'_ self, suspended: bool) -> Element<'_, crate::app::Message> {
+        let mut content = Column::new()
+            .push(widget::text::heading(fl!("gpu-title-processes")))
+            .spacing(cosmic::theme::spacing().space_xxs);
+
+        if suspended {
+            return content
+                .push(widget::text::body(fl!("gpu-processes-suspended")))
+                .into();
+        }
+        if !self.processes_supported {
+            return content
+                .push(widget::text::body(fl!("gpu-processes-unsupported")))
+                .into();
+        }
+        if self.processes.is_empty() {
+            return content
+                .push(widget::text::body(fl!("gpu-processes-none")))
+                .into();
         }
+
+        for process in self.processes.iter().take(MAX_PROCESSES) {
+            let usage = process
+                .usage
+                .map_or_else(|| "-".to_string(), |usage| format!("{usage}%"));
+            let memory = process
+                .memory
+                .map_or_else(|| "-".to_string(), |bytes| self.display.units.size(bytes));
+            content = content.push(settings::item(
+                format!("{} ({})", process.name, process.pid),
+                widget::text::body(format!("{usage}  {memory}")),
+            ));
+        }
+
+        content.into()
     }
 
     pub fn restart(&mut self) {
         info!("Restarting {}", self.name());
         self.gpu_if.restart();
-        self.gpu.disabled = false;
-        self.vram.disabled = false;
-        self.temp.disabled = false;
+        self.set_state(GpuPowerState::Active);
     }
 
     pub fn stop(&mut self) {
@@ -918,15 +1578,29 @@ impl Gpu {
         self.gpu.clear();
         self.vram.clear();
         self.temp.clear();
-        self.gpu.disabled = true;
-        self.vram.disabled = true;
-        self.temp.disabled = true;
+        self.set_state(GpuPowerState::Paused);
     }
 
     pub fn is_active(&self) -> bool {
         self.gpu_if.is_active()
     }
 
+    pub fn is_present(&self) -> bool {
+        self.gpu_if.is_present()
+    }
+
+    pub fn pdev(&self) -> Option<String> {
+        self.gpu_if.pdev()
+    }
+
+    /// Board power in watts, None if paused or not supported
+    pub fn power(&self) -> Option<f64> {
+        if !self.gpu_if.is_active() {
+            return None;
+        }
+        self.gpu_if.power().ok().map(|mw| f64::from(mw) / 1000.0)
+    }
+
     pub fn gpu_type(&self) -> GpuType {
         self.gpu_if.gpu_type()
     }
@@ -943,7 +1617,7 @@ impl Gpu {
         let usage = self.gpu.to_string();
         gpu_elements.push(Element::from(
             column!(
-                Container::new(self.gpu.chart().width(60).height(60))
+                Container::new(self.gpu.chart(60, 60).width(60).height(60))
                     .width(90)
                     .align_x(Alignment::Center),
                 cosmic::widget::text::body(usage.to_string())
@@ -955,7 +1629,8 @@ impl Gpu {
         ));
 
         let gpu_kind = self.gpu.graph_kind();
-        let selected: Option<usize> = Some(gpu_kind.into());
+        let selected = self.gpu.graph_options.index(gpu_kind);
+        let graph_options = self.gpu.graph_options;
         let id = self.id();
         gpu_elements.push(Element::from(
             column!(
@@ -971,10 +1646,25 @@ impl Gpu {
                         Message::GpuToggleValue(self.id(), DeviceKind::Gpu, value)
                     }),
                 ),
+                crate::template::settings_ui(&config.template, move |t| {
+                    Message::GpuTemplateChanged(self.id(), t)
+                }),
+                settings::item(
+                    fl!("show-grid"),
+                    toggler(config.grid_visible()).on_toggle(|value| Message::GpuToggleGrid(
+                        self.id(),
+                        DeviceKind::Gpu,
+                        value
+                    )),
+                ),
                 row!(
                     widget::text::body(fl!("chart-type")),
-                    widget::dropdown(&self.gpu.graph_options, selected, move |m| {
-                        Message::GpuSelectGraphType(id.clone(), DeviceKind::Gpu, m.into())
+                    widget::dropdown(graph_options.names(), selected, move |m| {
+                        Message::GpuSelectGraphType(
+                            id.clone(),
+                            DeviceKind::Gpu,
+                            graph_options.kind(m),
+                        )
                     },)
                     .width(70),
                     widget::space::horizontal(),
@@ -1009,7 +1699,7 @@ impl Gpu {
         let vram = self.vram.string(false);
         vram_elements.push(Element::from(
             column!(
-                Container::new(self.vram.chart().width(60).height(60))
+                Container::new(self.vram.chart(60, 60).width(60).height(60))
                     .width(90)
                     .align_x(Alignment::Center),
                 cosmic::widget::text::body(vram.to_string())
@@ -1020,7 +1710,8 @@ impl Gpu {
             .align_x(Alignment::Center),
         ));
 
-        let selected: Option<usize> = Some(self.vram.graph_kind().into());
+        let selected = self.vram.graph_options.index(self.vram.graph_kind());
+        let graph_options = self.vram.graph_options;
         let mem_kind = self.vram.graph_kind();
         let id = self.id();
         vram_elements.push(Element::from(
@@ -1037,10 +1728,22 @@ impl Gpu {
                         Message::GpuToggleValue(self.id(), DeviceKind::Vram, value)
                     }),
                 ),
+                settings::item(
+                    fl!("show-grid"),
+                    toggler(config.grid_visible()).on_toggle(|value| Message::GpuToggleGrid(
+                        self.id(),
+                        DeviceKind::Vram,
+                        value
+                    )),
+                ),
                 row!(
                     widget::text::body(fl!("chart-type")),
-                    widget::dropdown(&self.vram.graph_options, selected, move |m| {
-                        Message::GpuSelectGraphType(id.clone(), DeviceKind::Vram, m.into())
+                    widget::dropdown(graph_options.names(), selected, move |m| {
+                        Message::GpuSelectGraphType(
+                            id.clone(),
+                            DeviceKind::Vram,
+                            graph_options.kind(m),
+                        )
                     },)
                     .width(70),
                     widget::space::horizontal(),
@@ -1053,12 +1756,25 @@ impl Gpu {
             .spacing(cosmic.space_xs()),
         ));
 
+        let units = self.display.units;
+        let shared = if self.vram.is_shared() {
+            Some(widget::text::caption(fl!(
+                "gpu-shared-memory",
+                vram = format!("{:.1}", units.from_gib(self.vram.vram_total())),
+                gtt = format!("{:.1}", units.from_gib(self.vram.gtt_total())),
+                unit = units.giga()
+            )))
+        } else {
+            None
+        };
+
         column![
             widget::text::heading(fl!("gpu-title-vram")),
             Row::with_children(vram_elements)
                 .align_y(Alignment::Center)
                 .spacing(cosmic.space_xs())
         ]
+        .push_maybe(shared)
         .spacing(cosmic::theme::spacing().space_xs)
         .into()
     }
@@ -1075,7 +1791,7 @@ impl Gpu {
         let temp = self.temp.to_string();
         temp_elements.push(Element::from(
             column!(
-                Container::new(self.temp.chart().width(60).height(60))
+                Container::new(self.temp.chart(60, 60).width(60).height(60))
                     .width(90)
                     .align_x(Alignment::Center),
                 cosmic::widget::text::body(temp.to_string())
@@ -1086,14 +1802,29 @@ impl Gpu {
             .align_x(Alignment::Center),
         ));
 
-        let selected: Option<usize> = Some(self.temp.graph_kind().into());
+        let selected = self.temp.graph_options.index(self.temp.graph_kind());
+        let graph_options = self.temp.graph_options;
         let selected_unit: Option<usize> = Some(self.temp.config.unit.into());
         let temp_kind = self.temp.graph_kind();
         let id1 = self.id();
         let id2 = self.id();
         let id3 = self.id();
+        let id4 = self.id();
         let min_temp_val = config.min_temp;
 
+        let sensors = self.temp.sensors.clone();
+        let selected_sensor = sensors.iter().position(|s| *s == config.sensor);
+        let sensor_choice = if sensors.len() > 1 {
+            Some(settings::item(
+                fl!("gpu-temp-sensor"),
+                widget::dropdown(&self.temp.sensor_options, selected_sensor, move |m| {
+                    Message::SelectGpuTempSensor(id4.clone(), sensors[m])
+                }),
+            ))
+        } else {
+            None
+        };
+
         let min_temp_input = {
             let val_string = min_temp_val.to_string();
             widget::text_input("", val_string)
@@ -1128,10 +1859,22 @@ impl Gpu {
                         Message::SelectGpuTempUnit(id1.clone(), m.into())
                     },)
                 ),
+                settings::item(
+                    fl!("show-grid"),
+                    toggler(config.grid_visible()).on_toggle(|value| Message::GpuToggleGrid(
+                        self.id(),
+                        DeviceKind::GpuTemp,
+                        value
+                    )),
+                ),
                 row!(
                     widget::text::body(fl!("chart-type")),
-                    widget::dropdown(&self.temp.graph_options, selected, move |m| {
-                        Message::GpuSelectGraphType(id2.clone(), DeviceKind::GpuTemp, m.into())
+                    widget::dropdown(graph_options.names(), selected, move |m| {
+                        Message::GpuSelectGraphType(
+                            id2.clone(),
+                            DeviceKind::GpuTemp,
+                            graph_options.kind(m),
+                        )
                     },)
                     .width(70),
                     widget::space::horizontal(),
@@ -1141,6 +1884,7 @@ impl Gpu {
                 )
                 .align_y(Center),
             )
+            .push_maybe(sensor_choice)
             .push(settings::item(fl!("min-temperature"), min_temp_input))
             .spacing(cosmic.space_xs()),
         ));
@@ -1176,6 +1920,30 @@ impl Gpu {
             None
         };
 
+        let name_input = settings::item(
+            fl!("gpu-display-name"),
+            widget::text_input(self.detected_name.as_str(), config.name.as_str())
+                .width(200)
+                .on_input(move |name| Message::GpuRename(self.id(), name)),
+        );
+
+        let hidden_toggle = settings::item(
+            fl!("gpu-hide"),
+            widget::toggler(config.hidden)
+                .on_toggle(move |value| Message::GpuToggleHidden(self.id(), value)),
+        );
+
+        let order_buttons = settings::item(
+            fl!("gpu-order"),
+            row!(
+                widget::button::icon(widget::icon::from_name("pan-up-symbolic").size(5))
+                    .on_press(Message::GpuChangeOrder(self.id(), -1)),
+                widget::button::icon(widget::icon::from_name("pan-down-symbolic").size(5))
+                    .on_press(Message::GpuChangeOrder(self.id(), 1)),
+            )
+            .spacing(cosmic::theme::spacing().space_xxs),
+        );
+
         let label_toggle = settings::item(
             fl!("enable-label"),
             widget::toggler(config.usage.label_visible())
@@ -1191,7 +1959,10 @@ impl Gpu {
         let usage = self.settings_usage_ui(&config.usage);
         let vram = self.settings_vram_ui(&config.vram);
 
-        let stacked = if config.vram.value_visible() && config.usage.value_visible() {
+        // On shared memory the same option stacks the GTT used on top of the VRAM
+        let stacked = if (config.vram.value_visible() && config.usage.value_visible())
+            || self.vram.is_shared()
+        {
             Some(settings::item(
                 fl!("settings-gpu-stack-values"),
                 row!(
@@ -1203,10 +1974,29 @@ impl Gpu {
         } else {
             None
         };
+        let stacked_gtt = self
+            .vram
+            .is_shared()
+            .then(|| widget::text::caption(fl!("settings-gpu-stack-gtt")));
 
         let temp = self.settings_temp_ui(&config.temp);
 
+        let extended_toggle = settings::item(
+            fl!("gpu-show-extended"),
+            widget::toggler(config.show_extended)
+                .on_toggle(move |value| Message::GpuToggleExtended(self.id().clone(), value)),
+        );
+
+        let extended = if config.show_extended {
+            Some(self.extended.settings_ui(self.display.units))
+        } else {
+            None
+        };
+
         Column::new()
+            .push(name_input)
+            .push(hidden_toggle)
+            .push(order_buttons)
             .push_maybe(battery_disable)
             .push(label_toggle)
             .push(icon_toggle)
@@ -1214,6 +2004,10 @@ impl Gpu {
             .push(temp)
             .push(vram)
             .push_maybe(stacked)
+            .push_maybe(stacked_gtt)
+            .push(widget::text::heading(fl!("gpu-title-extended")))
+            .push(extended_toggle)
+            .push_maybe(extended)
             .spacing(cosmic::theme::spacing().space_xs)
             .into()
     }
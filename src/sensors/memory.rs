@@ -2,11 +2,12 @@ use cosmic::{Element, iced::Alignment::Center, widget::Container};
 use sysinfo::{MemoryRefreshKind, System};
 
 use crate::{
+    charts::{line::LineChart, ring::RingChart},
     colorpicker::DemoGraph,
     config::{ChartColors, ChartKind, ColorVariant, DeviceKind, MemoryConfig},
     fl,
     sensors::INVALID_IMG,
-    svg_graph::SvgColors,
+    svg_graph::{ChartSize, SvgColors},
 };
 
 use cosmic::widget;
@@ -26,7 +27,7 @@ use crate::app::Message;
 use bounded_vec_deque::BoundedVecDeque;
 use std::fmt::Write;
 
-use super::Sensor;
+use super::{DisplayOptions, Sensor};
 
 const MAX_SAMPLES: usize = 21;
 
@@ -57,9 +58,10 @@ pub struct Memory {
     samples_allocated: BoundedVecDeque<f64>,
     total_memory: f64,
     system: System,
-    graph_options: Vec<&'static str>,
+    graph_options: &'static super::GraphOptions,
     /// colors cached so we don't need to convert to string every time
     svg_colors: SvgColors,
+    display: DisplayOptions,
     config: MemoryConfig,
 }
 
@@ -90,15 +92,34 @@ impl DemoGraph for Memory {
                         &std::collections::VecDeque::from(DEMO_SAMPLES_ALLOCATED),
                         38.0,
                         &self.svg_colors,
+                        ChartSize::SQUARE,
                     )
                 } else {
                     crate::svg_graph::line(
                         &std::collections::VecDeque::from(DEMO_SAMPLES),
                         38.0,
                         &self.svg_colors,
+                        ChartSize::SQUARE,
                     )
                 }
             }
+            ChartKind::Gauge => crate::svg_graph::gauge(
+                if self.config.show_allocated {
+                    &[0.4, 0.8]
+                } else {
+                    &[0.4]
+                },
+                &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                DEMO_SAMPLES.len(),
+                38.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
             _ => {
                 log::error!(
                     "Graph type {:?} not supported for memory",
@@ -119,7 +140,11 @@ impl DemoGraph for Memory {
     }
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
-        if self.config.chart == ChartKind::Line {
+        if self.config.chart == ChartKind::Sparkline {
+            (*super::COLOR_CHOICES_SPARKLINE).into()
+        } else if self.config.chart == ChartKind::Gauge {
+            (*super::COLOR_CHOICES_LINE).into()
+        } else if self.config.chart == ChartKind::Line {
             if self.config.show_allocated {
                 (*COLOR_CHOICES_LINE_STACKED).into()
             } else {
@@ -142,9 +167,10 @@ impl DemoGraph for Memory {
 }
 
 impl Sensor for Memory {
-    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<MemoryConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors.set_colors(cfg.colors());
         }
     }
@@ -154,7 +180,7 @@ impl Sensor for Memory {
     }
 
     fn set_graph_kind(&mut self, kind: ChartKind) {
-        assert!(kind == ChartKind::Line || kind == ChartKind::Ring);
+        assert!(self.graph_options.contains(kind));
         self.config.chart = kind;
     }
 
@@ -171,58 +197,52 @@ impl Sensor for Memory {
 
     fn demo_graph(&self) -> Box<dyn DemoGraph> {
         let mut dmo = Memory::default();
-        dmo.update_config(&self.config, 0);
+        dmo.update_config(&self.config, 0, self.display);
         Box::new(dmo)
     }
 
-    #[cfg(feature = "lyon_charts")]
-    fn chart<'a>(
-        &self,
-    ) -> cosmic::widget::Container<crate::app::Message, cosmic::Theme, cosmic::Renderer> {
-        if self.config.kind == ChartKind::Ring {
-            let mut latest = self.latest_sample();
-            let mut text = String::with_capacity(10);
-
-            let mut pct: u64 = ((latest / self.total_memory) * 100.0) as u64;
-            if pct > 100 {
-                pct = 100;
-            }
+    fn chart(
+        &'_ self,
+        height_hint: u16,
+        width_hint: u16,
+    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
 
-            // If set, convert to percentage
-            if self.config.percentage {
-                latest = (latest * 100.0) / self.total_memory;
+        // The allocated memory is a second bar or line in graph2
+        let allocated = self
+            .config
+            .show_allocated
+            .then_some(&*self.samples_allocated);
+        match self.config.chart {
+            ChartKind::Gauge => {
+                let mut fractions = vec![self.latest_sample() / self.total_memory];
+                if allocated.is_some() {
+                    fractions.push(self.latest_sample_allocated() / self.total_memory);
+                }
+                return super::gauge_container(
+                    &fractions,
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    self.display.canvas,
+                );
             }
-
-            if latest < 10.0 {
-                write!(text, "{latest:.2}").unwrap();
-            } else if latest <= 99.9 {
-                write!(text, "{latest:.1}").unwrap();
-            } else {
-                write!(text, "100").unwrap();
+            ChartKind::Sparkline => {
+                return super::sparkline_container(
+                    &self.samples_used,
+                    allocated,
+                    MAX_SAMPLES,
+                    self.total_memory,
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    self.display.canvas,
+                );
             }
-
-            chart_container!(crate::charts::ring::RingChart::new(
-                latest as f32,
-                &text,
-                &self.config.colors,
-            ))
-        } else {
-            chart_container!(crate::charts::line::LineChart::new(
-                MAX_SAMPLES,
-                &self.samples,
-                &VecDeque::new(),
-                Some(self.total_memory),
-                &self.config.colors,
-            ))
+            _ => (),
         }
-    }
 
-    #[cfg(not(feature = "lyon_charts"))]
-    fn chart(
-        &'_ self,
-        _height_hint: u16,
-        _width_hint: u16,
-    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let canvas = self.display.canvas;
         let svg = if self.config.chart == ChartKind::Ring {
             let mut latest = self.latest_sample();
             let mut value = String::with_capacity(10);
@@ -245,30 +265,75 @@ impl Sensor for Memory {
                 let _ = write!(value, "100");
             }
 
-            if self.config.show_allocated {
-                let mut pct_allocated: u64 =
+            let pct_allocated = self.config.show_allocated.then(|| {
+                let pct_allocated: u64 =
                     ((self.latest_sample_allocated() / self.total_memory) * 100.0) as u64;
-                if pct_allocated > 100 {
-                    pct_allocated = 100;
-                }
-                crate::svg_graph::ring(
+                pct_allocated.min(100) as u8
+            });
+
+            if canvas {
+                return super::canvas_container(RingChart::new(
                     &value,
                     pct as u8,
-                    Some(pct_allocated as u8),
+                    pct_allocated,
+                    self.config.colors(),
+                ));
+            }
+            crate::svg_graph::ring(&value, pct as u8, pct_allocated, &self.svg_colors)
+        } else {
+            let grid = self.config.grid_visible().then(|| {
+                if self.config.percentage {
+                    "100%".to_owned()
+                } else {
+                    let units = self.display.units;
+                    format!(
+                        "{}{}",
+                        units.from_gib(self.total_memory).round(),
+                        units.giga()
+                    )
+                }
+            });
+            let line = if self.config.show_allocated {
+                if canvas {
+                    return super::canvas_container(
+                        LineChart::stacked(
+                            &self.samples_used,
+                            &self.samples_allocated,
+                            self.total_memory,
+                            self.config.colors(),
+                        )
+                        .grid(grid),
+                    );
+                }
+                crate::svg_graph::line_stacked(
+                    &self.samples_used,
+                    &self.samples_allocated,
+                    self.total_memory,
                     &self.svg_colors,
+                    size,
                 )
             } else {
-                crate::svg_graph::ring(&value, pct as u8, None, &self.svg_colors)
-            }
-        } else if self.config.show_allocated {
-            crate::svg_graph::line_stacked(
-                &self.samples_used,
-                &self.samples_allocated,
+                if canvas {
+                    return super::canvas_container(
+                        LineChart::new(&self.samples_used, self.total_memory, self.config.colors())
+                            .grid(grid),
+                    );
+                }
+                crate::svg_graph::line(
+                    &self.samples_used,
+                    self.total_memory,
+                    &self.svg_colors,
+                    size,
+                )
+            };
+            crate::svg_graph::with_grid(
+                line,
                 self.total_memory,
+                false,
+                grid.as_deref(),
                 &self.svg_colors,
+                size,
             )
-        } else {
-            crate::svg_graph::line(&self.samples_used, self.total_memory, &self.svg_colors)
         };
         super::svg_icon_container::<Message>(svg)
     }
@@ -286,7 +351,12 @@ impl Sensor for Memory {
         );
 
         if self.config.show_allocated {
-            let allocated = format!("{:.1} GB", self.latest_sample_allocated());
+            let units = self.display.units;
+            let allocated = format!(
+                "{:.1} {}",
+                units.from_gib(self.latest_sample_allocated()),
+                units.giga()
+            );
             text = text.push(
                 cosmic::widget::text::body(allocated)
                     .width(90)
@@ -307,7 +377,8 @@ impl Sensor for Memory {
         ));
 
         let config = &self.config;
-        let selected: Option<usize> = Some(self.graph_kind().into());
+        let selected = self.graph_options.index(self.graph_kind());
+        let graph_options = self.graph_options;
         let mem_kind = self.graph_kind();
 
         let expl = widget::text::caption(fl!("allocated-explanation"));
@@ -343,10 +414,18 @@ impl Sensor for Memory {
                     fl!("memory-as-percentage"),
                     toggler(config.percentage).on_toggle(Message::ToggleMemoryPercentage),
                 ),
+                crate::template::settings_ui(&config.template, |t| {
+                    Message::TemplateChanged(DeviceKind::Memory, t)
+                }),
+                settings::item(
+                    fl!("show-grid"),
+                    toggler(config.grid_visible())
+                        .on_toggle(|t| Message::ToggleGrid(DeviceKind::Memory, t)),
+                ),
                 row!(
                     widget::text::body(fl!("chart-type")),
-                    widget::dropdown(&self.graph_options, selected, move |m| {
-                        Message::SelectGraphType(DeviceKind::Memory, m.into())
+                    widget::dropdown(graph_options.names(), selected, move |m| {
+                        Message::SelectGraphType(DeviceKind::Memory, graph_options.kind(m))
                     },)
                     .width(70),
                     widget::space::horizontal(),
@@ -372,7 +451,7 @@ impl Default for Memory {
 
         let total_memory: f64 = system.total_memory() as f64 / 1_073_741_824.0;
         log::info!(
-            "System memory: {} / {:.2} GB",
+            "System memory: {} / {:.2} GiB",
             system.total_memory(),
             total_memory
         );
@@ -396,8 +475,9 @@ impl Default for Memory {
             total_memory,
             system,
             config: MemoryConfig::default(),
-            graph_options: super::GRAPH_OPTIONS_RING_LINE.to_vec(),
+            graph_options: &super::GRAPH_OPTIONS_RING_LINE,
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
         };
         memory.set_colors(&ChartColors::default());
         memory
@@ -419,24 +499,24 @@ impl Memory {
 
     pub fn to_string(&self, vertical_panel: bool) -> String {
         let mut current_val = self.latest_sample();
-        let unit: &str;
-
-        if self.config.percentage {
+        let unit = if self.config.percentage {
             current_val = (current_val * 100.0) / self.total_memory;
-            unit = "%";
-        } else if !vertical_panel {
-            unit = " GB";
+            crate::locale::unit_symbol("%")
         } else {
-            unit = "GB";
-        }
+            let units = self.display.units;
+            current_val = units.from_gib(current_val);
+            let space = if vertical_panel { "" } else { " " };
+            format!("{space}{}", units.giga())
+        };
 
-        if current_val < 10.0 {
-            format!("{:.2}{unit}", (current_val * 100.0).trunc() / 100.0)
+        let value = if current_val < 10.0 {
+            crate::locale::format_number((current_val * 100.0).trunc() / 100.0, 2)
         } else if current_val < 100.0 {
-            format!("{:.1}{unit}", (current_val * 10.0).trunc() / 10.0)
+            crate::locale::format_number((current_val * 10.0).trunc() / 10.0, 1)
         } else {
-            format!("{}{unit}", current_val.round())
-        }
+            crate::locale::format_number(current_val.round(), 0)
+        };
+        format!("{value}{unit}")
     }
 }
 
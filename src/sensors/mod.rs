@@ -1,11 +1,13 @@
 use cosmic::{Element, Renderer, Theme};
 use cosmic::{iced::Length, widget::Container};
 use serde::{Deserialize, Serialize};
-use std::{collections::VecDeque, sync::LazyLock};
+use std::{borrow::Cow, collections::VecDeque, sync::LazyLock};
 
 use crate::{
-    config::{ColorVariant, GpuConfig},
+    charts::{gauge::GaugeChart, sleeping::SleepingChart, sparkline::SparklineChart},
+    config::{ChartColors, ColorVariant, GpuConfig, UnitSystem},
     fl,
+    svg_graph::{ChartSize, SvgColors},
 };
 
 const INVALID_IMG: &str = r#"
@@ -19,13 +21,6 @@ const INVALID_IMG: &str = r#"
   <line x1="18" y1="6" x2="6" y2="18" stroke-linecap="round"/>
 </svg>"#;
 
-#[cfg(feature = "lyon_charts")]
-macro_rules! chart_container {
-    ($chart:expr) => {
-        Container::new(cosmic::widget::Canvas::new($chart))
-    };
-}
-
 pub static COLOR_CHOICES_RING: LazyLock<[(&'static str, ColorVariant); 4]> = LazyLock::new(|| {
     [
         (fl!("graph-ring-r1").leak(), ColorVariant::Graph1),
@@ -43,8 +38,9 @@ pub static COLOR_CHOICES_LINE: LazyLock<[(&'static str, ColorVariant); 3]> = Laz
     ]
 });
 
-pub static COLOR_CHOICES_HEAT: LazyLock<[(&'static str, ColorVariant); 2]> = LazyLock::new(|| {
+pub static COLOR_CHOICES_HEAT: LazyLock<[(&'static str, ColorVariant); 3]> = LazyLock::new(|| {
     [
+        (fl!("graph-line-graph").leak(), ColorVariant::Graph1),
         (fl!("graph-line-back").leak(), ColorVariant::Background),
         (fl!("graph-line-frame").leak(), ColorVariant::Frame),
     ]
@@ -59,17 +55,77 @@ pub static UNIT_OPTIONS: LazyLock<[&'static str; 4]> = LazyLock::new(|| {
     ]
 });
 
-static GRAPH_OPTIONS_RING_LINE: LazyLock<[&'static str; 2]> =
-    LazyLock::new(|| [fl!("graph-type-ring").leak(), fl!("graph-type-line").leak()]);
+pub static COLOR_CHOICES_SPARKLINE: LazyLock<[(&'static str, ColorVariant); 1]> =
+    LazyLock::new(|| [(fl!("graph-line-graph").leak(), ColorVariant::Graph1)]);
 
-static GRAPH_OPTIONS_RING_LINE_HEAT: LazyLock<[&'static str; 3]> = LazyLock::new(|| {
-    [
-        fl!("graph-type-ring").leak(),
-        fl!("graph-type-line").leak(),
-        fl!("graph-type-heat").leak(),
-    ]
+/// The chart kinds a sensor offers in its settings dropdown, and their names
+#[derive(Debug)]
+pub struct GraphOptions {
+    kinds: &'static [ChartKind],
+    names: Vec<&'static str>,
+}
+
+impl GraphOptions {
+    fn new(kinds: &'static [ChartKind]) -> Self {
+        let names = kinds
+            .iter()
+            .map(|kind| match kind {
+                ChartKind::Ring => fl!("graph-type-ring").leak(),
+                ChartKind::Line => fl!("graph-type-line").leak(),
+                ChartKind::Heat => fl!("graph-type-heat").leak(),
+                ChartKind::StackedBars => fl!("graph-type-bars").leak(),
+                ChartKind::Gauge => fl!("graph-type-gauge").leak(),
+                ChartKind::Sparkline => fl!("graph-type-sparkline").leak(),
+            })
+            .collect();
+        GraphOptions { kinds, names }
+    }
+
+    pub fn names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    /// Dropdown index of `kind`, if it is offered
+    pub fn index(&self, kind: ChartKind) -> Option<usize> {
+        self.kinds.iter().position(|k| *k == kind)
+    }
+
+    /// The kind at a dropdown index
+    pub fn kind(&self, index: usize) -> ChartKind {
+        self.kinds.get(index).copied().unwrap_or_else(|| {
+            log::error!("GraphOptions::kind({index}) Invalid index");
+            self.kinds[0]
+        })
+    }
+
+    pub fn contains(&self, kind: ChartKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+}
+
+static GRAPH_OPTIONS_RING_LINE: LazyLock<GraphOptions> = LazyLock::new(|| {
+    GraphOptions::new(&[
+        ChartKind::Ring,
+        ChartKind::Line,
+        ChartKind::Gauge,
+        ChartKind::Sparkline,
+    ])
+});
+
+static GRAPH_OPTIONS_RING_LINE_HEAT: LazyLock<GraphOptions> = LazyLock::new(|| {
+    GraphOptions::new(&[
+        ChartKind::Ring,
+        ChartKind::Line,
+        ChartKind::Heat,
+        ChartKind::Gauge,
+        ChartKind::Sparkline,
+    ])
 });
 
+// Network and disks rates have no upper bound for a ring
+static GRAPH_OPTIONS_LINE: LazyLock<GraphOptions> =
+    LazyLock::new(|| GraphOptions::new(&[ChartKind::Line, ChartKind::Gauge, ChartKind::Sparkline]));
+
 use crate::{colorpicker::DemoGraph, config::ChartKind};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -80,6 +136,30 @@ pub enum TempUnit {
     Rankine,
 }
 
+impl TempUnit {
+    /// A temperature in Celsius converted to this unit
+    pub fn convert(self, celsius: f64) -> f64 {
+        match self {
+            TempUnit::Celsius => celsius,
+            TempUnit::Farenheit => celsius * 9.0 / 5.0 + 32.0,
+            TempUnit::Kelvin => celsius + 273.15,
+            TempUnit::Rankine => celsius * 9.0 / 5.0 + 491.67,
+        }
+    }
+
+    /// A temperature in Celsius shown in this unit, in whole degrees
+    pub fn label(self, celsius: f64) -> String {
+        let value = crate::locale::format_number(self.convert(celsius).trunc(), 0);
+        let symbol = match self {
+            TempUnit::Celsius => "°C",
+            TempUnit::Farenheit => "°F",
+            TempUnit::Kelvin => "K",
+            TempUnit::Rankine => "°R",
+        };
+        format!("{value}{}", crate::locale::unit_symbol(symbol))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CpuVariant {
     Amd,
@@ -87,8 +167,17 @@ pub enum CpuVariant {
 }
 
 use std::any::Any;
+/// Display settings shared by every sensor, handed over with its config
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Draw the charts on a canvas instead of as SVG images
+    pub canvas: bool,
+    /// Prefixes of the sizes and rates
+    pub units: UnitSystem,
+}
+
 pub trait Sensor {
-    fn update_config(&mut self, config: &dyn Any, refresh_rate: u32);
+    fn update_config(&mut self, config: &dyn Any, refresh_rate: u32, display: DisplayOptions);
     fn graph_kind(&self) -> ChartKind;
     fn set_graph_kind(&mut self, kind: ChartKind);
     fn update(&mut self);
@@ -101,6 +190,7 @@ pub trait Sensor {
     fn settings_ui(&'_ self) -> Element<'_, crate::app::Message>;
 }
 
+pub mod battery;
 pub mod cpu;
 pub mod cputemp;
 pub mod disks;
@@ -108,6 +198,7 @@ pub mod gpu;
 pub mod gpus;
 pub mod memory;
 pub mod network;
+pub mod power;
 
 impl From<usize> for TempUnit {
     fn from(index: usize) -> Self {
@@ -141,11 +232,159 @@ pub fn svg_icon_container<'a, Message>(
 where
     Message: 'a,
 {
-    let icon = cosmic::widget::icon::from_svg_bytes(svg.as_bytes().to_vec()).symbolic(false);
+    let icon = crate::render_cache::cached_icon(&svg);
 
     Container::new(icon.icon().height(Length::Fill).width(Length::Fill))
 }
 
+/// A chart drawn by one of the canvas programs in `charts`
+pub fn canvas_container<'a, P>(
+    chart: P,
+) -> cosmic::widget::Container<'a, crate::app::Message, Theme, Renderer>
+where
+    P: cosmic::widget::canvas::Program<crate::app::Message, Theme> + 'a,
+{
+    Container::new(
+        cosmic::widget::Canvas::new(chart)
+            .height(Length::Fill)
+            .width(Length::Fill),
+    )
+}
+
+/// A gauge of `fractions`, 0.0 to 1.0, drawn on a canvas or as an SVG
+fn gauge_container<'a>(
+    fractions: &[f64],
+    colors: &ChartColors,
+    svg_colors: &SvgColors,
+    size: ChartSize,
+    canvas: bool,
+) -> cosmic::widget::Container<'a, crate::app::Message, Theme, Renderer> {
+    if canvas {
+        return canvas_container(GaugeChart::new(fractions, colors));
+    }
+    svg_icon_container::<crate::app::Message>(crate::svg_graph::gauge(fractions, svg_colors, size))
+}
+
+/// A sleeping GPU in the shape of a ring or the other charts, drawn on a canvas or as an SVG
+fn sleeping_container<'a>(
+    ring: bool,
+    colors: &ChartColors,
+    svg_colors: &SvgColors,
+    size: ChartSize,
+    canvas: bool,
+) -> cosmic::widget::Container<'a, crate::app::Message, Theme, Renderer> {
+    if canvas {
+        return canvas_container(SleepingChart::new(ring, colors));
+    }
+    svg_icon_container::<crate::app::Message>(crate::svg_graph::sleeping(ring, svg_colors, size))
+}
+
+/// A sparkline of the last `steps` samples, drawn on a canvas or as an SVG
+#[allow(clippy::too_many_arguments)]
+fn sparkline_container<'a>(
+    samples: &VecDeque<f64>,
+    samples2: Option<&VecDeque<f64>>,
+    steps: usize,
+    max_y: f64,
+    colors: &ChartColors,
+    svg_colors: &SvgColors,
+    size: ChartSize,
+    canvas: bool,
+) -> cosmic::widget::Container<'a, crate::app::Message, Theme, Renderer> {
+    if canvas {
+        return canvas_container(SparklineChart::new(samples, samples2, steps, max_y, colors));
+    }
+    svg_icon_container::<crate::app::Message>(crate::svg_graph::sparkline(
+        samples, samples2, steps, max_y, svg_colors, size,
+    ))
+}
+
+/// A gauge or sparkline of transfer rates, `second` adds the other direction.
+/// Without a configured `max_y` both scale to the recent peak, like the adaptive line.
+#[allow(clippy::too_many_arguments)]
+fn rate_container<'a>(
+    kind: ChartKind,
+    first: &VecDeque<u64>,
+    second: Option<&VecDeque<u64>>,
+    steps: usize,
+    max_y: Option<u64>,
+    colors: &ChartColors,
+    svg_colors: &SvgColors,
+    size: ChartSize,
+    canvas: bool,
+) -> cosmic::widget::Container<'a, crate::app::Message, Theme, Renderer> {
+    let max = max_y.unwrap_or_else(|| recent_max(first, second, steps)) as f64;
+
+    if kind == ChartKind::Gauge {
+        let latest = |samples: &VecDeque<u64>| samples.back().copied().unwrap_or(0) as f64 / max;
+        let mut fractions = vec![latest(first)];
+        fractions.extend(second.map(latest));
+        return gauge_container(&fractions, colors, svg_colors, size, canvas);
+    }
+
+    let to_f64 = |samples: &VecDeque<u64>| samples.iter().map(|&s| s as f64).collect();
+    let first: VecDeque<f64> = to_f64(first);
+    let second: Option<VecDeque<f64>> = second.map(to_f64);
+    sparkline_container(
+        &first,
+        second.as_ref(),
+        steps,
+        max,
+        colors,
+        svg_colors,
+        size,
+        canvas,
+    )
+}
+
+// Adaptive rate charts never scale below this
+const MIN_RATE_MAX: u64 = 40;
+
+// Seconds for a sticky peak to fall to half, the same at any refresh rate
+const PEAK_HALF_LIFE: f64 = 13.5;
+
+/// The largest of the last `steps` rates, the scale of an adaptive chart
+fn recent_max(first: &VecDeque<u64>, second: Option<&VecDeque<u64>>, steps: usize) -> u64 {
+    let recent = |samples: &VecDeque<u64>| {
+        samples
+            .iter()
+            .skip(samples.len().saturating_sub(steps))
+            .copied()
+            .max()
+            .unwrap_or(0)
+    };
+    recent(first)
+        .max(second.map(recent).unwrap_or(0))
+        .max(MIN_RATE_MAX)
+}
+
+/// A scale that follows the rates up at once but falls back slowly after a peak,
+/// `refresh_rate` is the time since the last update in milliseconds
+fn decayed_peak(
+    peak: u64,
+    first: &VecDeque<u64>,
+    second: Option<&VecDeque<u64>>,
+    steps: usize,
+    refresh_rate: u32,
+) -> u64 {
+    let decayed = (peak as f64 * peak_decay(refresh_rate)) as u64;
+    recent_max(first, second, steps).max(decayed)
+}
+
+// How much of a sticky peak is left after `refresh_rate` milliseconds
+fn peak_decay(refresh_rate: u32) -> f64 {
+    0.5_f64.powf(f64::from(refresh_rate) / 1000.0 / PEAK_HALF_LIFE)
+}
+
+/// The samples scaled to 0..100 above `floor`, or as they are without a floor
+fn temps_above_floor(samples: &VecDeque<f64>, floor: f64) -> Cow<'_, VecDeque<f64>> {
+    if floor == 0.0 {
+        Cow::Borrowed(samples)
+    } else {
+        Cow::Owned(normalize_temps_dynamic(samples, floor))
+    }
+}
+
 fn normalize_temps_dynamic(samples: &VecDeque<f64>, floor: f64) -> VecDeque<f64> {
     // Find the maximum value in the samples; if empty, just return empty.
     let Some(&max_sample) = samples.iter().max_by(|a, b| a.partial_cmp(b).unwrap()) else {
@@ -174,3 +413,47 @@ fn normalize_temps_dynamic(samples: &VecDeque<f64>, floor: f64) -> VecDeque<f64>
         })
         .collect()
 }
+
+/// The temperature at the top of a chart of `temps_above_floor` scaled to `max`
+fn temp_chart_top(samples: &VecDeque<f64>, floor: f64, max: f64) -> f64 {
+    if floor == 0.0 {
+        return max;
+    }
+    let max_sample = samples.iter().copied().fold(f64::MIN, f64::max);
+    let ceiling = max_sample.max(floor).max(100.0);
+    floor + (ceiling - floor) * max / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peak_halves_in_the_same_time_at_any_refresh_rate() {
+        for refresh_rate in [250, 1000, 2000, 5000] {
+            let updates = (PEAK_HALF_LIFE * 1000.0 / f64::from(refresh_rate)).round() as i32;
+            let left = peak_decay(refresh_rate).powi(updates);
+            assert!((left - 0.5).abs() < 0.02, "{refresh_rate} ms: {left}");
+        }
+    }
+
+    #[test]
+    fn peak_follows_up_at_once_and_falls_slowly() {
+        let quiet: VecDeque<u64> = std::iter::repeat_n(100, 30).collect();
+        let mut burst = quiet.clone();
+        burst.push_back(10_000);
+
+        let peak = decayed_peak(0, &burst, None, 21, 1000);
+        assert_eq!(peak, 10_000);
+
+        let peak = decayed_peak(peak, &quiet, None, 21, 1000);
+        assert!(peak < 10_000 && peak > 9_000);
+
+        // Never below the recent rates or the minimum
+        assert_eq!(decayed_peak(0, &quiet, None, 21, 1000), 100);
+        assert_eq!(
+            decayed_peak(0, &VecDeque::new(), None, 21, 1000),
+            MIN_RATE_MAX
+        );
+    }
+}
@@ -1,14 +1,16 @@
 use bounded_vec_deque::BoundedVecDeque;
 
 use cosmic::{Element, widget::Column, widget::Container, widget::Row};
-use log::info;
 use sysinfo::Networks;
 
 use crate::{
+    charts::line::LineChart,
     colorpicker::DemoGraph,
-    config::{ChartColors, ChartKind, ColorVariant, DeviceKind, NetworkConfig, NetworkVariant},
+    config::{
+        ChartColors, ChartKind, ColorVariant, DeviceKind, NetworkConfig, NetworkVariant, UnitSystem,
+    },
     fl,
-    svg_graph::SvgColors,
+    svg_graph::{ChartSize, SvgColors},
 };
 
 use cosmic::widget;
@@ -20,15 +22,12 @@ use cosmic::iced::{
     widget::{column, row},
 };
 use std::any::Any;
+use std::collections::VecDeque;
 
-use super::Sensor;
+use super::{DisplayOptions, Sensor};
 
 const MAX_SAMPLES: usize = 30;
 const GRAPH_SAMPLES: usize = 21;
-const UNITS_SHORT: [&str; 5] = ["b", "K", "M", "G", "T"];
-const UNITS_LONG: [&str; 5] = ["bps", "Kbps", "Mbps", "Gbps", "Tbps"];
-const UNITS_SHORT_BYTES: [&str; 5] = ["B", "K", "M", "G", "T"];
-const UNITS_LONG_BYTES: [&str; 5] = ["B/s", "KB/s", "MB/s", "GB/s", "TB/s"];
 
 use std::sync::LazyLock;
 
@@ -58,11 +57,8 @@ pub static COLOR_CHOICES_UL: LazyLock<[(&'static str, ColorVariant); 3]> = LazyL
     ]
 });
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum UnitVariant {
-    Short,
-    Long,
-}
+use crate::units::Quantity;
+pub use crate::units::UnitVariant;
 
 #[derive(Debug)]
 pub struct Network {
@@ -70,7 +66,11 @@ pub struct Network {
     download: BoundedVecDeque<u64>,
     upload: BoundedVecDeque<u64>,
     max_y: Option<u64>,
+    // Scale of the adaptive chart with a sticky peak
+    peak: u64,
     svg_colors: SvgColors,
+    display: DisplayOptions,
+    graph_options: &'static super::GraphOptions,
     dropdown_options: Vec<&'static str>,
     config: NetworkConfig,
     refresh_rate: u32,
@@ -81,6 +81,10 @@ impl DemoGraph for Network {
         let download = std::collections::VecDeque::from(DL_DEMO);
         let upload = std::collections::VecDeque::from(UL_DEMO);
 
+        if matches!(self.config.chart, ChartKind::Gauge | ChartKind::Sparkline) {
+            return self.demo_rates(&download, &upload);
+        }
+
         match self.config.variant {
             NetworkVariant::Combined => crate::svg_graph::double_line(
                 &download,
@@ -88,14 +92,28 @@ impl DemoGraph for Network {
                 GRAPH_SAMPLES,
                 &self.svg_colors,
                 None,
+                self.config.log_scale,
+                ChartSize::SQUARE,
+            ),
+            NetworkVariant::Download => crate::svg_graph::line_adaptive(
+                &download,
+                GRAPH_SAMPLES,
+                &self.svg_colors,
+                None,
+                self.config.log_scale,
+                ChartSize::SQUARE,
             ),
-            NetworkVariant::Download => {
-                crate::svg_graph::line_adaptive(&download, GRAPH_SAMPLES, &self.svg_colors, None)
-            }
             NetworkVariant::Upload => {
                 let mut cols = self.svg_colors.clone();
                 cols.graph1 = cols.graph2.clone();
-                crate::svg_graph::line_adaptive(&upload, GRAPH_SAMPLES, &cols, None)
+                crate::svg_graph::line_adaptive(
+                    &upload,
+                    GRAPH_SAMPLES,
+                    &cols,
+                    None,
+                    self.config.log_scale,
+                    ChartSize::SQUARE,
+                )
             }
         }
     }
@@ -110,11 +128,18 @@ impl DemoGraph for Network {
     }
 
     fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
-        match self.config.variant {
+        let mut choices: Vec<(&'static str, ColorVariant)> = match self.config.variant {
             NetworkVariant::Combined => (*COLOR_CHOICES_COMBINED).into(),
             NetworkVariant::Download => (*COLOR_CHOICES_DL).into(),
             NetworkVariant::Upload => (*COLOR_CHOICES_UL).into(),
+        };
+        // Sparklines have no background or frame
+        if self.config.chart == ChartKind::Sparkline {
+            choices.retain(|(_, variant)| {
+                matches!(variant, ColorVariant::Graph1 | ColorVariant::Graph2)
+            });
         }
+        choices
     }
 
     fn id(&self) -> Option<String> {
@@ -127,36 +152,44 @@ impl DemoGraph for Network {
 }
 
 impl Sensor for Network {
-    fn update_config(&mut self, config: &dyn Any, refresh_rate: u32) {
+    fn update_config(&mut self, config: &dyn Any, refresh_rate: u32, display: DisplayOptions) {
         if let Some(cfg) = config.downcast_ref::<NetworkConfig>() {
             self.config = cfg.clone();
+            self.display = display;
             self.svg_colors.set_colors(cfg.colors());
             self.refresh_rate = refresh_rate;
 
-            if self.config.show_bytes {
-                self.dropdown_options = ["b", "Kb", "Mb", "Gb", "Tb"].into();
+            if !cfg.sticky_peak {
+                self.peak = 0;
+            }
+
+            // The bandwidth is entered in the quantity the values are shown in
+            let system = self.display.units;
+            let (quantity, bits) = if self.config.show_bytes {
+                (Quantity::Bytes, 8)
             } else {
-                self.dropdown_options = ["B", "KB", "MB", "GB", "TB"].into();
+                (Quantity::Bits, 1)
             };
+            self.dropdown_options = system.unit_names(quantity).to_vec();
 
             if cfg.adaptive {
                 self.max_y = None;
             } else {
                 let unit = cfg.unit.unwrap_or(1).min(4); // ensure safe index
-                let multiplier = [1, 1_000, 1_000_000, 1_000_000_000, 1_000_000_000_000];
                 let sec_per_tic = refresh_rate as f64 / 1000.0;
-                let new_y = (cfg.bandwidth * multiplier[unit]) as f64 * sec_per_tic;
+                let new_y = (cfg.bandwidth * system.multiplier(unit) * bits) as f64 * sec_per_tic;
                 self.max_y = Some(new_y.round() as u64);
             }
         }
     }
 
     fn graph_kind(&self) -> ChartKind {
-        ChartKind::Line
+        self.config.chart
     }
 
     fn set_graph_kind(&mut self, kind: ChartKind) {
-        assert!(kind == ChartKind::Line);
+        assert!(self.graph_options.contains(kind));
+        self.config.chart = kind;
     }
 
     /// Retrieve the amount of data transmitted since last update.
@@ -171,85 +204,114 @@ impl Sensor for Network {
         }
         self.download.push_back(dl);
         self.upload.push_back(ul);
+
+        if self.config.sticky_peak {
+            let (samples, samples2) = self.shown_samples();
+            self.peak = super::decayed_peak(
+                self.peak,
+                samples,
+                samples2,
+                GRAPH_SAMPLES,
+                self.refresh_rate,
+            );
+        }
     }
 
     fn demo_graph(&self) -> Box<dyn DemoGraph> {
         let mut dmo = Network::default();
-        dmo.update_config(&self.config, self.refresh_rate);
+        dmo.update_config(&self.config, self.refresh_rate, self.display);
         Box::new(dmo)
     }
 
-    #[cfg(feature = "lyon_charts")]
     fn chart(
-        &self,
-    ) -> cosmic::widget::Container<crate::app::Message, cosmic::Theme, cosmic::Renderer> {
-        let mut colors = self.config.colors;
-        match self.config.variant {
-            NetworkVariant::Combined => {
-                //A bit awkward, but to maintain compatibility with the SVG charts
-                colors.color4 = self.config.colors.color2;
-                colors.color2 = self.config.colors.color4;
-                chart_container!(crate::charts::line::LineChart::new(
-                    GRAPH_SAMPLES,
-                    &self.download,
-                    &self.upload,
-                    self.max_y,
-                    &colors,
-                ))
-            }
-            NetworkVariant::Download => {
-                //A bit awkward, but to maintain compatibility with the SVG charts
-                colors.color4 = self.config.colors.color2;
-                colors.color2 = self.config.colors.color4;
-                chart_container!(crate::charts::line::LineChart::new(
-                    GRAPH_SAMPLES,
-                    &self.download,
-                    &VecDeque::new(),
-                    self.max_y,
-                    &colors,
-                ))
-            }
-            NetworkVariant::Upload => {
-                //A bit awkward, but to maintain compatibility with the SVG charts
-                colors.color4 = self.config.colors.color3;
-                colors.color2 = self.config.colors.color4;
-                chart_container!(crate::charts::line::LineChart::new(
-                    GRAPH_SAMPLES,
-                    &self.upload,
-                    &VecDeque::new(),
-                    self.max_y,
-                    &colors,
-                ))
+        &'_ self,
+        height_hint: u16,
+        width_hint: u16,
+    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
+        let max_y = self.scale_max();
+        let log_scale = self.config.log_scale;
+        if matches!(self.config.chart, ChartKind::Gauge | ChartKind::Sparkline) {
+            let mut colors = *self.config.colors();
+            let mut svg_colors = self.svg_colors.clone();
+            if self.config.variant == NetworkVariant::Upload {
+                colors.graph1 = colors.graph2;
+                svg_colors.graph1 = svg_colors.graph2.clone();
             }
+            let (samples, samples2) = self.shown_samples();
+            return super::rate_container(
+                self.config.chart,
+                samples,
+                samples2,
+                GRAPH_SAMPLES,
+                max_y,
+                &colors,
+                &svg_colors,
+                size,
+                self.display.canvas,
+            );
+        }
+        // Both renderers scale to the samples they show
+        let (samples, samples2) = self.shown_samples();
+        let top = max_y.unwrap_or_else(|| super::recent_max(samples, samples2, GRAPH_SAMPLES));
+
+        if self.display.canvas {
+            let colors = self.config.colors();
+            let chart = match self.config.variant {
+                NetworkVariant::Combined => {
+                    LineChart::double(&self.download, &self.upload, GRAPH_SAMPLES, colors, max_y)
+                }
+                NetworkVariant::Download => {
+                    LineChart::adaptive(&self.download, GRAPH_SAMPLES, colors, max_y)
+                }
+                NetworkVariant::Upload => {
+                    let mut cols = *colors;
+                    cols.graph1 = cols.graph2;
+                    LineChart::adaptive(&self.upload, GRAPH_SAMPLES, &cols, max_y)
+                }
+            };
+            return super::canvas_container(chart.log_scale(log_scale).grid(self.grid_label(top)));
         }
-    }
 
-    #[cfg(not(feature = "lyon_charts"))]
-    fn chart(
-        &'_ self,
-        _height_hint: u16,
-        _width_hint: u16,
-    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
         let svg = match self.config.variant {
             NetworkVariant::Combined => crate::svg_graph::double_line(
                 &self.download,
                 &self.upload,
                 GRAPH_SAMPLES,
                 &self.svg_colors,
-                self.max_y,
+                max_y,
+                log_scale,
+                size,
             ),
             NetworkVariant::Download => crate::svg_graph::line_adaptive(
                 &self.download,
                 GRAPH_SAMPLES,
                 &self.svg_colors,
-                self.max_y,
+                max_y,
+                log_scale,
+                size,
             ),
             NetworkVariant::Upload => {
                 let mut cols = self.svg_colors.clone();
                 cols.graph1 = cols.graph2.clone();
-                crate::svg_graph::line_adaptive(&self.upload, GRAPH_SAMPLES, &cols, self.max_y)
+                crate::svg_graph::line_adaptive(
+                    &self.upload,
+                    GRAPH_SAMPLES,
+                    &cols,
+                    max_y,
+                    log_scale,
+                    size,
+                )
             }
         };
+        let svg = crate::svg_graph::with_grid(
+            svg,
+            top as f64,
+            log_scale,
+            self.grid_label(top).as_deref(),
+            &self.svg_colors,
+            size,
+        );
         super::svg_icon_container::<Message>(svg)
     }
 
@@ -329,6 +391,35 @@ impl Sensor for Network {
             )
             .into(),
         );
+        // The template covers the whole panel item, it is kept with the first config
+        if k != NetworkVariant::Upload {
+            net_bandwidth_items.push(crate::template::settings_ui(&config.template, move |t| {
+                Message::TemplateChanged(DeviceKind::Network(k), t)
+            }));
+        }
+        net_bandwidth_items.push(
+            settings::item(
+                fl!("show-grid"),
+                widget::toggler(config.grid_visible())
+                    .on_toggle(move |t| Message::ToggleGrid(DeviceKind::Network(k), t)),
+            )
+            .into(),
+        );
+        let graph_options = self.graph_options;
+        net_bandwidth_items.push(
+            settings::item(
+                fl!("chart-type"),
+                widget::dropdown(
+                    graph_options.names(),
+                    graph_options.index(config.chart),
+                    move |m| {
+                        Message::SelectGraphType(DeviceKind::Network(k), graph_options.kind(m))
+                    },
+                )
+                .width(90),
+            )
+            .into(),
+        );
         net_bandwidth_items.push(
             settings::item(
                 fl!("use-adaptive"),
@@ -340,7 +431,18 @@ impl Sensor for Network {
             .into(),
         );
 
-        if !config.adaptive {
+        if config.adaptive {
+            net_bandwidth_items.push(
+                settings::item(
+                    fl!("sticky-peak"),
+                    row!(
+                        widget::checkbox(config.sticky_peak)
+                            .on_toggle(move |t| Message::ToggleNetStickyPeak(k, t))
+                    ),
+                )
+                .into(),
+            );
+        } else {
             net_bandwidth_items.push(
                 settings::item(
                     fl!("net-bandwidth"),
@@ -358,12 +460,23 @@ impl Sensor for Network {
             );
         }
 
+        net_bandwidth_items.push(
+            settings::item(
+                fl!("log-scale"),
+                row!(
+                    widget::checkbox(config.log_scale)
+                        .on_toggle(move |t| Message::ToggleNetLogScale(k, t))
+                ),
+            )
+            .into(),
+        );
+
         net_bandwidth_items.push(
             row!(
                 widget::space::horizontal(),
                 widget::button::standard(fl!("change-colors")).on_press(Message::ColorPickerOpen(
                     DeviceKind::Network(self.config.variant),
-                    ChartKind::Line,
+                    self.config.chart,
                     None
                 )),
                 widget::space::horizontal()
@@ -395,6 +508,71 @@ impl Sensor for Network {
     }
 }
 
+impl Network {
+    /// The rates the variant shows, the second one only when combined
+    fn shown_samples(&self) -> (&VecDeque<u64>, Option<&VecDeque<u64>>) {
+        match self.config.variant {
+            NetworkVariant::Combined => (&*self.download, Some(&*self.upload)),
+            NetworkVariant::Download => (&*self.download, None),
+            NetworkVariant::Upload => (&*self.upload, None),
+        }
+    }
+
+    /// Label for the top of a line chart scaled to `max`, as a rate per second
+    fn grid_label(&self, max: u64) -> Option<String> {
+        self.config.grid_visible().then(|| {
+            let per_second = max as f64 * 1000.0 / self.refresh_rate.max(1) as f64;
+            self.makestr(
+                per_second as u64,
+                UnitVariant::Short,
+                self.config.show_bytes,
+            )
+        })
+    }
+
+    /// The fixed bandwidth, or the sticky peak of the adaptive scale
+    fn scale_max(&self) -> Option<u64> {
+        // Until the first update there is no peak yet
+        let peak = Some(self.peak).filter(|&peak| self.config.sticky_peak && peak > 0);
+        self.max_y.or(peak)
+    }
+
+    /// Gauge and sparkline demos of the rates, in the colors of the variant
+    fn demo_rates(
+        &self,
+        download: &std::collections::VecDeque<u64>,
+        upload: &std::collections::VecDeque<u64>,
+    ) -> String {
+        let mut cols = self.svg_colors.clone();
+        let (samples, samples2) = match self.config.variant {
+            NetworkVariant::Combined => (download, Some(upload)),
+            NetworkVariant::Download => (download, None),
+            NetworkVariant::Upload => {
+                cols.graph1 = cols.graph2.clone();
+                (upload, None)
+            }
+        };
+        let max = super::recent_max(samples, samples2, GRAPH_SAMPLES) as f64;
+        if self.config.chart == ChartKind::Gauge {
+            let latest =
+                |s: &std::collections::VecDeque<u64>| s.back().copied().unwrap_or(0) as f64 / max;
+            let mut fractions = vec![latest(samples)];
+            fractions.extend(samples2.map(latest));
+            return crate::svg_graph::gauge(&fractions, &cols, ChartSize::SQUARE);
+        }
+        let to_f64 = |s: &std::collections::VecDeque<u64>| s.iter().map(|&v| v as f64).collect();
+        let samples2: Option<std::collections::VecDeque<f64>> = samples2.map(to_f64);
+        crate::svg_graph::sparkline(
+            &to_f64(samples),
+            samples2.as_ref(),
+            GRAPH_SAMPLES,
+            max,
+            &cols,
+            ChartSize::SQUARE,
+        )
+    }
+}
+
 impl Default for Network {
     fn default() -> Self {
         let networks = Networks::new_with_refreshed_list();
@@ -403,8 +581,11 @@ impl Default for Network {
             download: BoundedVecDeque::from_iter(std::iter::repeat_n(0, MAX_SAMPLES), MAX_SAMPLES),
             upload: BoundedVecDeque::from_iter(std::iter::repeat_n(0, MAX_SAMPLES), MAX_SAMPLES),
             max_y: None,
-            dropdown_options: ["b", "Kb", "Mb", "Gb", "Tb"].into(),
+            peak: 0,
+            dropdown_options: UnitSystem::default().unit_names(Quantity::Bits).to_vec(),
             svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
+            graph_options: &super::GRAPH_OPTIONS_LINE,
             config: NetworkConfig::default(),
             refresh_rate: 1000,
         }
@@ -412,61 +593,13 @@ impl Default for Network {
 }
 
 impl Network {
-    fn makestr(val: u64, format: UnitVariant, show_bytes: bool) -> String {
-        let mut value = val as f64;
-
+    fn makestr(&self, val: u64, format: UnitVariant, show_bytes: bool) -> String {
+        // Samples are in bits
         if show_bytes {
-            value /= 8.0;
-        }
-
-        let mut unit_index = 0;
-
-        let units = match (show_bytes, format) {
-            (false, UnitVariant::Short) => UNITS_SHORT,
-            (false, UnitVariant::Long) => UNITS_LONG,
-            (true, UnitVariant::Short) => UNITS_SHORT_BYTES,
-            (true, UnitVariant::Long) => UNITS_LONG_BYTES,
-        };
-
-        // Scale the value to the appropriate unit
-        while value >= 999.0 && unit_index < units.len() - 1 {
-            value /= 1000.0;
-            unit_index += 1;
-        }
-
-        // Format the number with varying precision, prevent the formatter from rounding up
-        let mut value_str = if value < 10.0 {
-            format!("{:.2}", (value * 100.0).trunc() / 100.0)
-        } else if value < 100.0 {
-            format!("{:.1}", (value * 10.0).trunc() / 10.0)
+            self.display.units.rate(val / 8, Quantity::Bytes, format)
         } else {
-            format!("{:.0}", value.trunc())
-        };
-
-        // This happens when value is something like 9.9543456789908765453456 and it's rounded up to 10.
-        if value_str.len() == 5 {
-            info!("Value: {value}. formatted: {value:.2}. string: {value_str}");
-            value_str.pop();
-        }
-
-        let unit_str = units[unit_index];
-        let mut result = String::with_capacity(20);
-        result.push_str(&value_str);
-
-        if format == UnitVariant::Long {
-            result.push(' ');
+            self.display.units.rate(val, Quantity::Bits, format)
         }
-
-        result.push_str(unit_str);
-
-        if format == UnitVariant::Long {
-            let padding = 9usize.saturating_sub(result.len());
-            if padding > 0 {
-                result = " ".repeat(padding) + &result;
-            }
-        }
-
-        result
     }
 
     // If the sample rate doesn't match exactly one second (more or less),
@@ -494,13 +627,13 @@ impl Network {
     // Get bits per second
     pub fn download_label(&self, sample_interval_ms: u32, format: UnitVariant) -> String {
         let rate = Network::last_second_bitrate(&self.download, sample_interval_ms);
-        Network::makestr(rate, format, self.config.show_bytes)
+        self.makestr(rate, format, self.config.show_bytes)
     }
 
     // Get bits per second
     pub fn upload_label(&self, sample_interval_ms: u32, format: UnitVariant) -> String {
         let rate = Network::last_second_bitrate(&self.upload, sample_interval_ms);
-        Network::makestr(rate, format, self.config.show_bytes)
+        self.makestr(rate, format, self.config.show_bytes)
     }
 }
 
@@ -0,0 +1,633 @@
+use cosmic::{Element, iced::Alignment::Center, widget::Container};
+
+use crate::{
+    charts::{line::LineChart, ring::RingChart},
+    colorpicker::DemoGraph,
+    config::{ChartColors, ChartKind, ColorVariant, DeviceKind, PowerConfig, PowerVariant},
+    fl,
+    sensors::INVALID_IMG,
+    svg_graph::{ChartSize, SvgColors},
+};
+
+use cosmic::widget;
+use cosmic::widget::{settings, toggler};
+use std::any::Any;
+
+use cosmic::{
+    iced::{
+        Alignment,
+        widget::{column, row},
+    },
+    widget::Row,
+};
+use log::info;
+
+use crate::app::Message;
+
+use bounded_vec_deque::BoundedVecDeque;
+use std::fmt::Write;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Instant,
+};
+
+use super::{DisplayOptions, Sensor};
+
+const MAX_SAMPLES: usize = 21;
+
+// Lowest full scale of the charts when no max is configured
+const ADAPTIVE_MIN_WATTS: f64 = 10.0;
+
+pub static POWER_VARIANT_OPTIONS: LazyLock<[&'static str; 4]> = LazyLock::new(|| {
+    [
+        fl!("power-total").leak(),
+        fl!("power-cpu").leak(),
+        fl!("power-gpu").leak(),
+        fl!("power-battery").leak(),
+    ]
+});
+
+impl From<usize> for PowerVariant {
+    fn from(index: usize) -> Self {
+        match index {
+            0 => PowerVariant::Total,
+            1 => PowerVariant::Cpu,
+            2 => PowerVariant::Gpu,
+            3 => PowerVariant::Battery,
+            _ => {
+                log::error!("Invalid index for PowerVariant");
+                PowerVariant::Total
+            }
+        }
+    }
+}
+
+impl From<PowerVariant> for usize {
+    fn from(variant: PowerVariant) -> Self {
+        match variant {
+            PowerVariant::Total => 0,
+            PowerVariant::Cpu => 1,
+            PowerVariant::Gpu => 2,
+            PowerVariant::Battery => 3,
+        }
+    }
+}
+
+/// A RAPL package domain under /sys/class/powercap
+#[derive(Debug)]
+struct RaplDomain {
+    energy_path: PathBuf,
+    max_energy_range_uj: u64,
+    last: Option<(u64, Instant)>,
+}
+
+impl RaplDomain {
+    /// Find the top level package domains. Sub-zones like core/uncore/dram are already
+    /// included in the package counter so they are skipped.
+    fn find_domains() -> Vec<RaplDomain> {
+        info!("Find RAPL power domains");
+        let mut domains = Vec::new();
+
+        let Ok(entries) = fs::read_dir("/sys/class/powercap") else {
+            info!("  powercap not available");
+            return domains;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(zone) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+
+            // intel-rapl:0 is a package, intel-rapl:0:0 is a sub-zone. AMD uses the same naming.
+            if !zone.starts_with("intel-rapl:") || zone.matches(':').count() != 1 {
+                continue;
+            }
+
+            let name = read_trimmed(path.join("name")).unwrap_or_default();
+            if !name.starts_with("package") {
+                info!("  skipping {zone} ({name})");
+                continue;
+            }
+
+            let energy_path = path.join("energy_uj");
+            // energy_uj is root only on many distributions
+            if read_u64(&energy_path).is_none() {
+                info!("  {zone} ({name}) energy counter not readable");
+                continue;
+            }
+
+            let max_energy_range_uj = read_u64(path.join("max_energy_range_uj")).unwrap_or(0);
+            info!("  found {zone} ({name}), range {max_energy_range_uj}");
+
+            domains.push(RaplDomain {
+                energy_path,
+                max_energy_range_uj,
+                last: None,
+            });
+        }
+
+        domains
+    }
+
+    /// Power in watts since previous call, None on first call or read failure
+    fn read_watts(&mut self) -> Option<f64> {
+        let energy = read_u64(&self.energy_path)?;
+        let now = Instant::now();
+
+        let watts = self.last.and_then(|(last_energy, last_time)| {
+            let elapsed = now.duration_since(last_time).as_secs_f64();
+            if elapsed <= 0.0 {
+                return None;
+            }
+            let delta = energy_delta(last_energy, energy, self.max_energy_range_uj);
+            Some(delta as f64 / 1_000_000.0 / elapsed)
+        });
+
+        self.last = Some((energy, now));
+        watts
+    }
+}
+
+/// µJ used between two readings of a counter that wraps to 0 after `max_energy_range_uj`
+fn energy_delta(last_energy: u64, energy: u64, max_energy_range_uj: u64) -> u64 {
+    if energy >= last_energy {
+        energy - last_energy
+    } else {
+        // Counter wrapped around
+        max_energy_range_uj.saturating_sub(last_energy) + energy
+    }
+}
+
+fn read_trimmed<P: AsRef<Path>>(path: P) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_u64<P: AsRef<Path>>(path: P) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+/// Battery discharge rate in watts, None if not discharging or not reported
+fn battery_discharge_watts(batteries: &[PathBuf]) -> Option<f64> {
+    let mut total: Option<f64> = None;
+
+    for battery in batteries {
+        if read_trimmed(battery.join("status")).as_deref() != Some("Discharging") {
+            continue;
+        }
+
+        // power_now is in µW, some batteries only report current_now (µA) and voltage_now (µV)
+        let micro_watts = read_u64(battery.join("power_now")).or_else(|| {
+            let current = read_u64(battery.join("current_now"))?;
+            let voltage = read_u64(battery.join("voltage_now"))?;
+            Some(current.saturating_mul(voltage) / 1_000_000)
+        });
+
+        if let Some(uw) = micro_watts {
+            *total.get_or_insert(0.0) += uw as f64 / 1_000_000.0;
+        }
+    }
+
+    total
+}
+
+#[derive(Debug)]
+pub struct Power {
+    rapl: Vec<RaplDomain>,
+    batteries: Vec<PathBuf>,
+    gpu_watts: Option<f64>,
+    cpu_watts: Option<f64>,
+    battery_watts: Option<f64>,
+    samples_total: BoundedVecDeque<f64>,
+    samples_cpu: BoundedVecDeque<f64>,
+    samples_gpu: BoundedVecDeque<f64>,
+    samples_battery: BoundedVecDeque<f64>,
+    graph_options: &'static super::GraphOptions,
+    variant_options: Vec<&'static str>,
+    /// colors cached so we don't need to convert to string every time
+    svg_colors: SvgColors,
+    display: DisplayOptions,
+    config: PowerConfig,
+}
+
+impl DemoGraph for Power {
+    fn demo(&self) -> String {
+        match self.config.chart {
+            ChartKind::Ring => crate::svg_graph::ring("24", 40, None, &self.svg_colors),
+            ChartKind::Line => crate::svg_graph::line(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                60.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            ChartKind::Gauge => {
+                crate::svg_graph::gauge(&[0.4], &self.svg_colors, ChartSize::SQUARE)
+            }
+            ChartKind::Sparkline => crate::svg_graph::sparkline(
+                &std::collections::VecDeque::from(DEMO_SAMPLES),
+                None,
+                DEMO_SAMPLES.len(),
+                60.0,
+                &self.svg_colors,
+                ChartSize::SQUARE,
+            ),
+            _ => {
+                log::error!("Graph type {:?} not supported for power", self.config.chart);
+                INVALID_IMG.to_string()
+            }
+        }
+    }
+
+    fn colors(&self) -> &ChartColors {
+        self.config.colors()
+    }
+
+    fn set_colors(&mut self, colors: &ChartColors) {
+        *self.config.colors_mut() = *colors;
+        self.svg_colors.set_colors(colors);
+    }
+
+    fn color_choices(&self) -> Vec<(&'static str, ColorVariant)> {
+        if self.config.chart == ChartKind::Sparkline {
+            (*super::COLOR_CHOICES_SPARKLINE).into()
+        } else if matches!(self.config.chart, ChartKind::Line | ChartKind::Gauge) {
+            (*super::COLOR_CHOICES_LINE).into()
+        } else {
+            (*super::COLOR_CHOICES_RING).into()
+        }
+    }
+
+    fn id(&self) -> Option<String> {
+        None
+    }
+
+    fn kind(&self) -> ChartKind {
+        self.config.chart
+    }
+}
+
+impl Sensor for Power {
+    fn update_config(&mut self, config: &dyn Any, _refresh_rate: u32, display: DisplayOptions) {
+        if let Some(cfg) = config.downcast_ref::<PowerConfig>() {
+            self.config = cfg.clone();
+            self.display = display;
+            self.svg_colors.set_colors(cfg.colors());
+        }
+    }
+
+    fn graph_kind(&self) -> ChartKind {
+        self.config.chart
+    }
+
+    fn set_graph_kind(&mut self, kind: ChartKind) {
+        assert!(self.graph_options.contains(kind));
+        self.config.chart = kind;
+    }
+
+    fn update(&mut self) {
+        self.cpu_watts = self
+            .rapl
+            .iter_mut()
+            .filter_map(RaplDomain::read_watts)
+            .reduce(|a, b| a + b);
+        self.battery_watts = battery_discharge_watts(&self.batteries);
+
+        self.samples_cpu.push_back(self.cpu_watts.unwrap_or(0.0));
+        self.samples_gpu.push_back(self.gpu_watts.unwrap_or(0.0));
+        self.samples_battery
+            .push_back(self.battery_watts.unwrap_or(0.0));
+        let total = self.total_watts().unwrap_or(0.0);
+        self.samples_total.push_back(total);
+    }
+
+    fn demo_graph(&self) -> Box<dyn DemoGraph> {
+        let mut dmo = Power::default();
+        dmo.update_config(&self.config, 0, self.display);
+        Box::new(dmo)
+    }
+
+    fn chart(
+        &'_ self,
+        height_hint: u16,
+        width_hint: u16,
+    ) -> cosmic::widget::Container<'_, crate::app::Message, cosmic::Theme, cosmic::Renderer> {
+        let size = ChartSize::new(width_hint, height_hint);
+        let max = self.scale_max();
+        match self.config.chart {
+            ChartKind::Gauge => {
+                return super::gauge_container(
+                    &[self.latest_sample() / max],
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    self.display.canvas,
+                );
+            }
+            ChartKind::Sparkline => {
+                return super::sparkline_container(
+                    self.samples(),
+                    None,
+                    MAX_SAMPLES,
+                    max,
+                    self.config.colors(),
+                    &self.svg_colors,
+                    size,
+                    self.display.canvas,
+                );
+            }
+            _ => (),
+        }
+        let canvas = self.display.canvas;
+        let svg = if self.config.chart == ChartKind::Ring {
+            let latest = self.latest_sample();
+            let mut value = String::with_capacity(10);
+
+            if latest < 10.0 {
+                let _ = write!(value, "{latest:.1}");
+            } else {
+                let _ = write!(value, "{}", latest.round());
+            }
+
+            let percentage = ((latest / max) * 100.0).round().clamp(0.0, 100.0) as u8;
+            if canvas {
+                return super::canvas_container(RingChart::new(
+                    &value,
+                    percentage,
+                    None,
+                    self.config.colors(),
+                ));
+            }
+            crate::svg_graph::ring(&value, percentage, None, &self.svg_colors)
+        } else {
+            let grid = self
+                .config
+                .grid_visible()
+                .then(|| format!("{}W", max.round()));
+            if canvas {
+                return super::canvas_container(
+                    LineChart::new(self.samples(), max, self.config.colors()).grid(grid),
+                );
+            }
+            crate::svg_graph::with_grid(
+                crate::svg_graph::line(self.samples(), max, &self.svg_colors, size),
+                max,
+                false,
+                grid.as_deref(),
+                &self.svg_colors,
+                size,
+            )
+        };
+        super::svg_icon_container::<Message>(svg)
+    }
+
+    fn settings_ui(&'_ self) -> Element<'_, crate::app::Message> {
+        let theme = cosmic::theme::active();
+        let cosmic = theme.cosmic();
+
+        let mut power_elements = Vec::new();
+
+        let mut sources = column!(
+            cosmic::widget::text::body(self.to_string(false))
+                .width(90)
+                .align_x(Alignment::Center)
+        );
+        for (label, watts) in [
+            (fl!("power-cpu"), self.cpu_watts),
+            (fl!("power-gpu"), self.gpu_watts),
+            (fl!("power-battery"), self.battery_watts),
+        ] {
+            if let Some(w) = watts {
+                sources = sources.push(
+                    cosmic::widget::text::caption(format!("{label} {}", format_watts(w, false)))
+                        .width(90)
+                        .align_x(Alignment::Center),
+                );
+            }
+        }
+
+        power_elements.push(Element::from(
+            column!(
+                Container::new(self.chart(60, 60).width(60).height(60))
+                    .width(90)
+                    .align_x(Alignment::Center),
+                sources
+            )
+            .padding(5)
+            .align_x(Alignment::Center),
+        ));
+
+        let config = &self.config;
+        let selected = self.graph_options.index(self.graph_kind());
+        let graph_options = self.graph_options;
+        let selected_variant: Option<usize> = Some(config.variant.into());
+        let power_kind = self.graph_kind();
+
+        let max_watts = config.max_watts;
+        let max_input = widget::text_input(fl!("power-max-adaptive"), {
+            if max_watts == 0 {
+                String::new()
+            } else {
+                max_watts.to_string()
+            }
+        })
+        .width(100)
+        .on_input(move |s| {
+            let watts = if s.is_empty() {
+                0
+            } else {
+                s.parse::<u32>().unwrap_or(max_watts)
+            };
+            Message::PowerMaxWattsChanged(watts)
+        });
+
+        power_elements.push(Element::from(
+            column!(
+                settings::item(
+                    fl!("enable-chart"),
+                    toggler(config.chart_visible()).on_toggle(Message::TogglePowerChart),
+                ),
+                settings::item(
+                    fl!("enable-value"),
+                    toggler(config.value_visible()).on_toggle(Message::TogglePowerValue),
+                ),
+                settings::item(
+                    fl!("enable-label"),
+                    toggler(config.label_visible()).on_toggle(Message::TogglePowerLabel),
+                ),
+                settings::item(
+                    fl!("enable-icon"),
+                    toggler(config.icon_visible()).on_toggle(Message::TogglePowerIcon),
+                ),
+                settings::item(
+                    fl!("power-source"),
+                    widget::dropdown(&self.variant_options, selected_variant, |m| {
+                        Message::SelectPowerVariant(m.into())
+                    })
+                ),
+                settings::item(fl!("power-max"), max_input),
+                crate::template::settings_ui(&config.template, |t| {
+                    Message::TemplateChanged(DeviceKind::Power, t)
+                }),
+                settings::item(
+                    fl!("show-grid"),
+                    toggler(config.grid_visible())
+                        .on_toggle(|t| Message::ToggleGrid(DeviceKind::Power, t)),
+                ),
+                row!(
+                    widget::text::body(fl!("chart-type")),
+                    widget::dropdown(graph_options.names(), selected, move |m| {
+                        Message::SelectGraphType(DeviceKind::Power, graph_options.kind(m))
+                    },)
+                    .width(70),
+                    widget::space::horizontal(),
+                    widget::button::standard(fl!("change-colors")).on_press(
+                        Message::ColorPickerOpen(DeviceKind::Power, power_kind, None)
+                    ),
+                )
+                .align_y(Center)
+            )
+            .spacing(cosmic.space_xs()),
+        ));
+
+        column!(
+            Row::with_children(power_elements)
+                .align_y(Alignment::Center)
+                .spacing(0),
+            widget::text::caption(fl!("power-explanation"))
+        )
+        .spacing(cosmic.space_xs())
+        .into()
+    }
+}
+
+impl Default for Power {
+    fn default() -> Self {
+        let samples =
+            || BoundedVecDeque::from_iter(std::iter::repeat_n(0.0, MAX_SAMPLES), MAX_SAMPLES);
+
+        let mut power = Power {
+            rapl: Vec::new(),
+            batteries: Vec::new(),
+            gpu_watts: None,
+            cpu_watts: None,
+            battery_watts: None,
+            samples_total: samples(),
+            samples_cpu: samples(),
+            samples_gpu: samples(),
+            samples_battery: samples(),
+            graph_options: &super::GRAPH_OPTIONS_RING_LINE,
+            variant_options: POWER_VARIANT_OPTIONS.to_vec(),
+            svg_colors: SvgColors::new(&ChartColors::default()),
+            display: DisplayOptions::default(),
+            config: PowerConfig::default(),
+        };
+        power.set_colors(&ChartColors::default());
+        power
+    }
+}
+
+impl Power {
+    pub fn new() -> Self {
+        Power {
+            rapl: RaplDomain::find_domains(),
+            batteries: super::battery::sysfs_batteries(),
+            ..Default::default()
+        }
+    }
+
+    /// True if at least one power source can be read
+    pub fn is_found(&self) -> bool {
+        !self.rapl.is_empty() || !self.batteries.is_empty() || self.gpu_watts.is_some()
+    }
+
+    /// Set the summed GPU board power, read through the GPU backends
+    pub fn set_gpu_power(&mut self, watts: Option<f64>) {
+        self.gpu_watts = watts;
+    }
+
+    /// Estimated total system draw. On battery the discharge rate covers the whole system,
+    /// otherwise it's the sum of the CPU package(s) and GPUs.
+    pub fn total_watts(&self) -> Option<f64> {
+        if self.battery_watts.is_some() {
+            return self.battery_watts;
+        }
+        match (self.cpu_watts, self.gpu_watts) {
+            (None, None) => None,
+            (cpu, gpu) => Some(cpu.unwrap_or(0.0) + gpu.unwrap_or(0.0)),
+        }
+    }
+
+    fn samples(&self) -> &BoundedVecDeque<f64> {
+        match self.config.variant {
+            PowerVariant::Total => &self.samples_total,
+            PowerVariant::Cpu => &self.samples_cpu,
+            PowerVariant::Gpu => &self.samples_gpu,
+            PowerVariant::Battery => &self.samples_battery,
+        }
+    }
+
+    pub fn latest_sample(&self) -> f64 {
+        *self.samples().back().unwrap_or(&0f64)
+    }
+
+    /// Configured max, or the highest value in the sample window
+    fn scale_max(&self) -> f64 {
+        if self.config.max_watts > 0 {
+            f64::from(self.config.max_watts)
+        } else {
+            self.samples()
+                .iter()
+                .copied()
+                .fold(ADAPTIVE_MIN_WATTS, f64::max)
+        }
+    }
+
+    pub fn to_string(&self, vertical_panel: bool) -> String {
+        format_watts(self.latest_sample(), vertical_panel)
+    }
+}
+
+fn format_watts(watts: f64, vertical_panel: bool) -> String {
+    let unit = if vertical_panel { "W" } else { " W" };
+    let value = if watts < 10.0 {
+        crate::locale::format_number((watts * 100.0).trunc() / 100.0, 2)
+    } else if watts < 100.0 {
+        crate::locale::format_number((watts * 10.0).trunc() / 10.0, 1)
+    } else {
+        crate::locale::format_number(watts.round(), 0)
+    };
+    format!("{value}{unit}")
+}
+
+const DEMO_SAMPLES: [f64; 21] = [
+    8.2, 9.1, 12.4, 11.8, 10.3, 24.6, 31.2, 28.7, 18.4, 14.2, 12.9, 13.3, 35.8, 42.1, 38.6, 22.4,
+    16.5, 12.1, 10.8, 9.9, 11.2,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn energy_counter() {
+        assert_eq!(energy_delta(1_000, 5_000, 262_143_328_850), 4_000);
+        assert_eq!(energy_delta(5_000, 5_000, 262_143_328_850), 0);
+    }
+
+    #[test]
+    fn wrapped_energy_counter() {
+        let max = 262_143_328_850;
+        assert_eq!(energy_delta(max - 1_000, 3_000, max), 4_000);
+        assert_eq!(energy_delta(max, 0, max), 0);
+        // 2 s at 15 W across the wrap
+        let watts = energy_delta(max - 10_000_000, 20_000_000, max) as f64 / 1_000_000.0 / 2.0;
+        assert!((watts - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unknown_range_counts_from_zero() {
+        assert_eq!(energy_delta(9_000, 2_000, 0), 2_000);
+    }
+}
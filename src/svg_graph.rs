@@ -1,12 +1,17 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::hash::Hash;
+use std::rc::Rc;
 
 use cosmic::cosmic_theme::palette::Srgba;
 
-use crate::config::ChartColors;
+use crate::config::{ChartColors, Gradient};
+use crate::render_cache::{RenderCache, cached_svg, chart_key, hash_samples};
 
 use std::fmt::Write;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SvgColors {
     pub background: String,
     pub frame: String,
@@ -14,6 +19,152 @@ pub struct SvgColors {
     pub graph1: String,
     pub graph2: String,
     pub graph3: String,
+    /// Polygon fills of the graphs, the graph color or a reference to its gradient
+    pub fill1: String,
+    pub fill2: String,
+    pub fill3: String,
+    // The `<stop>` elements of the graphs with a gradient fill
+    stops: [Option<String>; 3],
+    layers: Rc<StaticLayers>,
+}
+
+// Layers of the charts that aren't square, a few view widths for each set of colors
+const WIDE_LAYERS_CAPACITY: usize = 32;
+
+thread_local! {
+    static WIDE_LAYERS: RefCell<RenderCache<Rc<StaticLayers>>> =
+        RefCell::new(RenderCache::new(WIDE_LAYERS_CAPACITY));
+}
+
+// The parts of the charts that only depend on the background and frame colors and
+// the gradient fills, built once when the colors are set instead of on every chart
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct StaticLayers {
+    line_start: String,
+    double_line_start: String,
+    heat_start: String,
+    heat_end: String,
+    polygon_end: String,
+    frame: String,
+}
+
+impl StaticLayers {
+    // `gradients` are the definitions the graph fills refer to
+    fn new(background: &str, frame: &str, gradients: &str, fill: &str, view_width: u32) -> Self {
+        let w = view_width;
+        let right = view_width - 1;
+        StaticLayers {
+            line_start: format!(
+                "{}{background}{LINESVG_2}{frame}{LINESVG_3}",
+                linesvg_1(w, gradients)
+            ),
+            double_line_start: format!(
+                "{}{background}{DBLLINESVG_2}{frame}{DBLLINESVG_3}",
+                dbllinesvg_1(w, gradients)
+            ),
+            heat_start: format!(
+                "{}{background}{HEATSVG_2}{frame}{HEATSVG_3}{fill}{HEATSVG_4}",
+                heatsvg_1(w, gradients)
+            ),
+            heat_end: format!("{}{frame}{HEATSVG_9}", heatsvg_8(w)),
+            polygon_end: format!(r#"  {right},41 1,41"/>"#),
+            frame: format!("{}{frame}{LINESVG_FRAME_END}", linesvg_frame_start(w)),
+        }
+    }
+}
+
+/// Size of a chart in pixels, the line and heat charts stretch their view box to it.
+///
+/// The view box is always 42 high, a chart twice as wide as high gets an 84 wide one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChartSize {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl ChartSize {
+    pub const SQUARE: ChartSize = ChartSize {
+        width: VIEW_HEIGHT as u16,
+        height: VIEW_HEIGHT as u16,
+    };
+
+    pub fn new(width: u16, height: u16) -> Self {
+        ChartSize { width, height }
+    }
+
+    fn view_width(self) -> u32 {
+        if self.width == 0 || self.height == 0 {
+            return VIEW_HEIGHT;
+        }
+        let width = f64::from(VIEW_HEIGHT) * f64::from(self.width) / f64::from(self.height);
+        (width.round() as u32).max(MIN_VIEW_WIDTH)
+    }
+}
+
+const VIEW_HEIGHT: u32 = 42;
+const MIN_VIEW_WIDTH: u32 = 8;
+
+impl SvgColors {
+    // The layers for a view box `view_width` wide, the ones of a square chart are kept with
+    // the colors and the others cached by colors and width
+    fn layers(&self, view_width: u32) -> Rc<StaticLayers> {
+        if view_width == VIEW_HEIGHT {
+            return Rc::clone(&self.layers);
+        }
+        let key = chart_key("layers", |h| {
+            (
+                &self.background,
+                &self.frame,
+                &self.stops,
+                &self.fill1,
+                view_width,
+            )
+                .hash(h)
+        });
+        WIDE_LAYERS.with_borrow_mut(|cache| {
+            cache
+                .get_or_insert_with(key, || {
+                    Rc::new(StaticLayers::new(
+                        &self.background,
+                        &self.frame,
+                        &self.gradients(VERTICAL_GRADIENT),
+                        &self.fill1,
+                        view_width,
+                    ))
+                })
+                .0
+        })
+    }
+
+    /// A `<linearGradient>` for each graph with a gradient fill, running from the first
+    /// point of `line` to the second in view box coordinates
+    fn gradients(&self, line: GradientLine) -> String {
+        let ((x1, y1), (x2, y2)) = line;
+        let mut defs = String::new();
+        for (index, stops) in self.stops.iter().enumerate() {
+            if let Some(stops) = stops {
+                let _ = write!(
+                    defs,
+                    r#"<linearGradient id="fill{}" x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" gradientUnits="userSpaceOnUse">{stops}</linearGradient>"#,
+                    index + 1
+                );
+            }
+        }
+        defs
+    }
+}
+
+// Start and end of a gradient, the graph color is at the start
+type GradientLine = ((f32, f32), (f32, f32));
+
+// Line, heat and vertical gauge fills blend from the bottom of the graph area to the top
+const VERTICAL_GRADIENT: GradientLine = ((0.0, 41.0), (0.0, 1.0));
+
+// X coordinate of sample `index`, 2 apart on a square chart and spread over the width
+// on a wider one
+fn x_position(index: usize, view_width: u32) -> f32 {
+    let step = 2.0 * (view_width - 2) as f32 / (VIEW_HEIGHT - 2) as f32;
+    ((1.0 + step * index as f32) * 10.0).round() / 10.0
 }
 
 impl From<ChartColors> for SvgColors {
@@ -25,14 +176,62 @@ impl From<ChartColors> for SvgColors {
             )
         }
 
-        SvgColors {
-            background: to_hex(graph_colors.background),
-            frame: to_hex(graph_colors.frame),
-            text: to_hex(graph_colors.text),
-            graph1: to_hex(graph_colors.graph1),
-            graph2: to_hex(graph_colors.graph2),
-            graph3: to_hex(graph_colors.graph3),
+        fn gradient_stops(gradient: Option<Gradient>, color: Srgba<u8>) -> Option<String> {
+            gradient.map(|gradient| {
+                gradient
+                    .stops(color)
+                    .into_iter()
+                    .fold(String::new(), |mut acc, (offset, color)| {
+                        let _ = write!(
+                            acc,
+                            r#"<stop offset="{}%" stop-color="{}"/>"#,
+                            (offset * 100.0).round(),
+                            to_hex(color)
+                        );
+                        acc
+                    })
+            })
         }
+
+        let background = to_hex(graph_colors.background);
+        let frame = to_hex(graph_colors.frame);
+        let graph1 = to_hex(graph_colors.graph1);
+        let graph2 = to_hex(graph_colors.graph2);
+        let graph3 = to_hex(graph_colors.graph3);
+        let stops = [
+            gradient_stops(graph_colors.gradient1, graph_colors.graph1),
+            gradient_stops(graph_colors.gradient2, graph_colors.graph2),
+            gradient_stops(graph_colors.gradient3, graph_colors.graph3),
+        ];
+        let fill = |index: usize, color: &str| {
+            if stops[index].is_some() {
+                format!("url(#fill{})", index + 1)
+            } else {
+                color.to_string()
+            }
+        };
+
+        let mut colors = SvgColors {
+            fill1: fill(0, &graph1),
+            fill2: fill(1, &graph2),
+            fill3: fill(2, &graph3),
+            background,
+            frame,
+            text: to_hex(graph_colors.text),
+            graph1,
+            graph2,
+            graph3,
+            stops,
+            layers: Rc::default(),
+        };
+        colors.layers = Rc::new(StaticLayers::new(
+            &colors.background,
+            &colors.frame,
+            &colors.gradients(VERTICAL_GRADIENT),
+            &colors.fill1,
+            VIEW_HEIGHT,
+        ));
+        colors
     }
 }
 
@@ -74,6 +273,13 @@ fn clip_path_for_ram_fill(percentage: u8) -> String {
 }
 
 pub fn ring(value1: &str, percentage1: u8, percentage2: Option<u8>, color: &SvgColors) -> String {
+    let key = chart_key("ring", |h| {
+        (value1, percentage1, percentage2, color).hash(h)
+    });
+    cached_svg(key, || ring_svg(value1, percentage1, percentage2, color))
+}
+
+fn ring_svg(value1: &str, percentage1: u8, percentage2: Option<u8>, color: &SvgColors) -> String {
     let mut svg = String::with_capacity(RINGSVG_LEN);
     svg.push_str(RINGSVG_1);
     svg.push_str(&color.background);
@@ -100,9 +306,49 @@ pub fn ring(value1: &str, percentage1: u8, percentage2: Option<u8>, color: &SvgC
     svg
 }
 
+/// A sleeping GPU, a dashed outline in the shape of the chart with "zZ" instead of a value
+pub fn sleeping(ring: bool, colors: &SvgColors, size: ChartSize) -> String {
+    let key = chart_key("sleeping", |h| (ring, colors, size).hash(h));
+    cached_svg(key, || sleeping_svg(ring, colors, size))
+}
+
+fn sleeping_svg(ring: bool, colors: &SvgColors, size: ChartSize) -> String {
+    if ring {
+        format!(
+            r#"<svg viewBox="0 0 34 34" xmlns="http://www.w3.org/2000/svg">
+<circle cx="17" cy="17" r="15.9155" fill="{}" stroke="{}" stroke-width="2" stroke-dasharray="3 3"/>
+<text x="17" y="22" fill="{}" font-family="Noto Sans, sans-serif" font-size="13" text-anchor="middle">zZ</text></svg>"#,
+            colors.background, colors.frame, colors.text
+        )
+    } else {
+        let w = size.view_width();
+        format!(
+            r#"<svg width="{w}" height="42" viewBox="0 0 {w} 42" xmlns="http://www.w3.org/2000/svg">
+<rect x="1" y="1" rx="7" ry="7" width="{}" height="40" fill="{}" stroke="{}" stroke-width="1" stroke-dasharray="3 3"/>
+<text x="{}" y="27" fill="{}" font-family="Noto Sans, sans-serif" font-size="15" text-anchor="middle">zZ</text></svg>"#,
+            w - 2,
+            colors.background,
+            colors.frame,
+            w / 2,
+            colors.text
+        )
+    }
+}
+
 //stroke, dashoffset,dasharray
 
-pub fn line(samples: &VecDeque<f64>, max_y: f64, colors: &SvgColors) -> String {
+pub fn line(samples: &VecDeque<f64>, max_y: f64, colors: &SvgColors, size: ChartSize) -> String {
+    let key = chart_key("line", |h| {
+        hash_samples(h, samples);
+        (max_y.to_bits(), colors, size).hash(h);
+    });
+    cached_svg(key, || line_svg(samples, max_y, colors, size))
+}
+
+fn line_svg(samples: &VecDeque<f64>, max_y: f64, colors: &SvgColors, size: ChartSize) -> String {
+    let view_width = size.view_width();
+    let layers = colors.layers(view_width);
+
     // Generate list of coordinates for line
 
     let scaling: f32 = 40.0 / max_y as f32;
@@ -111,7 +357,7 @@ pub fn line(samples: &VecDeque<f64>, max_y: f64, colors: &SvgColors) -> String {
     let indexed_string = samples.iter().enumerate().fold(
         String::with_capacity(est_len),
         |mut acc, (index, &value)| {
-            let x = ((index * 2) + 1) as u32;
+            let x = x_position(index, view_width);
             let y = (41.0 - (scaling * value as f32)).round() as u32;
             if index > 0 {
                 acc.push(' ');
@@ -122,23 +368,17 @@ pub fn line(samples: &VecDeque<f64>, max_y: f64, colors: &SvgColors) -> String {
     );
 
     let mut svg = String::with_capacity(LINE_LEN);
-    svg.push_str(LINESVG_1);
-    svg.push_str(&colors.background);
-    svg.push_str(LINESVG_2);
-    svg.push_str(&colors.frame);
-    svg.push_str(LINESVG_3);
+    svg.push_str(&layers.line_start);
     svg.push_str(LINESVG_4);
     svg.push_str(&colors.graph1[..colors.graph1.len() - 2]);
     svg.push_str(LINESVG_5);
     svg.push_str(&indexed_string);
     svg.push_str(LINESVG_6);
-    svg.push_str(&colors.graph1);
+    svg.push_str(&colors.fill1);
     svg.push_str(LINESVG_7);
     svg.push_str(&indexed_string);
-    svg.push_str(LINESVG_8);
-    svg.push_str(LINESVG_FRAME_START);
-    svg.push_str(&colors.frame);
-    svg.push_str(LINESVG_FRAME_END);
+    svg.push_str(&layers.polygon_end);
+    svg.push_str(&layers.frame);
     svg.push_str(LINESVG_9);
 
     svg
@@ -149,7 +389,26 @@ pub fn line_stacked(
     samples_allocated: &VecDeque<f64>,
     max_y: f64,
     colors: &SvgColors,
+    size: ChartSize,
+) -> String {
+    let key = chart_key("line_stacked", |h| {
+        hash_samples(h, samples_used);
+        hash_samples(h, samples_allocated);
+        (max_y.to_bits(), colors, size).hash(h);
+    });
+    cached_svg(key, || {
+        line_stacked_svg(samples_used, samples_allocated, max_y, colors, size)
+    })
+}
+
+fn line_stacked_svg(
+    samples_used: &VecDeque<f64>,
+    samples_allocated: &VecDeque<f64>,
+    max_y: f64,
+    colors: &SvgColors,
+    size: ChartSize,
 ) -> String {
+    let view_width = size.view_width();
     let scaling: f32 = if max_y > 0.0 {
         40.0 / max_y as f32
     } else {
@@ -165,14 +424,14 @@ pub fn line_stacked(
     let est_len = n * 10;
     let mut pts_used_fwd = String::with_capacity(est_len);
     let mut pts_alloc_fwd = String::with_capacity(est_len);
-    let mut used_points: Vec<(u32, u32)> = Vec::with_capacity(n);
+    let mut used_points: Vec<(f32, u32)> = Vec::with_capacity(n);
 
     for (index, (u, a)) in samples_used
         .iter()
         .zip(samples_allocated.iter())
         .enumerate()
     {
-        let x = ((index * 2) + 1) as u32;
+        let x = x_position(index, view_width);
 
         let u_clamped = u.max(0.0).min(max_y);
         let a_clamped = a.max(0.0).min(max_y);
@@ -200,14 +459,11 @@ pub fn line_stacked(
         let _ = write!(&mut poly_band, "{x},{y}");
     }
 
+    let layers = colors.layers(view_width);
     let mut svg = String::with_capacity(LINE_LEN);
 
     // Frame + background
-    svg.push_str(LINESVG_1);
-    svg.push_str(&colors.background);
-    svg.push_str(LINESVG_2);
-    svg.push_str(&colors.frame);
-    svg.push_str(LINESVG_3);
+    svg.push_str(&layers.line_start);
 
     svg.push_str(LINESVG_4);
     svg.push_str(&colors.graph1[..colors.graph1.len() - 2]);
@@ -215,13 +471,13 @@ pub fn line_stacked(
     svg.push_str(&pts_used_fwd);
 
     svg.push_str(LINESVG_6);
-    svg.push_str(&colors.graph1);
+    svg.push_str(&colors.fill1);
     svg.push_str(LINESVG_7);
     svg.push_str(&pts_used_fwd);
-    svg.push_str(LINESVG_8);
+    svg.push_str(&layers.polygon_end);
 
     svg.push_str(r#"<polygon fill=""#);
-    svg.push_str(&colors.graph3);
+    svg.push_str(&colors.fill3);
     svg.push_str(r#"" points=""#);
     svg.push_str(&poly_band);
     svg.push_str(r#""/>"#);
@@ -231,31 +487,184 @@ pub fn line_stacked(
     svg.push_str(r#"" stroke-width="1" points=""#);
     svg.push_str(&pts_alloc_fwd);
     svg.push_str(r#""/>"#);
-    svg.push_str(LINESVG_FRAME_START);
-    svg.push_str(&colors.frame);
-    svg.push_str(LINESVG_FRAME_END);
+    svg.push_str(&layers.frame);
 
     svg.push_str(LINESVG_9);
     svg
 }
 
+/// Height of a rate above the bottom of the 40 high graph area, scaled to `max`.
+///
+/// The log scale keeps small rates visible next to a large one.
+fn rate_height(value: u64, max: u64, log_scale: bool) -> f64 {
+    if log_scale {
+        40.0 * scale_fraction(value as f64, max.max(1) as f64, true)
+    } else {
+        40.0 / max as f64 * value as f64
+    }
+}
+
+/// How far up a scale to `max` the value reaches, 0.0 at the bottom and 1.0 at the top
+pub fn scale_fraction(value: f64, max: f64, log_scale: bool) -> f64 {
+    if max <= 0.0 {
+        return 0.0;
+    }
+    if log_scale {
+        value.clamp(0.0, max).ln_1p() / max.ln_1p()
+    } else {
+        value.clamp(0.0, max) / max
+    }
+}
+
+// At most this many gridlines, and none closer to the top than GRID_TOP of the scale
+const GRID_LINES: usize = 4;
+const GRID_TOP: f64 = 0.95;
+
+/// Round values below `max_y` for gridlines.
+///
+/// On a linear scale they are 1, 2, 2.5 or 5 times a power of ten apart, on a log scale
+/// they are powers of ten, skipping some when there are too many.
+pub fn grid_ticks(max_y: f64, log_scale: bool) -> Vec<f64> {
+    if max_y.is_nan() || max_y <= 1.0 {
+        return Vec::new();
+    }
+    let top = max_y * GRID_TOP;
+
+    if log_scale {
+        let decades = top.log10().floor() as usize;
+        let every = decades.div_ceil(GRID_LINES).max(1);
+        return (1..=decades)
+            .filter(|decade| decade % every == 0)
+            .map(|decade| 10f64.powi(decade as i32))
+            .collect();
+    }
+
+    let raw = max_y / GRID_LINES as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10.0 * magnitude);
+    (1..=GRID_LINES)
+        .map(|line| line as f64 * step)
+        .take_while(|&tick| tick < top)
+        .collect()
+}
+
+/// Adds gridlines at round values of a scale up to `max_y` to a line chart, with
+/// `label` for the top of the scale in the upper left corner. Without a label the
+/// chart stays as it is.
+pub fn with_grid(
+    mut svg: String,
+    max_y: f64,
+    log_scale: bool,
+    label: Option<&str>,
+    colors: &SvgColors,
+    size: ChartSize,
+) -> String {
+    let Some(label) = label else {
+        return svg;
+    };
+    let right = size.view_width() - 1;
+    let mut path = String::with_capacity(64);
+    for tick in grid_ticks(max_y, log_scale) {
+        let y = (41.0 - 40.0 * scale_fraction(tick, max_y, log_scale)).round() + 0.5;
+        let _ = write!(path, "M1 {y}H{right}");
+    }
+
+    let mut grid = String::with_capacity(256);
+    if !path.is_empty() {
+        let _ = write!(
+            grid,
+            r#"<path d="{path}" fill="none" stroke="{}" stroke-opacity="0.4" stroke-width="0.5"/>"#,
+            colors.frame
+        );
+    }
+    if !label.is_empty() {
+        let _ = write!(
+            grid,
+            r#"<text x="3" y="9" fill="{}" font-family="Noto Sans, sans-serif" font-size="8">{}</text>"#,
+            colors.frame,
+            escape_text(label)
+        );
+    }
+
+    if let Some(end) = svg.rfind("</svg>") {
+        svg.insert_str(end, &grid);
+    }
+    svg
+}
+
+// Labels come from unit names, which translations are free to fill with markup characters
+fn escape_text(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>']) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+    )
+}
+
 pub fn double_line(
     samples: &VecDeque<u64>,
     samples2: &VecDeque<u64>,
     graph_samples: usize,
     colors: &SvgColors,
     max_y: Option<u64>,
+    log_scale: bool,
+    size: ChartSize,
+) -> String {
+    let key = chart_key("double_line", |h| {
+        (
+            samples,
+            samples2,
+            graph_samples,
+            colors,
+            max_y,
+            log_scale,
+            size,
+        )
+            .hash(h)
+    });
+    cached_svg(key, || {
+        double_line_svg(
+            samples,
+            samples2,
+            graph_samples,
+            colors,
+            max_y,
+            log_scale,
+            size,
+        )
+    })
+}
+
+fn double_line_svg(
+    samples: &VecDeque<u64>,
+    samples2: &VecDeque<u64>,
+    graph_samples: usize,
+    colors: &SvgColors,
+    max_y: Option<u64>,
+    log_scale: bool,
+    size: ChartSize,
 ) -> String {
     assert!(samples.len() == samples2.len());
 
+    let view_width = size.view_width();
+
     let len = samples.len();
 
     let start = len.saturating_sub(graph_samples);
 
+    // Scaled to the samples drawn, like the canvas chart
     let max = max_y.unwrap_or_else(|| {
         let calculated_max = samples
             .iter()
-            .chain(samples2.iter())
+            .skip(start)
+            .chain(samples2.iter().skip(start))
             .copied()
             .max()
             .unwrap_or(40);
@@ -264,7 +673,6 @@ pub fn double_line(
 
     // Generate list of coordinates for line
     let est_len = (samples.len() - start) * 10;
-    let scaling: f64 = 40.0 / max as f64;
     let (indexed_string, indexed_string2) = samples
         .iter()
         .skip(start)
@@ -276,21 +684,18 @@ pub fn double_line(
                 String::with_capacity(est_len),
             ),
             |(mut acc1, mut acc2), (index, (&value1, &value2))| {
-                let x = ((index * 2) + 1) as u32;
-                let y1 = (41.0 - (scaling * value1 as f64)).round() as u32;
-                let y2 = (41.0 - (scaling * value2 as f64)).round() as u32;
+                let x = x_position(index, view_width);
+                let y1 = (41.0 - rate_height(value1, max, log_scale)).round() as u32;
+                let y2 = (41.0 - rate_height(value2, max, log_scale)).round() as u32;
                 let _ = write!(&mut acc1, "{x},{y1} ");
                 let _ = write!(&mut acc2, "{x},{y2} ");
                 (acc1, acc2)
             },
         );
 
+    let layers = colors.layers(view_width);
     let mut svg = String::with_capacity(DBLLINESVG_LEN);
-    svg.push_str(DBLLINESVG_1);
-    svg.push_str(&colors.background);
-    svg.push_str(DBLLINESVG_2);
-    svg.push_str(&colors.frame);
-    svg.push_str(DBLLINESVG_3);
+    svg.push_str(&layers.double_line_start);
 
     //First graph and polygon
     svg.push_str(DBLLINESVG_4);
@@ -298,10 +703,10 @@ pub fn double_line(
     svg.push_str(DBLLINESVG_5);
     svg.push_str(&indexed_string);
     svg.push_str(DBLLINESVG_6);
-    svg.push_str(&colors.graph1);
+    svg.push_str(&colors.fill1);
     svg.push_str(DBLLINESVG_7);
     svg.push_str(&indexed_string);
-    svg.push_str(DBLLINESVG_8);
+    svg.push_str(&layers.polygon_end);
 
     //Second graph and polygon
     svg.push_str(DBLLINESVG_4);
@@ -309,13 +714,11 @@ pub fn double_line(
     svg.push_str(DBLLINESVG_5);
     svg.push_str(&indexed_string2);
     svg.push_str(DBLLINESVG_6);
-    svg.push_str(&colors.graph2);
+    svg.push_str(&colors.fill2);
     svg.push_str(DBLLINESVG_7);
     svg.push_str(&indexed_string2);
-    svg.push_str(DBLLINESVG_8);
-    svg.push_str(LINESVG_FRAME_START);
-    svg.push_str(&colors.frame);
-    svg.push_str(LINESVG_FRAME_END);
+    svg.push_str(&layers.polygon_end);
+    svg.push_str(&layers.frame);
 
     svg.push_str(DBLLINESVG_9);
 
@@ -327,34 +730,50 @@ pub fn line_adaptive(
     graph_samples: usize,
     colors: &SvgColors,
     max_y: Option<u64>,
+    log_scale: bool,
+    size: ChartSize,
+) -> String {
+    let key = chart_key("line_adaptive", |h| {
+        (samples, graph_samples, colors, max_y, log_scale, size).hash(h)
+    });
+    cached_svg(key, || {
+        line_adaptive_svg(samples, graph_samples, colors, max_y, log_scale, size)
+    })
+}
+
+fn line_adaptive_svg(
+    samples: &VecDeque<u64>,
+    graph_samples: usize,
+    colors: &SvgColors,
+    max_y: Option<u64>,
+    log_scale: bool,
+    size: ChartSize,
 ) -> String {
+    let view_width = size.view_width();
     let len = samples.len();
     let start = len.saturating_sub(graph_samples);
 
+    // Scaled to the samples drawn, like the canvas chart
     let max = max_y.unwrap_or_else(|| {
-        let calculated_max = samples.iter().copied().max().unwrap_or(40);
+        let calculated_max = samples.iter().skip(start).copied().max().unwrap_or(40);
         std::cmp::max(40, calculated_max) // Ensure min value is 40
     });
 
     // Generate list of coordinates for line
     let est_len = (samples.len() - start) * 10;
-    let scaling: f64 = 40.0 / max as f64;
     let indexed_string = samples.iter().skip(start).enumerate().fold(
         String::with_capacity(est_len),
         |mut acc, (index, &value)| {
-            let x = ((index * 2) + 1) as u32;
-            let y = (41.0 - (scaling * value as f64)).round() as u32;
+            let x = x_position(index, view_width);
+            let y = (41.0 - rate_height(value, max, log_scale)).round() as u32;
             let _ = write!(&mut acc, "{x},{y} ");
             acc
         },
     );
 
+    let layers = colors.layers(view_width);
     let mut svg = String::with_capacity(DBLLINESVG_LEN);
-    svg.push_str(DBLLINESVG_1);
-    svg.push_str(&colors.background);
-    svg.push_str(DBLLINESVG_2);
-    svg.push_str(&colors.frame);
-    svg.push_str(DBLLINESVG_3);
+    svg.push_str(&layers.double_line_start);
 
     //First graph and polygon
     svg.push_str(DBLLINESVG_4);
@@ -362,20 +781,29 @@ pub fn line_adaptive(
     svg.push_str(DBLLINESVG_5);
     svg.push_str(&indexed_string);
     svg.push_str(DBLLINESVG_6);
-    svg.push_str(&colors.graph1);
+    svg.push_str(&colors.fill1);
     svg.push_str(DBLLINESVG_7);
     svg.push_str(&indexed_string);
-    svg.push_str(DBLLINESVG_8);
-    svg.push_str(LINESVG_FRAME_START);
-    svg.push_str(&colors.frame);
-    svg.push_str(LINESVG_FRAME_END);
+    svg.push_str(&layers.polygon_end);
+    svg.push_str(&layers.frame);
 
     svg.push_str(DBLLINESVG_9);
 
     svg
 }
 
-pub fn heat(samples: &VecDeque<f64>, max_y: u64, colors: &SvgColors) -> String {
+pub fn heat(samples: &VecDeque<f64>, max_y: u64, colors: &SvgColors, size: ChartSize) -> String {
+    let key = chart_key("heat", |h| {
+        hash_samples(h, samples);
+        (max_y, colors, size).hash(h);
+    });
+    cached_svg(key, || heat_svg(samples, max_y, colors, size))
+}
+
+fn heat_svg(samples: &VecDeque<f64>, max_y: u64, colors: &SvgColors, size: ChartSize) -> String {
+    let view_width = size.view_width();
+    let layers = colors.layers(view_width);
+
     // Generate list of coordinates for line
 
     let scaling: f32 = 40.0 / max_y as f32;
@@ -384,7 +812,7 @@ pub fn heat(samples: &VecDeque<f64>, max_y: u64, colors: &SvgColors) -> String {
     let indexed_string = samples.iter().enumerate().fold(
         String::with_capacity(est_len),
         |mut acc, (index, &value)| {
-            let x = ((index * 2) + 1) as u32;
+            let x = x_position(index, view_width);
             let y = (41.0 - (scaling * value as f32)).round() as u32;
             if index > 0 {
                 acc.push(' ');
@@ -395,45 +823,213 @@ pub fn heat(samples: &VecDeque<f64>, max_y: u64, colors: &SvgColors) -> String {
     );
 
     let mut svg = String::with_capacity(LINE_LEN);
-    svg.push_str(HEATSVG_1);
-    svg.push_str(&colors.background);
-    svg.push_str(HEATSVG_2);
-    svg.push_str(&colors.frame);
-    svg.push_str(HEATSVG_3);
+    svg.push_str(&layers.heat_start);
     svg.push_str(&indexed_string);
-    svg.push_str(HEATSVG_8);
-    svg.push_str(&colors.frame);
-    svg.push_str(HEATSVG_9);
+    svg.push_str(&layers.heat_end);
 
     svg
 }
 
-const HEATSVG_1: &str = r#"<svg width="42" height="42" viewBox="0 0 42 42" xmlns="http://www.w3.org/2000/svg">
+/// A bar filled to `fractions`, 0.0 to 1.0, the first in graph1 and a second one in graph2.
+///
+/// The bars run along the longer side of the chart, filling from the left or the bottom.
+pub fn gauge(fractions: &[f64], colors: &SvgColors, size: ChartSize) -> String {
+    let key = chart_key("gauge", |h| {
+        hash_samples(h, fractions);
+        (colors, size).hash(h);
+    });
+    cached_svg(key, || gauge_svg(fractions, colors, size))
+}
+
+fn gauge_svg(fractions: &[f64], colors: &SvgColors, size: ChartSize) -> String {
+    let view_width = size.view_width();
+    let (width, height) = (view_width as f32, VIEW_HEIGHT as f32);
+    let horizontal = width > height;
+    let (long, short) = if horizontal {
+        (width, height)
+    } else {
+        (height, width)
+    };
+
+    // Each bar is a third of the short side thick, all of them centered
+    let bars = fractions.len().max(1) as f32;
+    let thickness = (short / 3.0).round().max(GAUGE_MIN_THICKNESS);
+    let total = bars * thickness + (bars - 1.0) * GAUGE_GAP;
+    let first = ((short - total) / 2.0).max(0.0);
+
+    let mut svg = String::with_capacity(GAUGE_LEN);
+    let _ = write!(
+        svg,
+        r#"<svg width="{view_width}" height="42" viewBox="0 0 {view_width} 42" xmlns="http://www.w3.org/2000/svg">"#
+    );
+
+    // Gradients run along the bars, from the empty end to the full one
+    let gradient_line = if horizontal {
+        ((2.5, 0.0), (long - 2.5, 0.0))
+    } else {
+        ((0.0, long - 2.5), (0.0, 2.5))
+    };
+    let gradients = colors.gradients(gradient_line);
+    if !gradients.is_empty() {
+        let _ = write!(svg, "<defs>{gradients}</defs>");
+    }
+
+    for (index, fraction) in fractions.iter().enumerate() {
+        let (color, fill) = if index == 0 {
+            (&colors.graph1, &colors.fill1)
+        } else {
+            (&colors.graph2, &colors.fill2)
+        };
+        // Solid fills are drawn without the alpha of the graph color
+        let fill = if fill == color {
+            &color[..color.len() - 2]
+        } else {
+            fill.as_str()
+        };
+        let across = first + index as f32 * (thickness + GAUGE_GAP);
+        let filled = (long - 5.0) * fraction.max(0.0).min(1.0) as f32;
+
+        // Track, then the fill inset by the frame
+        let (x, y, w, h) = if horizontal {
+            (1.0, across, long - 2.0, thickness)
+        } else {
+            (across, 1.0, thickness, long - 2.0)
+        };
+        let _ = write!(
+            svg,
+            r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" rx="2" ry="2" fill="{}" stroke="{}" stroke-width="1"/>"#,
+            colors.background, colors.frame
+        );
+        if filled > 0.0 {
+            let (x, y, w, h) = if horizontal {
+                (2.5, across + 1.5, filled, thickness - 3.0)
+            } else {
+                (across + 1.5, long - 2.5 - filled, thickness - 3.0, filled)
+            };
+            let _ = write!(
+                svg,
+                r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" rx="1" ry="1" fill="{fill}"/>"#
+            );
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+const GAUGE_MIN_THICKNESS: f32 = 6.0;
+const GAUGE_GAP: f32 = 2.0;
+const GAUGE_LEN: usize = 400; // For preallocation
+
+/// Just the line of the last `graph_samples` samples, no background, frame or fill,
+/// small enough to sit next to the value. A second line in graph2 if there are `samples2`.
+pub fn sparkline(
+    samples: &VecDeque<f64>,
+    samples2: Option<&VecDeque<f64>>,
+    graph_samples: usize,
+    max_y: f64,
+    colors: &SvgColors,
+    size: ChartSize,
+) -> String {
+    let key = chart_key("sparkline", |h| {
+        hash_samples(h, samples);
+        if let Some(samples2) = samples2 {
+            hash_samples(h, samples2);
+        }
+        (graph_samples, max_y.to_bits(), colors, size).hash(h);
+    });
+    cached_svg(key, || {
+        sparkline_svg(samples, samples2, graph_samples, max_y, colors, size)
+    })
+}
+
+fn sparkline_svg(
+    samples: &VecDeque<f64>,
+    samples2: Option<&VecDeque<f64>>,
+    graph_samples: usize,
+    max_y: f64,
+    colors: &SvgColors,
+    size: ChartSize,
+) -> String {
+    let view_width = size.view_width();
+    let scaling = if max_y > 0.0 { 40.0 / max_y } else { 0.0 };
+
+    let points = |samples: &VecDeque<f64>| {
+        let start = samples.len().saturating_sub(graph_samples);
+        samples.iter().skip(start).enumerate().fold(
+            String::with_capacity(graph_samples * 10),
+            |mut acc, (index, &value)| {
+                let x = x_position(index, view_width);
+                let y = (41.0 - scaling * value.clamp(0.0, max_y)).round() as u32;
+                if index > 0 {
+                    acc.push(' ');
+                }
+                let _ = write!(&mut acc, "{x},{y}");
+                acc
+            },
+        )
+    };
+
+    let mut svg = String::with_capacity(LINE_LEN);
+    let _ = write!(
+        svg,
+        r#"<svg width="{view_width}" height="42" viewBox="0 0 {view_width} 42" xmlns="http://www.w3.org/2000/svg">"#
+    );
+    let lines = std::iter::once((samples, &colors.graph1))
+        .chain(samples2.map(|samples2| (samples2, &colors.graph2)));
+    for (samples, color) in lines {
+        let _ = write!(
+            svg,
+            r#"<polyline fill="none" stroke="{}" stroke-width="2" stroke-linejoin="round" stroke-linecap="round" points="{}"/>"#,
+            &color[..color.len() - 2],
+            points(samples)
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+// Ends at the background color placeholder
+fn heatsvg_1(w: u32, gradients: &str) -> String {
+    format!(
+        r#"<svg width="{w}" height="42" viewBox="0 0 {w} 42" xmlns="http://www.w3.org/2000/svg">
   <defs>
-  <linearGradient id="temp-gradient" x1="0" y1="42" x2="0" y2="0" gradientUnits="userSpaceOnUse">
-      <stop offset="0%" stop-color="orange"/>
-      <stop offset="90%" stop-color="red"/>
-    </linearGradient>
+    {gradients}
     <clipPath id="rounded-clip">
-      <rect x="0" y="0" width="42" height="42" rx="7" ry="7"/>
+      <rect x="0" y="0" width="{w}" height="42" rx="7" ry="7"/>
     </clipPath>
   </defs>
   <g clip-path="url(#rounded-clip)">
-    <rect x="0" y="0" rx="7" ry="7" width="42" height="42" fill=""#; // background color placeholder
+    <rect x="0" y="0" rx="7" ry="7" width="{w}" height="42" fill=""#
+    )
+}
 
 const HEATSVG_2: &str = r#"" stroke=""#; // frame color placeholder
-const HEATSVG_3: &str = r#""/><polygon fill="url(#temp-gradient)" points=""#;
-const HEATSVG_8: &str = r#"  41,41 1,41"/><rect x="0" y="0" rx="7" ry="7" width="42" height="42" fill="rgba(0,0,0,0)" stroke=""#;
+const HEATSVG_3: &str = r#""/><polygon fill=""#; // graph fill placeholder
+const HEATSVG_4: &str = r#"" points=""#;
+// Closes the graph polygon, ends at the frame color placeholder
+fn heatsvg_8(w: u32) -> String {
+    let right = w - 1;
+    format!(
+        r#"  {right},41 1,41"/><rect x="0" y="0" rx="7" ry="7" width="{w}" height="42" fill="rgba(0,0,0,0)" stroke=""#
+    )
+}
 const HEATSVG_9: &str = r#""/></g></svg>"#;
 
-const LINESVG_1: &str = r#"<svg width="42" height="42" viewBox="0 0 42 42" xmlns="http://www.w3.org/2000/svg">
+// Ends at the background color placeholder
+fn linesvg_1(w: u32, gradients: &str) -> String {
+    format!(
+        r#"<svg width="{w}" height="42" viewBox="0 0 {w} 42" xmlns="http://www.w3.org/2000/svg">
 <defs>
+  {gradients}
   <clipPath id="rounded-clip">
-    <rect x="0" y="0" width="42" height="42" rx="7" ry="7"/>
+    <rect x="0" y="0" width="{w}" height="42" rx="7" ry="7"/>
   </clipPath>
 </defs>
 <g clip-path="url(#rounded-clip)">
-  <rect x="0" y="0" rx="7" ry="7" width="42" height="42" fill=""#; // background color placeholder
+  <rect x="0" y="0" rx="7" ry="7" width="{w}" height="42" fill=""#
+    )
+}
 
 const LINESVG_2: &str = r#"" stroke=""#; // frame color placeholder
 const LINESVG_3: &str = r#""/>"#;
@@ -441,11 +1037,11 @@ const LINESVG_4: &str = r#"<polyline fill="none" stroke=""#; // line color place
 const LINESVG_5: &str = r#"" stroke-width="1" points=""#;
 const LINESVG_6: &str = r#""/><polygon fill=""#; // polygon color placeholder
 const LINESVG_7: &str = r#"" points=""#;
-const LINESVG_8: &str = r#"  41,41 1,41"/>"#;
 const LINESVG_9: &str = r#"</g></svg>"#;
 
-const LINESVG_FRAME_START: &str =
-    r#"<rect x="0" y="0" rx="7" ry="7" width="42" height="42" fill="none" stroke=""#;
+fn linesvg_frame_start(w: u32) -> String {
+    format!(r#"<rect x="0" y="0" rx="7" ry="7" width="{w}" height="42" fill="none" stroke=""#)
+}
 const LINESVG_FRAME_END: &str = r#""/>"#;
 
 const LINE_LEN: usize = 640; // Just for preallocation
@@ -502,15 +1098,21 @@ const RINGSVG_7: &str = r#"</text></svg>"#;
 const RINGSVG_LEN: usize = 680; // For preallocation
 
 // Double Line SVG
-const DBLLINESVG_1: &str = r#"
-<svg width="42" height="42" viewBox="0 0 42 42" xmlns="http://www.w3.org/2000/svg">
+// Ends at the background color placeholder
+fn dbllinesvg_1(w: u32, gradients: &str) -> String {
+    format!(
+        r#"
+<svg width="{w}" height="42" viewBox="0 0 {w} 42" xmlns="http://www.w3.org/2000/svg">
 <defs>
+  {gradients}
   <clipPath id="rounded-clip">
-    <rect x="0" y="0" width="42" height="42" rx="7" ry="7"/>
+    <rect x="0" y="0" width="{w}" height="42" rx="7" ry="7"/>
   </clipPath>
 </defs>
 <g clip-path="url(#rounded-clip)">
-<rect x="0" y="0" width="42" height="42" rx="7" ry="7" fill=""#;
+<rect x="0" y="0" width="{w}" height="42" rx="7" ry="7" fill=""#
+    )
+}
 
 const DBLLINESVG_2: &str = r#"" stroke=""#;
 const DBLLINESVG_3: &str = r#""/>
@@ -524,8 +1126,172 @@ const DBLLINESVG_5: &str = r#"" stroke-width="1" points=""#;
 const DBLLINESVG_6: &str = r#""/>
 <polygon fill=""#;
 const DBLLINESVG_7: &str = r#"" points=""#;
-const DBLLINESVG_8: &str = r#"  41,41 1,41"/>"#;
 
 const DBLLINESVG_9: &str = r#"</g></svg>"#;
 
 const DBLLINESVG_LEN: usize = 1000; // For preallocation
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn wide_layers_cached_per_width() {
+        let colors = SvgColors::new(&ChartColors::default());
+
+        assert!(Rc::ptr_eq(&colors.layers(VIEW_HEIGHT), &colors.layers));
+
+        let wide = colors.layers(84);
+        assert!(wide.line_start.contains(r#"viewBox="0 0 84 42""#));
+        assert!(Rc::ptr_eq(&wide, &colors.layers(84)));
+
+        let wider = colors.layers(126);
+        assert!(wider.line_start.contains(r#"viewBox="0 0 126 42""#));
+        assert!(!Rc::ptr_eq(&wide, &wider));
+
+        // Other colors don't get the layers of these
+        let other = SvgColors::new(&ChartColors {
+            background: Srgba::new(1, 2, 3, 255),
+            ..Default::default()
+        });
+        assert!(!Rc::ptr_eq(&wide, &other.layers(84)));
+    }
+
+    #[test]
+    fn linear_grid_ticks() {
+        assert_eq!(grid_ticks(100.0, false), vec![25.0, 50.0, 75.0]);
+        assert_eq!(grid_ticks(1000.0, false), vec![250.0, 500.0, 750.0]);
+        assert_eq!(grid_ticks(40.0, false), vec![10.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn log_grid_ticks() {
+        // Every other decade once there are more than fit
+        assert_eq!(grid_ticks(1e6, true), vec![1e2, 1e4]);
+        assert_eq!(grid_ticks(1e9, true), vec![1e2, 1e4, 1e6, 1e8]);
+    }
+
+    #[test]
+    fn no_grid_ticks_on_empty_scales() {
+        assert!(grid_ticks(1.0, false).is_empty());
+        assert!(grid_ticks(0.5, true).is_empty());
+        assert!(grid_ticks(0.0, false).is_empty());
+        assert!(grid_ticks(f64::NAN, false).is_empty());
+        assert!(grid_ticks(f64::NAN, true).is_empty());
+    }
+
+    #[test]
+    fn grid_label_escaped() {
+        let colors = SvgColors::new(&ChartColors::default());
+        let size = ChartSize::new(42, 42);
+        let svg = with_grid(
+            "<svg></svg>".to_string(),
+            100.0,
+            false,
+            Some("<1 B&W>"),
+            &colors,
+            size,
+        );
+
+        assert!(svg.contains(">&lt;1 B&amp;W&gt;</text>"));
+    }
+
+    #[test]
+    fn same_samples_same_svg() {
+        let colors = SvgColors::new(&ChartColors::default());
+        let samples: VecDeque<f64> = (0..30).map(f64::from).collect();
+        let size = ChartSize::new(84, 42);
+
+        assert_eq!(
+            line(&samples, 100.0, &colors, size),
+            line_svg(&samples, 100.0, &colors, size)
+        );
+        assert_eq!(
+            line(&samples, 100.0, &colors, size),
+            line(&samples, 100.0, &colors, size)
+        );
+    }
+
+    // Builds every chart kind once per tick for a panel's worth of sensors, each tick
+    // with a new sample so the SVG cache misses like it does on a refresh.
+    // cargo test --release bench_charts -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_charts() {
+        const TICKS: u32 = 2000;
+        const SAMPLES: usize = 30;
+
+        let colors = SvgColors::new(&ChartColors::default());
+        let sizes = [ChartSize::SQUARE, ChartSize::new(84, 42)];
+        let mut samples: VecDeque<f64> = (0..SAMPLES).map(|i| (i * 3 % 100) as f64).collect();
+        let mut rates: VecDeque<u64> = (0..SAMPLES as u64).map(|i| i * 1000).collect();
+        let mut rates2: VecDeque<u64> = rates.iter().rev().copied().collect();
+
+        let mut timings: Vec<(&str, Duration)> = Vec::new();
+        let mut time = |name: &'static str, build: &mut dyn FnMut() -> String| {
+            let start = Instant::now();
+            std::hint::black_box(build());
+            match timings.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, total)) => *total += start.elapsed(),
+                None => timings.push((name, start.elapsed())),
+            }
+        };
+
+        for tick in 0..TICKS {
+            let value = f64::from(tick % 100);
+            samples.pop_front();
+            samples.push_back(value);
+            rates.pop_front();
+            rates.push_back(u64::from(tick) * 997);
+            rates2.pop_front();
+            rates2.push_back(u64::from(tick) * 13);
+
+            let percentage = (tick % 100) as u8;
+            time("ring", &mut || {
+                ring(&value.to_string(), percentage, None, &colors)
+            });
+            for size in sizes {
+                time("line", &mut || line(&samples, 100.0, &colors, size));
+                time("line_stacked", &mut || {
+                    line_stacked(&samples, &samples, 100.0, &colors, size)
+                });
+                time("double_line", &mut || {
+                    double_line(&rates, &rates2, 21, &colors, None, false, size)
+                });
+                time("line_adaptive", &mut || {
+                    line_adaptive(&rates, 21, &colors, None, true, size)
+                });
+                time("with_grid", &mut || {
+                    with_grid(
+                        line(&samples, 100.0, &colors, size),
+                        100.0,
+                        false,
+                        Some("100%"),
+                        &colors,
+                        size,
+                    )
+                });
+                time("heat", &mut || heat(&samples, 100, &colors, size));
+                time("gauge", &mut || gauge(&[value / 100.0], &colors, size));
+                time("sparkline", &mut || {
+                    sparkline(&samples, None, 21, 100.0, &colors, size)
+                });
+            }
+        }
+
+        let mut total = Duration::ZERO;
+        for (name, elapsed) in &timings {
+            total += *elapsed;
+            println!(
+                "{name:>14}: {:>6.2} µs per tick",
+                elapsed.as_secs_f64() * 1e6 / f64::from(TICKS)
+            );
+        }
+        println!(
+            "{:>14}: {:>6.2} µs per tick",
+            "all",
+            total.as_secs_f64() * 1e6 / f64::from(TICKS)
+        );
+    }
+}
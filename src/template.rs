@@ -0,0 +1,298 @@
+//! User defined text for the value of a panel item.
+//!
+//! A template is text with placeholders, `{cpu:.0}% {cputemp}°` or
+//! `↓{net.down} ↑{net.up}`. A placeholder names a value from `VALUES`, optionally
+//! followed by the number of decimals. `{{` and `}}` are literal braces.
+
+use std::fmt;
+
+use cosmic::Element;
+use cosmic::iced::widget::column;
+use cosmic::widget::{self, settings};
+
+use crate::app::Message;
+use crate::fl;
+
+/// The names a placeholder can refer to, and the decimals they are shown with when the
+/// placeholder has none
+pub const VALUES: &[(&str, usize)] = &[
+    ("cpu", 1),
+    ("cputemp", 0),
+    ("mem", 1),
+    ("mem.total", 1),
+    ("mem.pct", 0),
+    ("net.down", 0),
+    ("net.up", 0),
+    ("disk.read", 0),
+    ("disk.write", 0),
+    ("power", 1),
+    ("battery", 0),
+    ("gpu", 0),
+    ("gpu.temp", 0),
+    ("gpu.vram", 1),
+];
+
+// More decimals than this is a typo, not a wish
+const MAX_DECIMALS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Value { name: &'static str, decimals: usize },
+}
+
+/// A parsed template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+/// What a placeholder is replaced with
+pub enum Value {
+    /// A number, shown with the decimals of the placeholder
+    Number(f64),
+    /// Already formatted, a rate with its unit for example
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    Unclosed(usize),
+    Unopened(usize),
+    EmptyName(usize),
+    UnknownValue(String),
+    BadDecimals(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            TemplateError::Unclosed(pos) => fl!("template-unclosed", pos = pos + 1),
+            TemplateError::Unopened(pos) => fl!("template-unopened", pos = pos + 1),
+            TemplateError::EmptyName(pos) => fl!("template-empty", pos = pos + 1),
+            TemplateError::UnknownValue(name) => {
+                fl!("template-unknown", name = name.as_str())
+            }
+            TemplateError::BadDecimals(spec) => {
+                fl!("template-decimals", spec = spec.as_str())
+            }
+        };
+        f.write_str(&message)
+    }
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = source.chars().enumerate().peekable();
+
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|&(_, c)| c == '{').is_some() => text.push('{'),
+                '}' if chars.next_if(|&(_, c)| c == '}').is_some() => text.push('}'),
+                '}' => return Err(TemplateError::Unopened(pos)),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => placeholder.push(c),
+                            None => return Err(TemplateError::Unclosed(pos)),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Template::placeholder(&placeholder, pos)?);
+                }
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Template { segments })
+    }
+
+    // `name` or `name:.N`
+    fn placeholder(placeholder: &str, pos: usize) -> Result<Segment, TemplateError> {
+        let (name, spec) = match placeholder.split_once(':') {
+            Some((name, spec)) => (name.trim(), Some(spec.trim())),
+            None => (placeholder.trim(), None),
+        };
+
+        if name.is_empty() {
+            return Err(TemplateError::EmptyName(pos));
+        }
+
+        let (name, default) = VALUES
+            .iter()
+            .find(|(v, _)| *v == name)
+            .copied()
+            .ok_or_else(|| TemplateError::UnknownValue(name.to_string()))?;
+
+        let decimals = match spec {
+            None => default,
+            Some(spec) => match spec.strip_prefix('.').map(str::parse::<usize>) {
+                Some(Ok(n)) if n <= MAX_DECIMALS => n,
+                _ => return Err(TemplateError::BadDecimals(spec.to_string())),
+            },
+        };
+
+        Ok(Segment::Value { name, decimals })
+    }
+
+    /// The text with each placeholder replaced, values `lookup` doesn't know show as `--`
+    pub fn render(&self, lookup: impl Fn(&str) -> Option<Value>) -> String {
+        let mut result = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => result.push_str(text),
+                Segment::Value { name, decimals } => match lookup(name) {
+                    Some(Value::Number(value)) => {
+                        result.push_str(&crate::locale::format_number(value, *decimals));
+                    }
+                    Some(Value::Text(text)) => result.push_str(text.trim()),
+                    None => result.push_str("--"),
+                },
+            }
+        }
+        result
+    }
+
+    /// The text as wide as the values get, for a width that fits any of them. Numbers
+    /// get three digits before the point, `lookup` gives the widest text values, and
+    /// every digit is an 8.
+    pub fn widest(&self, lookup: impl Fn(&str) -> Option<Value>) -> String {
+        self.render(|name| match lookup(name) {
+            Some(Value::Number(_)) => Some(Value::Number(888.0)),
+            text => text,
+        })
+        .chars()
+        .map(|c| if c.is_ascii_digit() { '8' } else { c })
+        .collect()
+    }
+}
+
+/// Parses a configured template, an empty one means the sensor formats its own value
+pub fn configured(source: &str) -> Option<Template> {
+    if source.is_empty() {
+        None
+    } else {
+        Template::parse(source).ok()
+    }
+}
+
+/// Text input for the template of a panel item, with the parse error below it
+pub fn settings_ui<'a>(
+    source: &str,
+    on_input: impl Fn(String) -> Message + 'a,
+) -> Element<'a, Message> {
+    let hint = match Template::parse(source) {
+        Err(e) => widget::text::caption(e.to_string()),
+        Ok(_) => widget::text::caption(fl!(
+            "template-help",
+            values = VALUES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    };
+
+    column!(
+        settings::item(
+            fl!("template"),
+            widget::text_input(fl!("template-placeholder"), source.to_string())
+                .width(200)
+                .on_input(on_input),
+        ),
+        hint
+    )
+    .spacing(4)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(name: &str) -> Option<Value> {
+        match name {
+            "cpu" => Some(Value::Number(12.345)),
+            "cputemp" => Some(Value::Number(54.6)),
+            "net.down" => Some(Value::Text(" 1.2 MB/s".to_string())),
+            _ => None,
+        }
+    }
+
+    fn render(source: &str) -> String {
+        Template::parse(source).unwrap().render(values)
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(render("{{cpu}}"), "{cpu}");
+        assert_eq!(render("{{{cpu}}}"), "{12.3}");
+        assert_eq!(render("}}"), "}");
+    }
+
+    #[test]
+    fn unclosed_and_unopened() {
+        assert_eq!(Template::parse("ab{cpu"), Err(TemplateError::Unclosed(2)));
+        assert_eq!(Template::parse("{cpu} }"), Err(TemplateError::Unopened(6)));
+        assert_eq!(Template::parse("{cpu}}"), Err(TemplateError::Unopened(5)));
+    }
+
+    #[test]
+    fn empty_and_unknown_names() {
+        assert_eq!(Template::parse("x{}"), Err(TemplateError::EmptyName(1)));
+        assert_eq!(Template::parse("{ :.1}"), Err(TemplateError::EmptyName(0)));
+        assert_eq!(
+            Template::parse("{gpu.fan}"),
+            Err(TemplateError::UnknownValue("gpu.fan".to_string()))
+        );
+    }
+
+    #[test]
+    fn decimals() {
+        assert_eq!(render("{cpu}"), "12.3");
+        assert_eq!(render("{cputemp}"), "55");
+        assert_eq!(render("{cpu:.0}"), "12");
+        assert_eq!(render("{cpu : .3}"), "12.345");
+        assert_eq!(
+            Template::parse("{cpu:.x}"),
+            Err(TemplateError::BadDecimals(".x".to_string()))
+        );
+        assert_eq!(
+            Template::parse("{cpu:2}"),
+            Err(TemplateError::BadDecimals("2".to_string()))
+        );
+        assert_eq!(
+            Template::parse("{cpu:.7}"),
+            Err(TemplateError::BadDecimals(".7".to_string()))
+        );
+    }
+
+    #[test]
+    fn text_values_and_missing_ones() {
+        assert_eq!(render("↓{net.down}"), "↓1.2 MB/s");
+        assert_eq!(render("{gpu.temp}°"), "--°");
+    }
+
+    #[test]
+    fn widest() {
+        let template = Template::parse("{cpu}% {cputemp}° {net.down} {gpu}").unwrap();
+        assert_eq!(template.widest(values), "888.8% 888° 8.8 MB/s --");
+    }
+
+    #[test]
+    fn configured_template() {
+        assert_eq!(configured(""), None);
+        assert_eq!(configured("{cpu"), None);
+        assert!(configured("{cpu}").is_some());
+    }
+}
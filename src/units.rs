@@ -0,0 +1,257 @@
+//! Sizes and rates in SI (MB, powers of 1000) or IEC (MiB, powers of 1024) units.
+//!
+//! The system is a display setting handed to the sensors with their config, see
+//! `DisplayOptions`. Sensors keep their samples in plain bytes, bits or GiB and convert
+//! when they format a value.
+
+use crate::config::UnitSystem;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnitVariant {
+    Short,
+    Long,
+}
+
+/// What a rate counts
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Quantity {
+    Bits,
+    Bytes,
+}
+
+const SI_SHORT_BITS: [&str; 5] = ["b", "K", "M", "G", "T"];
+const SI_LONG_BITS: [&str; 5] = ["bps", "Kbps", "Mbps", "Gbps", "Tbps"];
+const SI_SHORT_BYTES: [&str; 5] = ["B", "K", "M", "G", "T"];
+const SI_LONG_BYTES: [&str; 5] = ["B/s", "KB/s", "MB/s", "GB/s", "TB/s"];
+const IEC_SHORT_BITS: [&str; 5] = ["b", "Ki", "Mi", "Gi", "Ti"];
+const IEC_LONG_BITS: [&str; 5] = ["bps", "Kibps", "Mibps", "Gibps", "Tibps"];
+const IEC_SHORT_BYTES: [&str; 5] = ["B", "Ki", "Mi", "Gi", "Ti"];
+const IEC_LONG_BYTES: [&str; 5] = ["B/s", "KiB/s", "MiB/s", "GiB/s", "TiB/s"];
+
+const SI_NAMES_BITS: [&str; 5] = ["b", "Kb", "Mb", "Gb", "Tb"];
+const SI_NAMES_BYTES: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+const IEC_NAMES_BITS: [&str; 5] = ["b", "Kib", "Mib", "Gib", "Tib"];
+const IEC_NAMES_BYTES: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+const GIB: f64 = 1_073_741_824.0;
+
+impl UnitSystem {
+    pub fn base(self) -> u64 {
+        match self {
+            UnitSystem::Si => 1000,
+            UnitSystem::Iec => 1024,
+        }
+    }
+
+    /// The factor of the prefix at `index` of the unit tables, 0 is no prefix
+    pub fn multiplier(self, index: usize) -> u64 {
+        self.base().pow(index.min(4) as u32)
+    }
+
+    /// Names of the units for a bandwidth setting, the index matches `multiplier`
+    pub fn unit_names(self, quantity: Quantity) -> &'static [&'static str; 5] {
+        match (self, quantity) {
+            (UnitSystem::Si, Quantity::Bits) => &SI_NAMES_BITS,
+            (UnitSystem::Si, Quantity::Bytes) => &SI_NAMES_BYTES,
+            (UnitSystem::Iec, Quantity::Bits) => &IEC_NAMES_BITS,
+            (UnitSystem::Iec, Quantity::Bytes) => &IEC_NAMES_BYTES,
+        }
+    }
+
+    fn rate_units(self, quantity: Quantity, format: UnitVariant) -> &'static [&'static str; 5] {
+        match (self, quantity, format) {
+            (UnitSystem::Si, Quantity::Bits, UnitVariant::Short) => &SI_SHORT_BITS,
+            (UnitSystem::Si, Quantity::Bits, UnitVariant::Long) => &SI_LONG_BITS,
+            (UnitSystem::Si, Quantity::Bytes, UnitVariant::Short) => &SI_SHORT_BYTES,
+            (UnitSystem::Si, Quantity::Bytes, UnitVariant::Long) => &SI_LONG_BYTES,
+            (UnitSystem::Iec, Quantity::Bits, UnitVariant::Short) => &IEC_SHORT_BITS,
+            (UnitSystem::Iec, Quantity::Bits, UnitVariant::Long) => &IEC_LONG_BITS,
+            (UnitSystem::Iec, Quantity::Bytes, UnitVariant::Short) => &IEC_SHORT_BYTES,
+            (UnitSystem::Iec, Quantity::Bytes, UnitVariant::Long) => &IEC_LONG_BYTES,
+        }
+    }
+
+    /// Memory sizes are sampled in GiB, this is the same size in GB or GiB
+    pub fn from_gib(self, gib: f64) -> f64 {
+        gib * GIB / self.multiplier(3) as f64
+    }
+
+    /// Label of the unit `from_gib` returns
+    pub fn giga(self) -> &'static str {
+        match self {
+            UnitSystem::Si => "GB",
+            UnitSystem::Iec => "GiB",
+        }
+    }
+
+    /// A size with the largest prefix that keeps it below 1000, "512 MB" or "1.5 GiB"
+    pub fn size(self, bytes: u64) -> String {
+        let names = self.unit_names(Quantity::Bytes);
+        let base = self.base() as f64;
+
+        let mut value = bytes as f64;
+        let mut unit_index = 0;
+
+        while value >= 999.5 && unit_index < names.len() - 1 {
+            value /= base;
+            unit_index += 1;
+        }
+
+        if value < 10.0 && unit_index > 0 {
+            format!(
+                "{} {}",
+                crate::locale::format_number(value, 1),
+                names[unit_index]
+            )
+        } else {
+            format!(
+                "{} {}",
+                crate::locale::format_number(value, 0),
+                names[unit_index]
+            )
+        }
+    }
+
+    /// A rate per second with the largest prefix that keeps it below 1000.
+    ///
+    /// Precision varies so the number has three or four digits, `Long` pads the
+    /// text to a stable width for the panel.
+    pub fn rate(self, value: u64, quantity: Quantity, format: UnitVariant) -> String {
+        let units = self.rate_units(quantity, format);
+        let base = self.base() as f64;
+
+        let mut value = value as f64;
+        let mut unit_index = 0;
+
+        while value >= 999.0 && unit_index < units.len() - 1 {
+            value /= base;
+            unit_index += 1;
+        }
+
+        // Format the number with varying precision, prevent the formatter from rounding up
+        let mut value_str = if value < 10.0 {
+            crate::locale::format_number((value * 100.0).trunc() / 100.0, 2)
+        } else if value < 100.0 {
+            crate::locale::format_number((value * 10.0).trunc() / 10.0, 1)
+        } else {
+            crate::locale::format_number(value.trunc(), 0)
+        };
+
+        // This happens when value is something like 9.9543456789908765453456 and it's rounded up to 10.
+        if value_str.len() == 5 {
+            log::info!("Value: {value}. formatted: {value:.2}. string: {value_str}");
+            value_str.pop();
+        }
+
+        let mut result = String::with_capacity(20);
+        result.push_str(&value_str);
+
+        if format == UnitVariant::Long {
+            result.push(' ');
+        }
+
+        result.push_str(units[unit_index]);
+
+        if format == UnitVariant::Long {
+            let padding = 9usize.saturating_sub(result.len());
+            if padding > 0 {
+                result = " ".repeat(padding) + &result;
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SI: UnitSystem = UnitSystem::Si;
+    const IEC: UnitSystem = UnitSystem::Iec;
+
+    #[test]
+    fn short_rates() {
+        assert_eq!(SI.rate(0, Quantity::Bytes, UnitVariant::Short), "0.00B");
+        assert_eq!(SI.rate(1500, Quantity::Bytes, UnitVariant::Short), "1.50K");
+        assert_eq!(
+            SI.rate(12_345_678, Quantity::Bits, UnitVariant::Short),
+            "12.3M"
+        );
+        assert_eq!(
+            IEC.rate(1536, Quantity::Bytes, UnitVariant::Short),
+            "1.50Ki"
+        );
+        assert_eq!(
+            IEC.rate(3 << 20, Quantity::Bits, UnitVariant::Short),
+            "3.00Mi"
+        );
+    }
+
+    #[test]
+    fn long_rates_are_padded() {
+        assert_eq!(SI.rate(5, Quantity::Bytes, UnitVariant::Long), " 5.00 B/s");
+        assert_eq!(
+            SI.rate(123_000_000, Quantity::Bytes, UnitVariant::Long),
+            " 123 MB/s"
+        );
+        assert_eq!(
+            SI.rate(1500, Quantity::Bytes, UnitVariant::Long),
+            "1.50 KB/s"
+        );
+        assert_eq!(
+            SI.rate(2_500_000, Quantity::Bits, UnitVariant::Long),
+            "2.50 Mbps"
+        );
+        assert_eq!(
+            IEC.rate(1536, Quantity::Bytes, UnitVariant::Long),
+            "1.50 KiB/s"
+        );
+        assert_eq!(
+            IEC.rate(1 << 30, Quantity::Bits, UnitVariant::Long),
+            "1.00 Gibps"
+        );
+    }
+
+    #[test]
+    fn rolls_over_at_999() {
+        assert_eq!(SI.rate(998, Quantity::Bytes, UnitVariant::Short), "998B");
+        assert_eq!(SI.rate(999, Quantity::Bytes, UnitVariant::Short), "0.99K");
+        assert_eq!(
+            IEC.rate(1000, Quantity::Bytes, UnitVariant::Short),
+            "0.97Ki"
+        );
+    }
+
+    #[test]
+    fn never_rounds_up() {
+        assert_eq!(SI.rate(9_999, Quantity::Bytes, UnitVariant::Short), "9.99K");
+        assert_eq!(
+            SI.rate(99_999, Quantity::Bytes, UnitVariant::Short),
+            "99.9K"
+        );
+    }
+
+    #[test]
+    fn drops_the_fifth_digit() {
+        // Past the largest prefix the number keeps growing
+        let huge = 12_345 * SI.multiplier(4);
+        assert_eq!(SI.rate(huge, Quantity::Bytes, UnitVariant::Short), "1234T");
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(SI.size(512), "512 B");
+        assert_eq!(SI.size(512_000_000), "512 MB");
+        assert_eq!(SI.size(1_500_000_000), "1.5 GB");
+        assert_eq!(IEC.size(3 << 29), "1.5 GiB");
+    }
+
+    #[test]
+    fn gib() {
+        assert_eq!(IEC.from_gib(2.0), 2.0);
+        assert!((SI.from_gib(1.0) - 1.073_741_824).abs() < 1e-9);
+        assert_eq!(SI.giga(), "GB");
+        assert_eq!(IEC.giga(), "GiB");
+    }
+}
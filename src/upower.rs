@@ -0,0 +1,359 @@
+use cosmic::iced::{
+    Subscription,
+    futures::{SinkExt, StreamExt, channel::mpsc::Sender},
+};
+use log::{error, info};
+use std::collections::HashMap;
+use zbus::{Connection, Proxy};
+use zvariant::{OwnedObjectPath, OwnedValue};
+
+use crate::{app::Message, config::PowerCondition, sensors::battery::BatteryStatus};
+
+const UPOWER_SERVICE: &str = "org.freedesktop.UPower";
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+const UPOWER_INTERFACE: &str = "org.freedesktop.UPower";
+const UPOWER_DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+// UPower device type of the AC adapter
+const UPOWER_TYPE_LINE_POWER: u32 = 1;
+
+// power-profiles-daemon moved to the UPower namespace, older versions only have the hadess name
+const POWER_PROFILES_SERVICES: [(&str, &str, &str); 2] = [
+    (
+        "org.freedesktop.UPower.PowerProfiles",
+        "/org/freedesktop/UPower/PowerProfiles",
+        "org.freedesktop.UPower.PowerProfiles",
+    ),
+    (
+        "net.hadess.PowerProfiles",
+        "/net/hadess/PowerProfiles",
+        "net.hadess.PowerProfiles",
+    ),
+];
+
+/// Emits `Message::AcStateChanged` with the current AC state and again whenever it changes
+pub fn ac_state_subscription() -> Subscription<Message> {
+    struct AcState;
+
+    Subscription::run_with_id(
+        std::any::TypeId::of::<AcState>(),
+        cosmic::iced::stream::channel(4, |mut output| async move {
+            match Connection::system().await {
+                Ok(connection) => {
+                    if let Err(e) = watch_line_power(&connection, &mut output).await {
+                        error!("UPower: watching line power failed: {e}");
+                    }
+                }
+                Err(e) => error!("UPower: could not connect to the system bus: {e}"),
+            }
+
+            // Nothing more to report, keep the subscription alive so it isn't restarted
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+/// Emits `Message::BatteryChanged` with the status of the UPower battery at `path` and
+/// again whenever it changes
+pub fn battery_subscription(path: OwnedObjectPath) -> Subscription<Message> {
+    struct Battery;
+
+    Subscription::run_with_id(
+        (std::any::TypeId::of::<Battery>(), path.to_string()),
+        cosmic::iced::stream::channel(4, move |mut output| async move {
+            match Connection::system().await {
+                Ok(connection) => {
+                    if let Err(e) = watch_battery(&connection, path, &mut output).await {
+                        error!("UPower: watching the battery failed: {e}");
+                    }
+                }
+                Err(e) => error!("UPower: could not connect to the system bus: {e}"),
+            }
+
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+/// Emits `Message::PowerProfileChanged` with the active power profile and again whenever it changes
+pub fn power_profile_subscription() -> Subscription<Message> {
+    struct PowerProfile;
+
+    Subscription::run_with_id(
+        std::any::TypeId::of::<PowerProfile>(),
+        cosmic::iced::stream::channel(4, |mut output| async move {
+            match Connection::system().await {
+                Ok(connection) => {
+                    if let Err(e) = watch_power_profile(&connection, &mut output).await {
+                        error!("PowerProfiles: watching active profile failed: {e}");
+                    }
+                }
+                Err(e) => error!("PowerProfiles: could not connect to the system bus: {e}"),
+            }
+
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+/// Forward `ActiveProfile` changes of power-profiles-daemon.
+///
+/// Returns when neither service name is available or the stream ends.
+pub async fn watch_power_profile(
+    connection: &Connection,
+    output: &mut Sender<Message>,
+) -> zbus::Result<()> {
+    let mut found = None;
+    for (service, path, interface) in POWER_PROFILES_SERVICES {
+        let proxy = Proxy::new(connection, service, path, interface).await?;
+        match proxy.get_property::<String>("ActiveProfile").await {
+            Ok(profile) => {
+                found = Some((proxy, profile));
+                break;
+            }
+            Err(e) => info!("PowerProfiles: {service} not available: {e}"),
+        }
+    }
+
+    let Some((proxy, profile)) = found else {
+        return Ok(());
+    };
+
+    if let Some(condition) = PowerCondition::from_profile_name(&profile)
+        && output
+            .send(Message::PowerProfileChanged(condition))
+            .await
+            .is_err()
+    {
+        return Ok(());
+    }
+
+    let mut changes = proxy
+        .receive_property_changed::<String>("ActiveProfile")
+        .await;
+    while let Some(change) = changes.next().await {
+        match change.get().await {
+            Ok(profile) => {
+                let Some(condition) = PowerCondition::from_profile_name(&profile) else {
+                    info!("PowerProfiles: unknown profile {profile}");
+                    continue;
+                };
+                if output
+                    .send(Message::PowerProfileChanged(condition))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Err(e) => error!("PowerProfiles: could not read ActiveProfile: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the line power device on the bus and forward its `Online` property changes.
+///
+/// Takes the connection as a parameter so it can run against a private bus.
+/// Returns when there is no line power device or the stream ends.
+pub async fn watch_line_power(
+    connection: &Connection,
+    output: &mut Sender<Message>,
+) -> zbus::Result<()> {
+    let Some(device) = find_line_power(connection).await? else {
+        info!("UPower: no line power device");
+        return Ok(());
+    };
+
+    // Report the state we start with, PropertiesChanged only tells us about changes
+    let online: bool = device.get_property("Online").await?;
+    if output.send(Message::AcStateChanged(online)).await.is_err() {
+        return Ok(());
+    }
+
+    let mut changes = device.receive_property_changed::<bool>("Online").await;
+    while let Some(change) = changes.next().await {
+        match change.get().await {
+            Ok(online) => {
+                if output.send(Message::AcStateChanged(online)).await.is_err() {
+                    break;
+                }
+            }
+            Err(e) => error!("UPower: could not read Online: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Forward the status of a battery device, read with one `GetAll` at start and after
+/// each `PropertiesChanged`, so the sensor doesn't query the bus on every tick.
+///
+/// Returns when the stream ends.
+pub async fn watch_battery(
+    connection: &Connection,
+    path: OwnedObjectPath,
+    output: &mut Sender<Message>,
+) -> zbus::Result<()> {
+    let device = Proxy::new(
+        connection,
+        UPOWER_SERVICE,
+        path.into_inner(),
+        PROPERTIES_INTERFACE,
+    )
+    .await?;
+
+    // Subscribe first so a change between the read and the subscription isn't lost
+    let mut changes = device.receive_signal("PropertiesChanged").await?;
+    loop {
+        let properties: HashMap<String, OwnedValue> =
+            device.call("GetAll", &(UPOWER_DEVICE_INTERFACE,)).await?;
+        if let Some(status) = BatteryStatus::from_upower(&properties)
+            && output.send(Message::BatteryChanged(status)).await.is_err()
+        {
+            break;
+        }
+        if changes.next().await.is_none() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+async fn find_line_power(connection: &Connection) -> zbus::Result<Option<Proxy<'static>>> {
+    let upower = Proxy::new(connection, UPOWER_SERVICE, UPOWER_PATH, UPOWER_INTERFACE).await?;
+
+    let devices: Vec<OwnedObjectPath> = upower.call("EnumerateDevices", &()).await?;
+
+    for device_path in devices {
+        let device = Proxy::new(
+            connection,
+            UPOWER_SERVICE,
+            device_path.into_inner(),
+            UPOWER_DEVICE_INTERFACE,
+        )
+        .await?;
+
+        let kind: u32 = device.get_property("Type").await?;
+        if kind == UPOWER_TYPE_LINE_POWER {
+            return Ok(Some(device));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmic::iced::futures::{channel::mpsc, future, try_join};
+    use std::os::unix::net::UnixStream;
+    use zbus::{Guid, connection, interface};
+
+    const LINE_POWER_PATH: &str = "/org/freedesktop/UPower/devices/line_power_AC";
+    const BATTERY_PATH: &str = "/org/freedesktop/UPower/devices/battery_BAT0";
+
+    struct FakeUPower;
+
+    #[interface(name = "org.freedesktop.UPower")]
+    impl FakeUPower {
+        fn enumerate_devices(&self) -> Vec<OwnedObjectPath> {
+            [BATTERY_PATH, LINE_POWER_PATH]
+                .into_iter()
+                .map(|path| OwnedObjectPath::try_from(path).unwrap())
+                .collect()
+        }
+    }
+
+    struct FakeDevice {
+        kind: u32,
+        online: bool,
+    }
+
+    #[interface(name = "org.freedesktop.UPower.Device")]
+    impl FakeDevice {
+        #[zbus(property, name = "Type")]
+        fn kind(&self) -> u32 {
+            self.kind
+        }
+
+        #[zbus(property)]
+        fn online(&self) -> bool {
+            self.online
+        }
+    }
+
+    #[test]
+    fn line_power_transitions() {
+        zbus::block_on(async {
+            let (server_stream, client_stream) = UnixStream::pair().unwrap();
+            let (server, client) = try_join!(
+                connection::Builder::unix_stream(server_stream)
+                    .server(Guid::generate())
+                    .unwrap()
+                    .p2p()
+                    .serve_at(UPOWER_PATH, FakeUPower)
+                    .unwrap()
+                    .serve_at(
+                        BATTERY_PATH,
+                        FakeDevice {
+                            kind: 2,
+                            online: false,
+                        },
+                    )
+                    .unwrap()
+                    .serve_at(
+                        LINE_POWER_PATH,
+                        FakeDevice {
+                            kind: UPOWER_TYPE_LINE_POWER,
+                            online: true,
+                        },
+                    )
+                    .unwrap()
+                    .build(),
+                connection::Builder::unix_stream(client_stream)
+                    .p2p()
+                    .build(),
+            )
+            .unwrap();
+
+            let (mut sender, mut receiver) = mpsc::channel(4);
+            let watcher = Box::pin(async { watch_line_power(&client, &mut sender).await });
+
+            let checks = Box::pin(async {
+                // The state at start, then every transition
+                let first = receiver.next().await;
+                assert!(matches!(first, Some(Message::AcStateChanged(true))));
+
+                let device = server
+                    .object_server()
+                    .interface::<_, FakeDevice>(LINE_POWER_PATH)
+                    .await
+                    .unwrap();
+                for online in [false, true] {
+                    device.get_mut().await.online = online;
+                    device
+                        .get()
+                        .await
+                        .online_changed(device.signal_emitter())
+                        .await
+                        .unwrap();
+                    let message = receiver.next().await;
+                    assert!(
+                        matches!(message, Some(Message::AcStateChanged(state)) if state == online)
+                    );
+                }
+            });
+
+            match future::select(watcher, checks).await {
+                future::Either::Left((result, _)) => {
+                    panic!("watch_line_power returned early: {result:?}")
+                }
+                future::Either::Right(((), _)) => (),
+            }
+        });
+    }
+}